@@ -0,0 +1,224 @@
+//! Measures best price discovery on the 10,000 level book used by the demo, and compares
+//! the level index against the previous linear scan over the price levels
+//!
+//! Run with `cargo bench --bench order_book_bench`
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use rapid_rust_order_book::level_index::LevelIndex;
+use rapid_rust_order_book::market::Market;
+use rapid_rust_order_book::order::{OrderType, Side};
+use rapid_rust_order_book::order_book::OrderBook;
+use rapid_rust_order_book::request::{CancelOrderRequest, SubmitOrderRequest};
+
+const SYMBOL: &str = "BTCUSDT";
+const MAX_PRICE: u64 = 10_001;
+const MIN_PRICE: u64 = 1;
+const TOTAL_ORDERS: u64 = 10_000;
+const ROUNDS: u32 = 20;
+
+/// Price level sizes scanned one level at a time, as the best price was found before the
+/// level index
+struct ScanLevels {
+    sizes: Vec<u64>,
+}
+
+impl ScanLevels {
+    fn new(capacity: usize) -> Self {
+        Self {
+            sizes: vec![0; capacity],
+        }
+    }
+
+    fn insert(&mut self, index: usize) {
+        self.sizes[index] += 1;
+    }
+
+    fn remove(&mut self, index: usize) {
+        self.sizes[index] -= 1;
+    }
+
+    fn next_at_or_above(&self, index: usize) -> Option<usize> {
+        (index..self.sizes.len()).find(|&i| self.sizes[i] > 0)
+    }
+}
+
+fn new_order_book() -> OrderBook {
+    OrderBook::new(Market::new(SYMBOL, MAX_PRICE, MIN_PRICE))
+}
+
+fn limit_order(price: u64, side: Side) -> SubmitOrderRequest {
+    SubmitOrderRequest {
+        symbol: SYMBOL.to_string(),
+        price,
+        size: 1,
+        side,
+        order_type: OrderType::Limit,
+        ..Default::default()
+    }
+}
+
+/// Fills the book with one order per price level, in the same way as `demo::add_limit_orders`
+fn add_limit_orders(order_book: &mut OrderBook) {
+    for price in 1..TOTAL_ORDERS {
+        let side = if price < TOTAL_ORDERS / 2 {
+            Side::Buy
+        } else {
+            Side::Sell
+        };
+        order_book
+            .submit_order(&mut limit_order(price, side))
+            .unwrap();
+    }
+}
+
+/// Sends the market orders of `demo::do_market_orders` to the book
+fn bench_market_orders() -> Duration {
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..ROUNDS {
+        let mut order_book = new_order_book();
+        add_limit_orders(&mut order_book);
+        let start = Instant::now();
+        for i in 0..1_000 {
+            let side = if i < 500 { Side::Buy } else { Side::Sell };
+            let res = order_book.submit_order(&mut SubmitOrderRequest {
+                symbol: SYMBOL.to_string(),
+                price: 0,
+                size: 2,
+                side,
+                order_type: OrderType::Market,
+                ..Default::default()
+            });
+            black_box(res.unwrap());
+        }
+        elapsed += start.elapsed();
+    }
+    elapsed / (ROUNDS * 1_000)
+}
+
+/// Repeatedly cancels the best bid and ask when the next level is at the far end of the book
+fn bench_cancel_top_of_sparse_book() -> Duration {
+    let mut order_book = new_order_book();
+    order_book
+        .submit_order(&mut limit_order(MIN_PRICE, Side::Buy))
+        .unwrap();
+    order_book
+        .submit_order(&mut limit_order(MAX_PRICE - 1, Side::Sell))
+        .unwrap();
+    let iterations = 10_000;
+    let start = Instant::now();
+    for _ in 0..iterations {
+        let bid_id = order_book
+            .submit_order(&mut limit_order(5_000, Side::Buy))
+            .unwrap()
+            .id;
+        let ask_id = order_book
+            .submit_order(&mut limit_order(5_001, Side::Sell))
+            .unwrap()
+            .id;
+        for id in [bid_id, ask_id] {
+            let res = order_book.cancel_order(&CancelOrderRequest {
+                symbol: SYMBOL.to_string(),
+                id,
+            });
+            black_box(res.unwrap());
+        }
+    }
+    start.elapsed() / (iterations * 2)
+}
+
+/// Takes the best level off a full side and finds the next one, as market orders do
+fn bench_index_full_book() -> Duration {
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..ROUNDS {
+        let mut levels = LevelIndex::new(TOTAL_ORDERS as usize);
+        for index in 0..TOTAL_ORDERS as usize {
+            levels.insert(index);
+        }
+        let start = Instant::now();
+        let mut best = levels.first();
+        while let Some(index) = best {
+            levels.remove(index);
+            best = black_box(levels.next_at_or_above(index));
+        }
+        elapsed += start.elapsed();
+    }
+    elapsed
+}
+
+fn bench_scan_full_book() -> Duration {
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..ROUNDS {
+        let mut levels = ScanLevels::new(TOTAL_ORDERS as usize);
+        for index in 0..TOTAL_ORDERS as usize {
+            levels.insert(index);
+        }
+        let start = Instant::now();
+        let mut best = levels.next_at_or_above(0);
+        while let Some(index) = best {
+            levels.remove(index);
+            best = black_box(levels.next_at_or_above(index));
+        }
+        elapsed += start.elapsed();
+    }
+    elapsed
+}
+
+/// Adds and removes the best level when the next one is at the far end of the side
+fn bench_index_sparse_book() -> Duration {
+    let mut levels = LevelIndex::new(TOTAL_ORDERS as usize);
+    levels.insert(TOTAL_ORDERS as usize - 1);
+    let start = Instant::now();
+    for _ in 0..TOTAL_ORDERS {
+        levels.insert(0);
+        levels.remove(0);
+        black_box(levels.next_at_or_above(0));
+    }
+    start.elapsed()
+}
+
+fn bench_scan_sparse_book() -> Duration {
+    let mut levels = ScanLevels::new(TOTAL_ORDERS as usize);
+    levels.insert(TOTAL_ORDERS as usize - 1);
+    let start = Instant::now();
+    for _ in 0..TOTAL_ORDERS {
+        levels.insert(0);
+        levels.remove(0);
+        black_box(levels.next_at_or_above(0));
+    }
+    start.elapsed()
+}
+
+fn report(name: &str, operations: u64, index: Duration, scan: Duration) {
+    println!(
+        "{:<18} index={:>8.1}ns/op scan={:>8.1}ns/op speedup={:>7.1}x",
+        name,
+        index.as_nanos() as f64 / operations as f64,
+        scan.as_nanos() as f64 / operations as f64,
+        scan.as_secs_f64() / index.as_secs_f64()
+    );
+}
+
+fn main() {
+    report(
+        "full book",
+        TOTAL_ORDERS * ROUNDS as u64,
+        bench_index_full_book(),
+        bench_scan_full_book(),
+    );
+    report(
+        "sparse book",
+        TOTAL_ORDERS,
+        bench_index_sparse_book(),
+        bench_scan_sparse_book(),
+    );
+    println!(
+        "market orders on full book    = {} ns/order",
+        bench_market_orders().as_nanos()
+    );
+    println!(
+        "cancel top of sparse book     = {} ns/order",
+        bench_cancel_top_of_sparse_book().as_nanos()
+    );
+}
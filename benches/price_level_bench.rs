@@ -0,0 +1,144 @@
+//! Compares the arena-backed linked list price level against the previous vector layout
+//!
+//! Run with `cargo bench --bench price_level_bench`
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use rapid_rust_order_book::order::{Order, OrderStatus, OrderType, Side};
+use rapid_rust_order_book::order_arena::OrderArena;
+use rapid_rust_order_book::price_level::PriceLevel;
+
+const DEPTHS: [u64; 4] = [200, 2_000, 20_000, 100_000];
+const PRICE: u64 = 100;
+
+/// Price level laid out as before: a vector of orders scanned and shifted on removal
+struct VecPriceLevel {
+    orders: Vec<Order>,
+}
+
+impl VecPriceLevel {
+    fn new() -> Self {
+        Self {
+            orders: Vec::with_capacity(200),
+        }
+    }
+
+    fn add_order(&mut self, order: Order) {
+        self.orders.push(order);
+    }
+
+    fn remove_order(&mut self, id: u64) -> bool {
+        match self.orders.iter().position(|order| order.get_id() == id) {
+            Some(index) => {
+                self.orders.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn new_order(id: u64) -> Order {
+    let mut order = Order::new(PRICE, 1, Side::Buy, OrderStatus::Open, OrderType::Limit);
+    order.set_id(id);
+    order
+}
+
+/// Cancels every other order starting from the middle of the queue
+fn cancel_ids(depth: u64) -> Vec<u64> {
+    let middle = depth / 2;
+    (0..depth / 2)
+        .map(|i| {
+            if i % 2 == 0 {
+                middle + i / 2 + 1
+            } else {
+                middle - i / 2
+            }
+        })
+        .collect()
+}
+
+fn bench_arena_cancel(depth: u64) -> Duration {
+    let mut arena = OrderArena::with_capacity(depth as usize);
+    let mut price_level = PriceLevel::new(PRICE);
+    for id in 1..=depth {
+        price_level.add_order(&mut arena, new_order(id)).unwrap();
+    }
+    let ids = cancel_ids(depth);
+    let start = Instant::now();
+    for id in ids {
+        black_box(price_level.remove_order(&mut arena, id));
+    }
+    start.elapsed()
+}
+
+fn bench_vec_cancel(depth: u64) -> Duration {
+    let mut price_level = VecPriceLevel::new();
+    for id in 1..=depth {
+        price_level.add_order(new_order(id));
+    }
+    let ids = cancel_ids(depth);
+    let start = Instant::now();
+    for id in ids {
+        black_box(price_level.remove_order(id));
+    }
+    start.elapsed()
+}
+
+fn bench_arena_fill(depth: u64) -> Duration {
+    let mut arena = OrderArena::with_capacity(depth as usize);
+    let mut price_level = PriceLevel::new(PRICE);
+    for id in 1..=depth {
+        price_level.add_order(&mut arena, new_order(id)).unwrap();
+    }
+    let start = Instant::now();
+    while let Some(slot) = price_level.get_front() {
+        let id = arena.get(slot).get_id();
+        black_box(price_level.remove_order(&mut arena, id));
+    }
+    start.elapsed()
+}
+
+fn bench_vec_fill(depth: u64) -> Duration {
+    let mut price_level = VecPriceLevel::new();
+    for id in 1..=depth {
+        price_level.add_order(new_order(id));
+    }
+    let start = Instant::now();
+    while let Some(order) = price_level.orders.first() {
+        let id = order.get_id();
+        black_box(price_level.remove_order(id));
+    }
+    start.elapsed()
+}
+
+fn report(name: &str, depth: u64, operations: u64, arena: Duration, vec: Duration) {
+    println!(
+        "{:<8} depth={:<7} arena={:>10.1}ns/op vec={:>10.1}ns/op speedup={:>7.1}x",
+        name,
+        depth,
+        arena.as_nanos() as f64 / operations as f64,
+        vec.as_nanos() as f64 / operations as f64,
+        vec.as_secs_f64() / arena.as_secs_f64()
+    );
+}
+
+fn main() {
+    for depth in DEPTHS {
+        report(
+            "cancel",
+            depth,
+            depth / 2,
+            bench_arena_cancel(depth),
+            bench_vec_cancel(depth),
+        );
+        report(
+            "fill",
+            depth,
+            depth,
+            bench_arena_fill(depth),
+            bench_vec_fill(depth),
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TryRecvError};
+use std::time::{Duration, Instant};
+
+use crate::error::ExchangeError;
+use crate::request::RequestType;
+use crate::sync::{Condvar, Mutex};
+
+/// Outcome of a request, with the encoded response payload on success
+pub type RequestResult = Result<(RequestType, Vec<u8>), ExchangeError>;
+
+/// Function called on the worker thread with the id and result of a completed request
+pub type Callback = Box<dyn FnOnce(u128, RequestResult) + Send>;
+
+/// How the result of a request is delivered once a worker thread has processed it
+pub enum Completion {
+    /// Stored in the result table of the exchange, to be collected with `get_results`
+    Store,
+    /// Sent to the `ResultHandle` of the request
+    Handle(SyncSender<RequestResult>),
+    /// Pushed onto the completion queue of the exchange
+    Queue,
+    /// Passed to a callback on the worker thread
+    Callback(Callback),
+    /// Dropped, for requests replayed from the journal whose results were already
+    /// delivered before the restart
+    Discard,
+}
+
+impl fmt::Debug for Completion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Completion::Store => write!(f, "Store"),
+            Completion::Handle(_) => write!(f, "Handle"),
+            Completion::Queue => write!(f, "Queue"),
+            Completion::Callback(_) => write!(f, "Callback"),
+            Completion::Discard => write!(f, "Discard"),
+        }
+    }
+}
+
+/// One-shot handle to the result of a single request
+///
+/// The result can be taken exactly once, either by polling or by blocking on the handle
+#[derive(Debug)]
+pub struct ResultHandle {
+    id: u128,
+    receiver: Receiver<RequestResult>,
+}
+
+impl ResultHandle {
+    /// Creates a handle for the given request id, and the completion used to fulfil it
+    pub fn new(id: u128) -> (Self, Completion) {
+        let (sender, receiver) = mpsc::sync_channel(1);
+        (Self { id, receiver }, Completion::Handle(sender))
+    }
+
+    /// Returns the id of the request
+    pub fn get_id(&self) -> u128 {
+        self.id
+    }
+
+    /// Returns the result if the request has completed, without blocking
+    pub fn try_take(&self) -> Option<RequestResult> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => None,
+        }
+    }
+
+    /// Blocks until the request has completed, or the timeout elapses
+    pub fn wait_timeout(&self, timeout: Duration) -> Option<RequestResult> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(result) => Some(result),
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => None,
+        }
+    }
+
+    /// Blocks until the request has completed
+    ///
+    /// Returns `None` if the request was dropped without a result
+    pub fn wait(self) -> Option<RequestResult> {
+        self.receiver.recv().ok()
+    }
+}
+
+/// Queue of completed requests shared by the worker threads and clients
+///
+/// Clients can poll the queue or block on it with a timeout. Each result is handed out
+/// once and removed from the queue
+#[derive(Debug)]
+pub struct CompletionQueue {
+    results: Mutex<VecDeque<(u128, RequestResult)>>,
+    ready: Condvar,
+}
+
+impl Default for CompletionQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CompletionQueue {
+    /// Creates an empty completion queue
+    pub fn new() -> Self {
+        Self {
+            results: Mutex::new(VecDeque::new()),
+            ready: Condvar::new(),
+        }
+    }
+
+    /// Returns the number of completed requests waiting to be taken
+    pub fn len(&self) -> usize {
+        self.results.lock().unwrap().len()
+    }
+
+    /// Returns true if there are no completed requests waiting to be taken
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds the result of a completed request and wakes a waiting client
+    pub fn push(&self, request_id: u128, result: RequestResult) {
+        self.results.lock().unwrap().push_back((request_id, result));
+        self.ready.notify_one();
+    }
+
+    /// Takes the oldest completed request, without blocking
+    pub fn poll(&self) -> Option<(u128, RequestResult)> {
+        self.results.lock().unwrap().pop_front()
+    }
+
+    /// Takes the oldest completed request, blocking until one is available
+    pub fn wait(&self) -> (u128, RequestResult) {
+        let mut results = self.results.lock().unwrap();
+        loop {
+            if let Some(result) = results.pop_front() {
+                return result;
+            }
+            results = self.ready.wait(results).unwrap();
+        }
+    }
+
+    /// Takes the oldest completed request, blocking until one is available or the
+    /// timeout elapses
+    pub fn wait_timeout(&self, timeout: Duration) -> Option<(u128, RequestResult)> {
+        let deadline = Instant::now() + timeout;
+        let mut results = self.results.lock().unwrap();
+        loop {
+            if let Some(result) = results.pop_front() {
+                return Some(result);
+            }
+            let timeout = deadline.checked_duration_since(Instant::now())?;
+            results = self.ready.wait_timeout(results, timeout).unwrap().0;
+        }
+    }
+}
@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::completion::{Completion, CompletionQueue, ResultHandle};
+    use crate::error::ExchangeError;
+    use crate::request::RequestType;
+
+    #[test]
+    fn test_result_handle_try_take_before_completion() {
+        let (handle, _completion) = ResultHandle::new(1);
+        assert_eq!(handle.get_id(), 1);
+        assert!(handle.try_take().is_none());
+    }
+
+    #[test]
+    fn test_result_handle_takes_result_once() {
+        let (handle, completion) = ResultHandle::new(7);
+        let Completion::Handle(sender) = completion else {
+            panic!("expected handle completion");
+        };
+        sender
+            .send(Ok((RequestType::CancelOrder, vec![1, 2])))
+            .unwrap();
+        assert_eq!(
+            handle.try_take(),
+            Some(Ok((RequestType::CancelOrder, vec![1, 2])))
+        );
+        assert!(handle.try_take().is_none());
+    }
+
+    #[test]
+    fn test_result_handle_wait_timeout() {
+        let (handle, completion) = ResultHandle::new(1);
+        assert!(handle.wait_timeout(Duration::from_millis(10)).is_none());
+        let Completion::Handle(sender) = completion else {
+            panic!("expected handle completion");
+        };
+        thread::spawn(move || sender.send(Err(ExchangeError::OrderNotFound)).unwrap());
+        assert_eq!(
+            handle.wait_timeout(Duration::from_secs(5)),
+            Some(Err(ExchangeError::OrderNotFound))
+        );
+    }
+
+    #[test]
+    fn test_result_handle_wait_dropped_completion() {
+        let (handle, completion) = ResultHandle::new(1);
+        drop(completion);
+        assert!(handle.wait().is_none());
+    }
+
+    #[test]
+    fn test_completion_queue_poll_in_order() {
+        let queue = CompletionQueue::new();
+        assert!(queue.is_empty());
+        assert!(queue.poll().is_none());
+        queue.push(1, Ok((RequestType::SubmitOrder, vec![])));
+        queue.push(2, Err(ExchangeError::OrderBookNotFound));
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.poll().unwrap().0, 1);
+        assert_eq!(
+            queue.poll(),
+            Some((2, Err(ExchangeError::OrderBookNotFound)))
+        );
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_completion_queue_wait_timeout() {
+        let queue = Arc::new(CompletionQueue::new());
+        assert!(queue.wait_timeout(Duration::from_millis(10)).is_none());
+        let producer = queue.clone();
+        thread::spawn(move || producer.push(3, Ok((RequestType::AdvanceClock, vec![]))));
+        let (id, result) = queue.wait_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(id, 3);
+        assert_eq!(result, Ok((RequestType::AdvanceClock, vec![])));
+        assert!(queue.is_empty());
+    }
+}
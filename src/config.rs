@@ -0,0 +1,156 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::ExchangeError;
+use crate::published_depth::DEFAULT_DEPTH_LEVELS;
+use crate::result_ring::DEFAULT_RESULT_CAPACITY;
+
+/// Default number of requests each worker ring buffer can hold
+pub const DEFAULT_RING_CAPACITY: usize = 16_384;
+
+/// Transport used to send requests from the exchange to its worker threads
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Transport {
+    /// Unbounded `std::sync::mpsc` channels
+    #[default]
+    Mpsc,
+    /// Preallocated single producer, single consumer ring buffers of the given capacity
+    Spsc { capacity: usize },
+}
+
+/// When the journal is flushed from the operating system cache to disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FsyncPolicy {
+    /// Never, leaving it to the operating system. Records survive the process
+    /// crashing, but not the machine
+    None,
+    /// Once every given number of records, and when the journal is closed
+    Batch { records: usize },
+    /// After every record, before the request is dispatched
+    #[default]
+    Every,
+}
+
+/// Settings of the journal that requests are written to before they are dispatched
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalConfig {
+    path: PathBuf,
+    fsync_policy: FsyncPolicy,
+}
+
+impl JournalConfig {
+    /// Creates the settings of a journal kept in the given file
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            fsync_policy: FsyncPolicy::default(),
+        }
+    }
+
+    /// Get the path of the journal file
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get when the journal is flushed to disk
+    pub fn get_fsync_policy(&self) -> FsyncPolicy {
+        self.fsync_policy
+    }
+
+    /// Set when the journal is flushed to disk
+    pub fn set_fsync_policy(&mut self, fsync_policy: FsyncPolicy) {
+        self.fsync_policy = fsync_policy;
+    }
+}
+
+/// Settings used to create an exchange
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExchangeConfig {
+    result_capacity: usize,
+    transport: Transport,
+    depth_levels: usize,
+    journal: Option<JournalConfig>,
+    snapshot_path: Option<PathBuf>,
+}
+
+impl Default for ExchangeConfig {
+    fn default() -> Self {
+        Self {
+            result_capacity: DEFAULT_RESULT_CAPACITY,
+            transport: Transport::default(),
+            depth_levels: DEFAULT_DEPTH_LEVELS,
+            journal: None,
+            snapshot_path: None,
+        }
+    }
+}
+
+impl ExchangeConfig {
+    /// Returns an error if the settings cannot be used to create an exchange
+    pub fn validate(&self) -> Result<(), ExchangeError> {
+        if self.result_capacity == 0 {
+            return Err(ExchangeError::InvalidResultCapacity);
+        }
+        if self.transport == (Transport::Spsc { capacity: 0 }) {
+            return Err(ExchangeError::InvalidRingCapacity);
+        }
+        Ok(())
+    }
+
+    /// Get the number of request results kept before the oldest are overwritten
+    pub fn get_result_capacity(&self) -> usize {
+        self.result_capacity
+    }
+
+    /// Set the number of request results kept before the oldest are overwritten
+    pub fn set_result_capacity(&mut self, result_capacity: usize) {
+        self.result_capacity = result_capacity;
+    }
+
+    /// Get the transport used to send requests to the worker threads
+    pub fn get_transport(&self) -> Transport {
+        self.transport
+    }
+
+    /// Set the transport used to send requests to the worker threads
+    pub fn set_transport(&mut self, transport: Transport) {
+        self.transport = transport;
+    }
+
+    /// Get the number of price levels on each side of every market published to readers
+    /// on other threads
+    pub fn get_depth_levels(&self) -> usize {
+        self.depth_levels
+    }
+
+    /// Set the number of price levels on each side of every market published to readers
+    /// on other threads, which is the most that can be asked for in a depth query
+    pub fn set_depth_levels(&mut self, depth_levels: usize) {
+        self.depth_levels = depth_levels;
+    }
+
+    /// Get the settings of the request journal, if requests are journaled
+    pub fn get_journal(&self) -> Option<&JournalConfig> {
+        self.journal.as_ref()
+    }
+
+    /// Set the settings of the request journal
+    ///
+    /// With a journal, the exchange replays the requests already in the journal when it
+    /// is created, and writes every new request to it before dispatching it
+    pub fn set_journal(&mut self, journal: Option<JournalConfig>) {
+        self.journal = journal;
+    }
+
+    /// Get the path of the snapshot the exchange is restored from, if any
+    pub fn get_snapshot_path(&self) -> Option<&Path> {
+        self.snapshot_path.as_deref()
+    }
+
+    /// Set the path of the snapshot the exchange is restored from
+    ///
+    /// If the file exists when the exchange is created, the order books are restored
+    /// from it, and only the journal records written after the snapshot are replayed
+    pub fn set_snapshot_path(&mut self, snapshot_path: Option<PathBuf>) {
+        self.snapshot_path = snapshot_path;
+    }
+}
@@ -0,0 +1,60 @@
+/// Lookup table of the reflected CRC-32 polynomial used by zlib, gzip and PNG
+const TABLE: [u32; 256] = build_table();
+
+const fn build_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    table
+}
+
+/// CRC-32 checksum of bytes fed in one or more pieces
+#[derive(Debug, Clone, Copy)]
+pub struct Crc32 {
+    crc: u32,
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crc32 {
+    /// Creates a checksum of no bytes
+    pub fn new() -> Self {
+        Self { crc: !0 }
+    }
+
+    /// Add the given bytes to the checksum
+    pub fn update(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.crc = TABLE[((self.crc ^ *byte as u32) & 0xFF) as usize] ^ (self.crc >> 8);
+        }
+    }
+
+    /// Returns the checksum of the bytes added so far
+    pub fn get_value(&self) -> u32 {
+        !self.crc
+    }
+}
+
+/// Returns the CRC-32 checksum of the given bytes
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(bytes);
+    crc.get_value()
+}
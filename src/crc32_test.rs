@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::crc32::{Crc32, crc32};
+
+    #[test]
+    fn test_crc32_of_empty_input() {
+        assert_eq!(crc32(&[]), 0);
+    }
+
+    #[test]
+    fn test_crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414F_A339
+        );
+    }
+
+    #[test]
+    fn test_crc32_detects_single_bit_flip() {
+        let mut bytes = b"journal record".to_vec();
+        let checksum = crc32(&bytes);
+        bytes[3] ^= 0x10;
+        assert_ne!(crc32(&bytes), checksum);
+    }
+
+    #[test]
+    fn test_crc32_in_pieces() {
+        let mut crc = Crc32::new();
+        crc.update(b"1234");
+        crc.update(b"");
+        crc.update(b"56789");
+        assert_eq!(crc.get_value(), 0xCBF4_3926);
+    }
+}
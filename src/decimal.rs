@@ -0,0 +1,205 @@
+use std::fmt;
+use std::str::FromStr;
+
+use bincode::de::Decoder;
+use bincode::error::DecodeError;
+use bincode::{Decode, Encode};
+
+use crate::error::ExchangeError;
+
+/// Highest number of decimal places supported by prices and quantities
+pub const MAX_SCALE: u8 = 18;
+
+/// Fixed-point decimal price, equal to `units / 10^scale`
+///
+/// Two prices are only equal if they have the same units and scale, so prices should
+/// be rescaled to a common scale before they are compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode)]
+pub struct Price {
+    units: u64,
+    scale: u8,
+}
+
+impl Price {
+    /// Creates a price of `units / 10^scale`
+    pub fn new(units: u64, scale: u8) -> Self {
+        assert!(scale <= MAX_SCALE, "scale {} is above max scale", scale);
+        Self { units, scale }
+    }
+
+    /// Returns the price in units of the smallest increment at its scale
+    pub fn get_units(&self) -> u64 {
+        self.units
+    }
+
+    /// Returns the number of decimal places of the price
+    pub fn get_scale(&self) -> u8 {
+        self.scale
+    }
+
+    /// Returns the same price expressed with the given number of decimal places
+    ///
+    /// Returns an error if the price cannot be represented exactly at that scale
+    pub fn rescale(&self, scale: u8) -> Result<Self, ExchangeError> {
+        let units = rescale_units(self.units, self.scale, scale)?;
+        Ok(Self::new(units, scale))
+    }
+}
+
+impl<Context> Decode<Context> for Price {
+    fn decode<D: Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, DecodeError> {
+        let (units, scale) = decode_decimal(decoder)?;
+        Ok(Self::new(units, scale))
+    }
+}
+
+bincode::impl_borrow_decode!(Price);
+
+impl FromStr for Price {
+    type Err = ExchangeError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (units, scale) = parse_decimal(value)?;
+        Ok(Self::new(units, scale))
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        format_decimal(self.units, self.scale, f)
+    }
+}
+
+/// Fixed-point decimal quantity, equal to `units / 10^scale`
+///
+/// Two quantities are only equal if they have the same units and scale, so quantities
+/// should be rescaled to a common scale before they are compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode)]
+pub struct Quantity {
+    units: u64,
+    scale: u8,
+}
+
+impl Quantity {
+    /// Creates a quantity of `units / 10^scale`
+    pub fn new(units: u64, scale: u8) -> Self {
+        assert!(scale <= MAX_SCALE, "scale {} is above max scale", scale);
+        Self { units, scale }
+    }
+
+    /// Returns the quantity in units of the smallest increment at its scale
+    pub fn get_units(&self) -> u64 {
+        self.units
+    }
+
+    /// Returns the number of decimal places of the quantity
+    pub fn get_scale(&self) -> u8 {
+        self.scale
+    }
+
+    /// Returns the same quantity expressed with the given number of decimal places
+    ///
+    /// Returns an error if the quantity cannot be represented exactly at that scale
+    pub fn rescale(&self, scale: u8) -> Result<Self, ExchangeError> {
+        let units = rescale_units(self.units, self.scale, scale)?;
+        Ok(Self::new(units, scale))
+    }
+}
+
+impl<Context> Decode<Context> for Quantity {
+    fn decode<D: Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, DecodeError> {
+        let (units, scale) = decode_decimal(decoder)?;
+        Ok(Self::new(units, scale))
+    }
+}
+
+bincode::impl_borrow_decode!(Quantity);
+
+impl FromStr for Quantity {
+    type Err = ExchangeError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (units, scale) = parse_decimal(value)?;
+        Ok(Self::new(units, scale))
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        format_decimal(self.units, self.scale, f)
+    }
+}
+
+/// Decode units and scale, rejecting a scale above the max scale
+fn decode_decimal<D: Decoder>(decoder: &mut D) -> Result<(u64, u8), DecodeError> {
+    let units = u64::decode(decoder)?;
+    let scale = u8::decode(decoder)?;
+    if scale > MAX_SCALE {
+        return Err(DecodeError::OtherString(format!(
+            "scale {} is above max scale",
+            scale
+        )));
+    }
+    Ok((units, scale))
+}
+
+/// Convert units from one scale to another without losing precision
+fn rescale_units(units: u64, from: u8, to: u8) -> Result<u64, ExchangeError> {
+    if from > MAX_SCALE || to > MAX_SCALE {
+        return Err(ExchangeError::InvalidScale);
+    }
+    if to >= from {
+        let factor = 10u64
+            .checked_pow((to - from) as u32)
+            .ok_or(ExchangeError::InvalidScale)?;
+        units
+            .checked_mul(factor)
+            .ok_or(ExchangeError::DecimalTooLarge)
+    } else {
+        let divisor = 10u64
+            .checked_pow((from - to) as u32)
+            .ok_or(ExchangeError::InvalidScale)?;
+        if !units.is_multiple_of(divisor) {
+            return Err(ExchangeError::DecimalTooPrecise);
+        }
+        Ok(units / divisor)
+    }
+}
+
+/// Parse a non-negative decimal string such as `101.25` into units and scale
+fn parse_decimal(value: &str) -> Result<(u64, u8), ExchangeError> {
+    let (whole, fraction) = match value.split_once('.') {
+        Some((whole, fraction)) => (whole, fraction),
+        None => (value, ""),
+    };
+    let is_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+    if whole.is_empty() || !is_digits(whole) || !is_digits(fraction) {
+        return Err(ExchangeError::InvalidDecimal);
+    }
+    if fraction.len() > MAX_SCALE as usize {
+        return Err(ExchangeError::DecimalTooPrecise);
+    }
+    let mut units: u64 = 0;
+    for byte in whole.bytes().chain(fraction.bytes()) {
+        units = units
+            .checked_mul(10)
+            .and_then(|units| units.checked_add((byte - b'0') as u64))
+            .ok_or(ExchangeError::DecimalTooLarge)?;
+    }
+    Ok((units, fraction.len() as u8))
+}
+
+/// Write units at the given scale as a decimal string
+fn format_decimal(units: u64, scale: u8, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if scale == 0 {
+        return write!(f, "{}", units);
+    }
+    let divisor = 10u64.pow(scale as u32);
+    write!(
+        f,
+        "{}.{:0width$}",
+        units / divisor,
+        units % divisor,
+        width = scale as usize
+    )
+}
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::decimal::{Price, Quantity};
+    use crate::error::ExchangeError;
+
+    #[test]
+    fn test_new() {
+        let price = Price::new(10_125, 2);
+        assert_eq!(price.get_units(), 10_125);
+        assert_eq!(price.get_scale(), 2);
+        let quantity = Quantity::new(5, 3);
+        assert_eq!(quantity.get_units(), 5);
+        assert_eq!(quantity.get_scale(), 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_new_fails_above_max_scale() {
+        Price::new(1, 19);
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("101.25".parse::<Price>(), Ok(Price::new(10_125, 2)));
+        assert_eq!("101".parse::<Price>(), Ok(Price::new(101, 0)));
+        assert_eq!("0.001".parse::<Quantity>(), Ok(Quantity::new(1, 3)));
+        assert_eq!("1.".parse::<Quantity>(), Ok(Quantity::new(1, 0)));
+        assert!("".parse::<Price>().is_err());
+        assert!(".5".parse::<Price>().is_err());
+        assert!("-1".parse::<Price>().is_err());
+        assert!("1.2.3".parse::<Price>().is_err());
+        assert!("1e5".parse::<Price>().is_err());
+        assert!("0.0000000000000000001".parse::<Price>().is_err());
+        assert!("18446744073709551616".parse::<Quantity>().is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Price::new(10_125, 2).to_string(), "101.25");
+        assert_eq!(Price::new(5, 3).to_string(), "0.005");
+        assert_eq!(Quantity::new(42, 0).to_string(), "42");
+        assert_eq!(Quantity::new(4_200, 2).to_string(), "42.00");
+    }
+
+    #[test]
+    fn test_rescale() {
+        let price = Price::new(1_015, 1);
+        assert_eq!(price.rescale(3), Ok(Price::new(101_500, 3)));
+        assert_eq!(price.rescale(1), Ok(price));
+        assert_eq!(Price::new(101_500, 3).rescale(1), Ok(price));
+        assert_eq!(price.rescale(0), Err(ExchangeError::DecimalTooPrecise));
+        assert_eq!(
+            Quantity::new(u64::MAX, 0).rescale(1),
+            Err(ExchangeError::DecimalTooLarge)
+        );
+        assert_eq!(
+            Quantity::new(1, 0).rescale(19),
+            Err(ExchangeError::InvalidScale)
+        );
+    }
+
+    #[test]
+    fn test_decode_validates_scale() {
+        let config = bincode::config::standard();
+        let encoded = bincode::encode_to_vec(Price::new(10_125, 2), config).unwrap();
+        let (price, _): (Price, usize) = bincode::decode_from_slice(&encoded, config).unwrap();
+        assert_eq!(price, Price::new(10_125, 2));
+        let encoded = bincode::encode_to_vec((1u64, 19u8), config).unwrap();
+        assert!(bincode::decode_from_slice::<Price, _>(&encoded, config).is_err());
+        assert!(bincode::decode_from_slice::<Quantity, _>(&encoded, config).is_err());
+    }
+}
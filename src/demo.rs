@@ -1,27 +1,29 @@
 use std::time::SystemTime;
 
 use crate::{
+    config::{DEFAULT_RING_CAPACITY, ExchangeConfig, Transport},
     exchange::Exchange,
+    market::Market,
     order::{OrderType, Side},
     request::{CancelOrderRequest, SubmitOrderRequest},
 };
 
 fn list_markets(exchange: &mut Exchange) {
-    let res = exchange.list_market("BTCUSDT", 10_001, 1);
+    let res = exchange.list_market(Market::new("BTCUSDT", 10_001, 1));
     println!("listed market: {:?}", res);
-    let res = exchange.list_market("ETHUSDT", 10_001, 1);
+    let res = exchange.list_market(Market::new("ETHUSDT", 10_001, 1));
     println!("listed market: {:?}", res);
-    let res = exchange.list_market("SOLUSDT", 10_001, 1);
+    let res = exchange.list_market(Market::new("SOLUSDT", 10_001, 1));
     println!("listed market: {:?}", res);
-    let res = exchange.list_market("LINKUSDT", 10_001, 1);
+    let res = exchange.list_market(Market::new("LINKUSDT", 10_001, 1));
     println!("listed market: {:?}", res);
-    let res = exchange.list_market("AAVEUSDT", 10_001, 1);
+    let res = exchange.list_market(Market::new("AAVEUSDT", 10_001, 1));
     println!("listed market: {:?}", res);
-    let res = exchange.list_market("OPUSDT", 10_001, 1);
+    let res = exchange.list_market(Market::new("OPUSDT", 10_001, 1));
     println!("listed market: {:?}", res);
 }
 
-fn add_limit_orders(exchange: &mut Exchange) {
+fn add_limit_orders(exchange: &mut Exchange) -> u128 {
     println!("adding limit orders for each market...");
     let mut request_ids = Vec::new();
     let start = SystemTime::now()
@@ -43,6 +45,7 @@ fn add_limit_orders(exchange: &mut Exchange) {
                 size: 1,
                 side,
                 order_type: OrderType::Limit,
+                ..Default::default()
             });
             request_ids.push(res.unwrap());
         }
@@ -56,9 +59,10 @@ fn add_limit_orders(exchange: &mut Exchange) {
     let duration = end - start;
     let latency = duration / (total_orders as u128) / (markets.len() as u128);
     println!("insertion latency = {} ns", latency);
+    latency
 }
 
-fn cancel_limit_orders(exchange: &mut Exchange) {
+fn cancel_limit_orders(exchange: &mut Exchange) -> u128 {
     println!("cancelling orders on each market...");
     let mut request_ids = Vec::new();
     let start = SystemTime::now()
@@ -90,9 +94,10 @@ fn cancel_limit_orders(exchange: &mut Exchange) {
     let duration = end - start;
     let latency = duration / (total_cancellations as u128) / (markets.len() as u128);
     println!("cancellation latency = {} ns", latency);
+    latency
 }
 
-fn do_market_orders(exchange: &mut Exchange) {
+fn do_market_orders(exchange: &mut Exchange) -> u128 {
     println!("sending market orders to each book...");
     let mut request_ids = Vec::new();
     let start = SystemTime::now()
@@ -114,6 +119,7 @@ fn do_market_orders(exchange: &mut Exchange) {
                 size: 2,
                 side,
                 order_type: OrderType::Market,
+                ..Default::default()
             });
             request_ids.push(res.unwrap());
         }
@@ -127,6 +133,7 @@ fn do_market_orders(exchange: &mut Exchange) {
     let duration = end - start;
     let latency = duration / (total_orders as u128) / (markets.len() as u128);
     println!("matching latency = {} ns", latency);
+    latency
 }
 
 fn display_top_of_book(exchange: &mut Exchange) {
@@ -140,15 +147,46 @@ fn display_top_of_book(exchange: &mut Exchange) {
     }
 }
 
-pub fn run() {
-    let mut exchange = Exchange::new();
+/// Run the demo workload on an exchange using the given transport, returning the
+/// insertion, cancellation, re-insertion and matching latencies
+fn run_workload(transport: Transport) -> [u128; 4] {
+    println!("running workload over {:?} transport...", transport);
+    let mut config = ExchangeConfig::default();
+    config.set_transport(transport);
+    let mut exchange = Exchange::with_config(config).expect("cannot create exchange");
     list_markets(&mut exchange);
-    add_limit_orders(&mut exchange);
+    let insertion = add_limit_orders(&mut exchange);
     display_top_of_book(&mut exchange);
-    cancel_limit_orders(&mut exchange);
+    let cancellation = cancel_limit_orders(&mut exchange);
     //display_top_of_book(&mut exchange);
-    add_limit_orders(&mut exchange);
+    let reinsertion = add_limit_orders(&mut exchange);
     //display_top_of_book(&mut exchange);
-    do_market_orders(&mut exchange);
+    let matching = do_market_orders(&mut exchange);
     //display_top_of_book(&mut exchange);
+    [insertion, cancellation, reinsertion, matching]
+}
+
+pub fn run() {
+    let transports = [
+        Transport::Mpsc,
+        Transport::Spsc {
+            capacity: DEFAULT_RING_CAPACITY,
+        },
+    ];
+    let latencies = transports.map(run_workload);
+    println!("latency comparison (ns):");
+    println!(
+        "{:<10} {:>10} {:>12} {:>12} {:>10}",
+        "transport", "insertion", "cancellation", "reinsertion", "matching"
+    );
+    for (transport, latency) in transports.iter().zip(latencies) {
+        let name = match transport {
+            Transport::Mpsc => "mpsc",
+            Transport::Spsc { .. } => "spsc",
+        };
+        println!(
+            "{:<10} {:>10} {:>12} {:>12} {:>10}",
+            name, latency[0], latency[1], latency[2], latency[3]
+        );
+    }
 }
@@ -0,0 +1,317 @@
+use std::fmt;
+
+use bincode::de::{Decode, Decoder};
+use bincode::enc::{Encode, Encoder};
+use bincode::error::{DecodeError, EncodeError};
+
+/// Reason a request was rejected or failed
+///
+/// Each variant has a stable numeric code, which is what is written when the error is
+/// encoded, so codes can be matched by clients and stay the same across releases even
+/// if variants are reordered. Codes are grouped by hundreds: 1xx markets, 2xx order
+/// validation, 3xx execution, 4xx book state, 5xx decimals and 9xx transport
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExchangeError {
+    /// The symbol does not match a listed market
+    MarketNotFound,
+    /// A market with the same symbol is already listed
+    MarketAlreadyExists,
+    /// The symbol does not match an order book
+    OrderBookNotFound,
+    /// The min price of the market is not below its max price
+    InvalidPriceRange,
+    /// The tick size of the market is zero
+    InvalidTickSize,
+    /// The lot size of the market is zero
+    InvalidLotSize,
+    /// The min quantity of the market is above its max quantity
+    InvalidQuantityRange,
+    /// A decimal scale is above the max supported scale
+    InvalidScale,
+    /// The order price is below the min price of the market
+    PriceBelowMin,
+    /// The order price is not below the max price of the market
+    PriceAboveMax,
+    /// The order price is not on the tick grid of the market
+    PriceNotOnTick,
+    /// The order size is zero
+    SizeZero,
+    /// The order size is not a multiple of the lot size of the market
+    SizeNotOnLot,
+    /// The iceberg display size is not a multiple of the lot size of the market
+    DisplaySizeNotOnLot,
+    /// The order size is below the min quantity of the market
+    SizeBelowMin,
+    /// The order size is above the max quantity of the market
+    SizeAboveMax,
+    /// The order price multiplied by its size is below the min notional of the market
+    NotionalBelowMin,
+    /// The stop trigger price is below the min price of the market
+    TriggerPriceBelowMin,
+    /// The stop trigger price is not below the max price of the market
+    TriggerPriceAboveMax,
+    /// The expire time of a good-till-date order is not after the book clock
+    ExpireTimeInPast,
+    /// An amendment asked for a size of zero
+    AmendSizeZero,
+    /// A decimal price has more decimal places than the market allows
+    PriceNotRepresentable,
+    /// A decimal quantity has more decimal places than the market allows
+    QuantityNotRepresentable,
+    /// A fill-or-kill order cannot be fully filled on arrival
+    FillOrKillNotFilled,
+    /// A post-only order is not a limit order
+    PostOnlyNotLimit,
+    /// A post-only order with the reject instruction would cross the book
+    PostOnlyWouldTake,
+    /// A post-only buy order cannot slide below the min price of the market
+    PostOnlySlideBelowMin,
+    /// A post-only sell order cannot slide above the max price of the market
+    PostOnlySlideAboveMax,
+    /// The order id does not match a resting or stop order
+    OrderNotFound,
+    /// The new clock time is before the current book time
+    ClockMovedBackwards,
+    /// The order book cannot allocate memory for another order
+    CapacityExhausted,
+    /// The result of the request was overwritten by a newer request, or already taken
+    ResultExpired,
+    /// More price levels were requested than are published for the market
+    DepthLevelsAboveMax,
+    /// A decimal string is not a non-negative decimal number
+    InvalidDecimal,
+    /// A decimal value does not fit in 64 bits at its scale
+    DecimalTooLarge,
+    /// A decimal value cannot be represented exactly at the requested scale
+    DecimalTooPrecise,
+    /// A request or result cannot be encoded
+    PayloadEncoding,
+    /// A request or result cannot be decoded
+    PayloadDecoding,
+    /// The worker thread of the market is no longer receiving requests
+    ChannelClosed,
+    /// The journal file cannot be written or flushed to disk
+    JournalWrite,
+    /// The journal file cannot be opened or read
+    JournalRead,
+    /// The journal file holds a damaged record before its end, or is not a journal
+    JournalCorrupt,
+    /// The snapshot file cannot be written
+    SnapshotWrite,
+    /// The snapshot file cannot be opened or read
+    SnapshotRead,
+    /// The snapshot file is damaged, or does not describe a valid order book
+    SnapshotCorrupt,
+    /// The snapshot file was written in a format version that is not supported
+    SnapshotVersion,
+    /// The exchange config keeps the results of no requests
+    InvalidResultCapacity,
+    /// The exchange config sends requests through ring buffers that hold no requests
+    InvalidRingCapacity,
+}
+
+impl ExchangeError {
+    /// Returns the stable numeric code of the error
+    pub fn code(&self) -> u16 {
+        match self {
+            ExchangeError::MarketNotFound => 100,
+            ExchangeError::MarketAlreadyExists => 101,
+            ExchangeError::OrderBookNotFound => 102,
+            ExchangeError::InvalidPriceRange => 110,
+            ExchangeError::InvalidTickSize => 111,
+            ExchangeError::InvalidLotSize => 112,
+            ExchangeError::InvalidQuantityRange => 113,
+            ExchangeError::InvalidScale => 114,
+            ExchangeError::PriceBelowMin => 200,
+            ExchangeError::PriceAboveMax => 201,
+            ExchangeError::PriceNotOnTick => 202,
+            ExchangeError::SizeNotOnLot => 203,
+            ExchangeError::DisplaySizeNotOnLot => 204,
+            ExchangeError::SizeBelowMin => 205,
+            ExchangeError::SizeAboveMax => 206,
+            ExchangeError::NotionalBelowMin => 207,
+            ExchangeError::TriggerPriceBelowMin => 208,
+            ExchangeError::TriggerPriceAboveMax => 209,
+            ExchangeError::ExpireTimeInPast => 210,
+            ExchangeError::AmendSizeZero => 211,
+            ExchangeError::PriceNotRepresentable => 212,
+            ExchangeError::QuantityNotRepresentable => 213,
+            ExchangeError::SizeZero => 214,
+            ExchangeError::FillOrKillNotFilled => 300,
+            ExchangeError::PostOnlyNotLimit => 301,
+            ExchangeError::PostOnlyWouldTake => 302,
+            ExchangeError::PostOnlySlideBelowMin => 303,
+            ExchangeError::PostOnlySlideAboveMax => 304,
+            ExchangeError::OrderNotFound => 400,
+            ExchangeError::ClockMovedBackwards => 401,
+            ExchangeError::CapacityExhausted => 402,
+            ExchangeError::ResultExpired => 403,
+            ExchangeError::DepthLevelsAboveMax => 404,
+            ExchangeError::InvalidDecimal => 500,
+            ExchangeError::DecimalTooLarge => 501,
+            ExchangeError::DecimalTooPrecise => 502,
+            ExchangeError::PayloadEncoding => 900,
+            ExchangeError::PayloadDecoding => 901,
+            ExchangeError::ChannelClosed => 902,
+            ExchangeError::JournalWrite => 903,
+            ExchangeError::JournalRead => 904,
+            ExchangeError::JournalCorrupt => 905,
+            ExchangeError::SnapshotWrite => 906,
+            ExchangeError::SnapshotRead => 907,
+            ExchangeError::SnapshotCorrupt => 908,
+            ExchangeError::SnapshotVersion => 909,
+            ExchangeError::InvalidResultCapacity => 910,
+            ExchangeError::InvalidRingCapacity => 911,
+        }
+    }
+
+    /// Returns the error with the given numeric code
+    pub fn from_code(code: u16) -> Option<Self> {
+        match code {
+            100 => Some(ExchangeError::MarketNotFound),
+            101 => Some(ExchangeError::MarketAlreadyExists),
+            102 => Some(ExchangeError::OrderBookNotFound),
+            110 => Some(ExchangeError::InvalidPriceRange),
+            111 => Some(ExchangeError::InvalidTickSize),
+            112 => Some(ExchangeError::InvalidLotSize),
+            113 => Some(ExchangeError::InvalidQuantityRange),
+            114 => Some(ExchangeError::InvalidScale),
+            200 => Some(ExchangeError::PriceBelowMin),
+            201 => Some(ExchangeError::PriceAboveMax),
+            202 => Some(ExchangeError::PriceNotOnTick),
+            203 => Some(ExchangeError::SizeNotOnLot),
+            204 => Some(ExchangeError::DisplaySizeNotOnLot),
+            205 => Some(ExchangeError::SizeBelowMin),
+            206 => Some(ExchangeError::SizeAboveMax),
+            207 => Some(ExchangeError::NotionalBelowMin),
+            208 => Some(ExchangeError::TriggerPriceBelowMin),
+            209 => Some(ExchangeError::TriggerPriceAboveMax),
+            210 => Some(ExchangeError::ExpireTimeInPast),
+            211 => Some(ExchangeError::AmendSizeZero),
+            212 => Some(ExchangeError::PriceNotRepresentable),
+            213 => Some(ExchangeError::QuantityNotRepresentable),
+            214 => Some(ExchangeError::SizeZero),
+            300 => Some(ExchangeError::FillOrKillNotFilled),
+            301 => Some(ExchangeError::PostOnlyNotLimit),
+            302 => Some(ExchangeError::PostOnlyWouldTake),
+            303 => Some(ExchangeError::PostOnlySlideBelowMin),
+            304 => Some(ExchangeError::PostOnlySlideAboveMax),
+            400 => Some(ExchangeError::OrderNotFound),
+            401 => Some(ExchangeError::ClockMovedBackwards),
+            402 => Some(ExchangeError::CapacityExhausted),
+            403 => Some(ExchangeError::ResultExpired),
+            404 => Some(ExchangeError::DepthLevelsAboveMax),
+            500 => Some(ExchangeError::InvalidDecimal),
+            501 => Some(ExchangeError::DecimalTooLarge),
+            502 => Some(ExchangeError::DecimalTooPrecise),
+            900 => Some(ExchangeError::PayloadEncoding),
+            901 => Some(ExchangeError::PayloadDecoding),
+            902 => Some(ExchangeError::ChannelClosed),
+            903 => Some(ExchangeError::JournalWrite),
+            904 => Some(ExchangeError::JournalRead),
+            905 => Some(ExchangeError::JournalCorrupt),
+            906 => Some(ExchangeError::SnapshotWrite),
+            907 => Some(ExchangeError::SnapshotRead),
+            908 => Some(ExchangeError::SnapshotCorrupt),
+            909 => Some(ExchangeError::SnapshotVersion),
+            910 => Some(ExchangeError::InvalidResultCapacity),
+            911 => Some(ExchangeError::InvalidRingCapacity),
+            _ => None,
+        }
+    }
+
+    /// Returns a human readable description of the error
+    pub fn message(&self) -> &'static str {
+        match self {
+            ExchangeError::MarketNotFound => "market not found",
+            ExchangeError::MarketAlreadyExists => "market already exists",
+            ExchangeError::OrderBookNotFound => "order book not found",
+            ExchangeError::InvalidPriceRange => "min price must be below max price",
+            ExchangeError::InvalidTickSize => "tick size must be greater than zero",
+            ExchangeError::InvalidLotSize => "lot size must be greater than zero",
+            ExchangeError::InvalidQuantityRange => "min quantity must not be above max quantity",
+            ExchangeError::InvalidScale => "scale is above max scale",
+            ExchangeError::PriceBelowMin => "order price is below min for market",
+            ExchangeError::PriceAboveMax => "order price is above max for market",
+            ExchangeError::PriceNotOnTick => "order price is not a multiple of tick size",
+            ExchangeError::SizeZero => "order size must be greater than zero",
+            ExchangeError::SizeNotOnLot => "order size is not a multiple of lot size",
+            ExchangeError::DisplaySizeNotOnLot => {
+                "order display size is not a multiple of lot size"
+            }
+            ExchangeError::SizeBelowMin => "order size is below min quantity for market",
+            ExchangeError::SizeAboveMax => "order size is above max quantity for market",
+            ExchangeError::NotionalBelowMin => "order notional is below min notional for market",
+            ExchangeError::TriggerPriceBelowMin => "stop trigger price is below min for market",
+            ExchangeError::TriggerPriceAboveMax => "stop trigger price is above max for market",
+            ExchangeError::ExpireTimeInPast => "order expire time is in the past",
+            ExchangeError::AmendSizeZero => "amended order size must be greater than zero",
+            ExchangeError::PriceNotRepresentable => {
+                "price cannot be represented at market price scale"
+            }
+            ExchangeError::QuantityNotRepresentable => {
+                "quantity cannot be represented at market quantity scale"
+            }
+            ExchangeError::FillOrKillNotFilled => "fill or kill order cannot be fully filled",
+            ExchangeError::PostOnlyNotLimit => "post only order must be a limit order",
+            ExchangeError::PostOnlyWouldTake => "post only order would take liquidity",
+            ExchangeError::PostOnlySlideBelowMin => {
+                "post only order cannot slide below min for market"
+            }
+            ExchangeError::PostOnlySlideAboveMax => {
+                "post only order cannot slide above max for market"
+            }
+            ExchangeError::OrderNotFound => "order not found",
+            ExchangeError::ClockMovedBackwards => "clock cannot move backwards",
+            ExchangeError::CapacityExhausted => "order book capacity exhausted",
+            ExchangeError::ResultExpired => "request result expired",
+            ExchangeError::DepthLevelsAboveMax => {
+                "depth levels are above the published levels for market"
+            }
+            ExchangeError::InvalidDecimal => "invalid decimal value",
+            ExchangeError::DecimalTooLarge => "decimal value is too large",
+            ExchangeError::DecimalTooPrecise => "decimal value has too many decimal places",
+            ExchangeError::PayloadEncoding => "cannot build payload",
+            ExchangeError::PayloadDecoding => "cannot decode payload",
+            ExchangeError::ChannelClosed => "worker channel is closed",
+            ExchangeError::JournalWrite => "cannot write to journal",
+            ExchangeError::JournalRead => "cannot read journal",
+            ExchangeError::JournalCorrupt => "journal is corrupt",
+            ExchangeError::SnapshotWrite => "cannot write snapshot",
+            ExchangeError::SnapshotRead => "cannot read snapshot",
+            ExchangeError::SnapshotCorrupt => "snapshot is corrupt",
+            ExchangeError::SnapshotVersion => "snapshot version is not supported",
+            ExchangeError::InvalidResultCapacity => "result capacity must be greater than zero",
+            ExchangeError::InvalidRingCapacity => "ring capacity must be greater than zero",
+        }
+    }
+}
+
+impl fmt::Display for ExchangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for ExchangeError {}
+
+impl Encode for ExchangeError {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        self.code().encode(encoder)
+    }
+}
+
+impl<Context> Decode<Context> for ExchangeError {
+    fn decode<D: Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, DecodeError> {
+        let code = u16::decode(decoder)?;
+        match ExchangeError::from_code(code) {
+            Some(error) => Ok(error),
+            None => Err(DecodeError::OtherString(format!(
+                "unknown error code {}",
+                code
+            ))),
+        }
+    }
+}
+
+bincode::impl_borrow_decode!(ExchangeError);
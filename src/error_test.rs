@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+
+    use std::collections::HashSet;
+
+    use crate::error::ExchangeError;
+
+    const ALL_ERRORS: [ExchangeError; 48] = [
+        ExchangeError::MarketNotFound,
+        ExchangeError::MarketAlreadyExists,
+        ExchangeError::OrderBookNotFound,
+        ExchangeError::InvalidPriceRange,
+        ExchangeError::InvalidTickSize,
+        ExchangeError::InvalidLotSize,
+        ExchangeError::InvalidQuantityRange,
+        ExchangeError::InvalidScale,
+        ExchangeError::PriceBelowMin,
+        ExchangeError::PriceAboveMax,
+        ExchangeError::PriceNotOnTick,
+        ExchangeError::SizeNotOnLot,
+        ExchangeError::DisplaySizeNotOnLot,
+        ExchangeError::SizeBelowMin,
+        ExchangeError::SizeAboveMax,
+        ExchangeError::NotionalBelowMin,
+        ExchangeError::TriggerPriceBelowMin,
+        ExchangeError::TriggerPriceAboveMax,
+        ExchangeError::ExpireTimeInPast,
+        ExchangeError::AmendSizeZero,
+        ExchangeError::SizeZero,
+        ExchangeError::PriceNotRepresentable,
+        ExchangeError::QuantityNotRepresentable,
+        ExchangeError::FillOrKillNotFilled,
+        ExchangeError::PostOnlyNotLimit,
+        ExchangeError::PostOnlyWouldTake,
+        ExchangeError::PostOnlySlideBelowMin,
+        ExchangeError::PostOnlySlideAboveMax,
+        ExchangeError::OrderNotFound,
+        ExchangeError::ClockMovedBackwards,
+        ExchangeError::CapacityExhausted,
+        ExchangeError::ResultExpired,
+        ExchangeError::DepthLevelsAboveMax,
+        ExchangeError::InvalidDecimal,
+        ExchangeError::DecimalTooLarge,
+        ExchangeError::DecimalTooPrecise,
+        ExchangeError::PayloadEncoding,
+        ExchangeError::PayloadDecoding,
+        ExchangeError::ChannelClosed,
+        ExchangeError::JournalWrite,
+        ExchangeError::JournalRead,
+        ExchangeError::JournalCorrupt,
+        ExchangeError::SnapshotWrite,
+        ExchangeError::SnapshotRead,
+        ExchangeError::SnapshotCorrupt,
+        ExchangeError::SnapshotVersion,
+        ExchangeError::InvalidResultCapacity,
+        ExchangeError::InvalidRingCapacity,
+    ];
+
+    #[test]
+    fn test_codes_are_unique() {
+        let mut codes = HashSet::new();
+        let mut errors = HashSet::new();
+        for error in ALL_ERRORS {
+            if errors.insert(error) {
+                assert!(
+                    codes.insert(error.code()),
+                    "duplicate code {}",
+                    error.code()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_codes_are_stable() {
+        assert_eq!(ExchangeError::MarketNotFound.code(), 100);
+        assert_eq!(ExchangeError::PriceBelowMin.code(), 200);
+        assert_eq!(ExchangeError::FillOrKillNotFilled.code(), 300);
+        assert_eq!(ExchangeError::OrderNotFound.code(), 400);
+        assert_eq!(ExchangeError::InvalidDecimal.code(), 500);
+        assert_eq!(ExchangeError::PayloadEncoding.code(), 900);
+    }
+
+    #[test]
+    fn test_from_code() {
+        for error in ALL_ERRORS {
+            assert_eq!(ExchangeError::from_code(error.code()), Some(error));
+        }
+        assert_eq!(ExchangeError::from_code(0), None);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(ExchangeError::OrderNotFound.to_string(), "order not found");
+        assert_eq!(
+            ExchangeError::PostOnlyWouldTake.to_string(),
+            "post only order would take liquidity"
+        );
+    }
+
+    #[test]
+    fn test_encode_and_decode() {
+        let config = bincode::config::standard();
+        for error in ALL_ERRORS {
+            let encoded = bincode::encode_to_vec(error, config).unwrap();
+            let (decoded, _): (ExchangeError, usize) =
+                bincode::decode_from_slice(&encoded, config).unwrap();
+            assert_eq!(decoded, error);
+        }
+        let result: Result<u64, ExchangeError> = Err(ExchangeError::SizeAboveMax);
+        let encoded = bincode::encode_to_vec(result, config).unwrap();
+        let (decoded, _): (Result<u64, ExchangeError>, usize) =
+            bincode::decode_from_slice(&encoded, config).unwrap();
+        assert_eq!(decoded, result);
+    }
+
+    #[test]
+    fn test_decode_unknown_code() {
+        let config = bincode::config::standard();
+        let encoded = bincode::encode_to_vec(999u16, config).unwrap();
+        let decoded: Result<(ExchangeError, usize), _> =
+            bincode::decode_from_slice(&encoded, config);
+        assert!(decoded.is_err());
+    }
+}
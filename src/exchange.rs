@@ -1,279 +1,595 @@
-use bincode::{Decode, Encode};
 use std::{
     collections::HashMap,
-    sync::{
-        Arc, RwLock,
-        mpsc::{self, Sender},
-    },
+    path::{Path, PathBuf},
+    sync::mpsc,
     thread,
+    time::{Duration, Instant},
 };
 
 use crate::{
+    completion::{Completion, CompletionQueue, RequestResult, ResultHandle},
+    config::{ExchangeConfig, JournalConfig, Transport},
+    error::ExchangeError,
+    journal::{Journal, JournalEntry},
     market::Market,
-    order::Order,
-    order_book::{BestQuote, OrderBook},
-    request::{CancelOrderRequest, MarketRequest, RequestType, SignedRequest, SubmitOrderRequest},
+    order::{Order, Side},
+    order_book::{BestQuote, Depth, OrderBook},
+    published_depth::PublishedDepth,
+    published_quote::{PublishedQuote, QuoteSnapshot},
+    request::{
+        AdvanceClockRequest, AmendOrderRequest, CancelOrderRequest, DecimalAmendOrderRequest,
+        DecimalSubmitOrderRequest, MarketRequest, SignedRequest, SubmitOrderRequest,
+    },
+    response::{DecimalBestQuote, DecimalSubmitOrderResponse, SubmitOrderResponse},
+    snapshot::{self, ExchangeSnapshot},
     state::State,
+    sync::Arc,
+    worker::{self, BookQuery, Worker, WorkerMessage, WorkerSender},
 };
 
 pub struct Exchange {
-    channels: Vec<Sender<SignedRequest>>,
+    channels: Vec<WorkerSender>,
     channel_by_symbol: HashMap<String, usize>,
     last_req_id: u128,
-    markets_lock: Arc<RwLock<bool>>,
+    markets: HashMap<String, Market>,
+    symbols: Vec<String>,
+    quotes: HashMap<String, Arc<PublishedQuote>>,
+    depths: HashMap<String, Arc<PublishedDepth>>,
+    depth_levels: usize,
     state: Arc<State>,
+    journal: Option<Journal>,
+    snapshot_path: Option<PathBuf>,
+}
+
+impl Default for Exchange {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Exchange {
     /// Creates a new exchange
     pub fn new() -> Self {
+        Self::start(&ExchangeConfig::default())
+    }
+
+    /// Creates a new exchange that keeps the results of up to the given number of
+    /// requests
+    ///
+    /// Once more requests have been sent, the oldest uncollected results are
+    /// overwritten and collecting them gives a `ResultExpired` error
+    pub fn with_result_capacity(result_capacity: usize) -> Self {
+        let mut config = ExchangeConfig::default();
+        config.set_result_capacity(result_capacity);
+        Self::start(&config)
+    }
+
+    /// Creates a new exchange with the given settings
+    ///
+    /// If the settings include a snapshot that exists, the order books are first
+    /// restored from it. If they include a journal, the order books are then brought up
+    /// to date by replaying the requests written to the journal after the snapshot, and
+    /// every request accepted from then on is written to the journal before it is
+    /// dispatched. Settings that cannot be used give an error before anything is started
+    pub fn with_config(config: ExchangeConfig) -> Result<Self, ExchangeError> {
+        config.validate()?;
+        let mut exchange = Self::start(&config);
+        let mut journal_sequence = 0;
+        if let Some(snapshot_path) = config.get_snapshot_path() {
+            journal_sequence = exchange.restore_snapshot(snapshot_path)?;
+        }
+        if let Some(journal_config) = config.get_journal() {
+            exchange.replay_journal(journal_config, journal_sequence)?;
+        }
+        Ok(exchange)
+    }
+
+    /// Creates a new exchange with the given settings and starts its worker threads,
+    /// without a journal
+    fn start(config: &ExchangeConfig) -> Self {
         println!("initializing the exchange...");
-        let mut channels: Vec<Sender<SignedRequest>> = Vec::new();
-        let state = Arc::new(State::new());
-        let markets_lock = Arc::new(RwLock::new(true));
-        Self::setup_worker_threads(&mut channels, &state, &markets_lock);
+        let mut channels: Vec<WorkerSender> = Vec::new();
+        let state = Arc::new(State::new(config.get_result_capacity()));
+        Self::setup_worker_threads(&mut channels, &state, config.get_transport());
         Self {
             state,
             channels,
             channel_by_symbol: HashMap::new(),
             last_req_id: 0,
-            markets_lock,
+            markets: HashMap::new(),
+            symbols: Vec::new(),
+            quotes: HashMap::new(),
+            depths: HashMap::new(),
+            depth_levels: config.get_depth_levels(),
+            journal: None,
+            snapshot_path: config.get_snapshot_path().map(Path::to_path_buf),
+        }
+    }
+
+    /// Restore the markets and order books from the snapshot in the given file, if it
+    /// exists
+    ///
+    /// Returns the sequence number of the last journal record included in the snapshot
+    fn restore_snapshot(&mut self, path: &Path) -> Result<u64, ExchangeError> {
+        let Some(snapshot) = snapshot::read_snapshot(path)? else {
+            return Ok(0);
+        };
+        println!(
+            "restoring {} order books from snapshot...",
+            snapshot.order_books.len()
+        );
+        for order_book in snapshot.order_books {
+            let order_book = OrderBook::restore(order_book)?;
+            if self
+                .markets
+                .contains_key(order_book.get_market().get_symbol())
+            {
+                return Err(ExchangeError::SnapshotCorrupt);
+            }
+            self.add_order_book(order_book)?;
+        }
+        self.last_req_id = snapshot.last_request_id;
+        Ok(snapshot.journal_sequence)
+    }
+
+    /// Bring the markets and order books up to date with the records of the journal
+    /// after the given sequence number, then start journaling
+    ///
+    /// Replayed requests keep their original IDs, and their results are discarded, as
+    /// they were delivered before the restart. This returns once every replayed request
+    /// has been processed
+    fn replay_journal(
+        &mut self,
+        journal_config: &JournalConfig,
+        after_sequence: u64,
+    ) -> Result<(), ExchangeError> {
+        let (journal, records) =
+            Journal::open(journal_config.get_path(), journal_config.get_fsync_policy())?;
+        // the journal is synced before each snapshot, so it always reaches the snapshot,
+        // and is only compacted up to the snapshot it is restored with
+        if journal.get_next_sequence() <= after_sequence
+            || journal.get_first_sequence() > after_sequence + 1
+        {
+            return Err(ExchangeError::JournalCorrupt);
+        }
+        let records: Vec<_> = records
+            .into_iter()
+            .filter(|record| record.sequence > after_sequence)
+            .collect();
+        println!("replaying {} journal records...", records.len());
+        for record in records {
+            match record.entry {
+                JournalEntry::ListMarket(market) => {
+                    self.list_market(market)?;
+                }
+                JournalEntry::Request { id, request } => {
+                    self.last_req_id = id;
+                    self.dispatch_request(id, request, Completion::Discard)?;
+                }
+            }
+        }
+        // a query is answered after the requests sent before it, so once every market
+        // has answered, the order books are up to date
+        let symbols: Vec<String> = self.markets.keys().cloned().collect();
+        for symbol in symbols {
+            self.query_order_book(&symbol, |_| ())?;
+        }
+        self.journal = Some(journal);
+        Ok(())
+    }
+
+    /// Write a snapshot of every market and order book to the given file
+    ///
+    /// The snapshot is taken between requests, after every request accepted so far has
+    /// been processed, and records the position of the journal it was taken at, so an
+    /// exchange restored from it only needs to replay the journal records that follow.
+    /// When the file is the snapshot path of the exchange config, the journal records it
+    /// covers are then removed, so a restart does not read them again
+    pub fn save_snapshot(&mut self, path: &Path) -> Result<(), ExchangeError> {
+        let journal_sequence = match &mut self.journal {
+            Some(journal) => {
+                journal.sync()?;
+                journal.get_next_sequence() - 1
+            }
+            None => 0,
+        };
+        let mut order_books = Vec::with_capacity(self.symbols.len());
+        for symbol in &self.symbols {
+            order_books.push(self.query_order_book(symbol, |order_book| order_book.snapshot())?);
+        }
+        let snapshot = ExchangeSnapshot {
+            journal_sequence,
+            last_request_id: self.last_req_id,
+            order_books,
+        };
+        snapshot::write_snapshot(path, &snapshot)?;
+        if self.snapshot_path.as_deref() == Some(path)
+            && let Some(journal) = &mut self.journal
+        {
+            journal.compact()?;
+        }
+        Ok(())
+    }
+
+    /// Flush every journal record written so far to disk
+    ///
+    /// Only needed with the `Batch` or `None` fsync policies, to make sure the requests
+    /// accepted so far survive a crash
+    pub fn sync_journal(&mut self) -> Result<(), ExchangeError> {
+        match &mut self.journal {
+            Some(journal) => journal.sync(),
+            None => Ok(()),
         }
     }
 
     /// Setup worker threads used to process incoming requests
     fn setup_worker_threads(
-        channels: &mut Vec<Sender<SignedRequest>>,
+        channels: &mut Vec<WorkerSender>,
         state: &Arc<State>,
-        markets_lock: &Arc<RwLock<bool>>,
+        transport: Transport,
     ) {
         // total CPUs
         let core_ids = core_affinity::get_core_ids().unwrap();
         // for each core, spawn a worker thread
         for core_id in core_ids {
             // create new channel used to send requests to the worker thread
-            let (tx, rx) = mpsc::channel();
+            let (tx, rx) = worker::channel(transport);
             channels.push(tx);
-            // clone the state so each thread can hold its own reference
-            let state_clone = state.clone();
-            let markets_lock_clone = markets_lock.clone();
+            // each worker holds its own reference to the shared request results
+            let worker = Worker::new(state.clone());
             thread::spawn(move || {
                 core_affinity::set_for_current(core_id);
-                // process incoming requests
-                for request in rx {
-                    let lock = markets_lock_clone.read();
-                    Self::handle_signed_request(&state_clone, request);
-                    drop(lock);
-                }
+                worker.run(rx);
             });
             println!("created worker thread {}", core_id.id);
         }
     }
 
-    /// Handle a signed request when it is received by a worker thread
-    fn handle_signed_request(state: &Arc<State>, request: SignedRequest) {
-        let request_id = request.id;
-        match request.request_type {
-            RequestType::SubmitOrder => {
-                let mut request: SubmitOrderRequest = Self::decode_payload(request.payload);
-                let result = Self::handle_submit_order(state, &mut request);
-                state.save_request_result(request_id, result);
-            }
-            RequestType::CancelOrder => {
-                let mut request: CancelOrderRequest = Self::decode_payload(request.payload);
-                let result = Self::handle_cancel_order(state, &mut request);
-                state.save_request_result(request_id, result);
-            }
-        }
-    }
-
-    /// Decode binary payload into request object
-    fn decode_payload<T: Decode<()>>(payload: Vec<u8>) -> T {
-        let (request, _): (T, usize) =
-            bincode::decode_from_slice(&payload, bincode::config::standard()).unwrap();
-        request
-    }
-
-    /// Handle new order submission requests
-    fn handle_submit_order(
-        state: &Arc<State>,
-        request: &mut SubmitOrderRequest,
-    ) -> Result<(RequestType, Vec<u8>), String> {
-        let order_book = state.get_order_book_by_symbol(request.get_symbol())?;
-        let id = order_book.submit_order(request)?;
-        let response = bincode::encode_to_vec(id, bincode::config::standard()).unwrap();
-        Ok((RequestType::SubmitOrder, response))
-    }
-
-    /// Handle order cancellation requests
-    fn handle_cancel_order(
-        state: &Arc<State>,
-        request: &mut CancelOrderRequest,
-    ) -> Result<(RequestType, Vec<u8>), String> {
-        let order_book = state.get_order_book_by_symbol(request.get_symbol())?;
-        let result = order_book.cancel_order(request)?;
-        let response = bincode::encode_to_vec(result, bincode::config::standard()).unwrap();
-        Ok((RequestType::CancelOrder, response))
-    }
-
     /// Get the channel ID for the given symbol
     ///
     /// Each market submits requests exclusively to a single channel (aka worker thread)
     ///
     /// Multiple markets can be processed by a single worker thread, if there are more
     /// listed markets than physical cores
-    fn get_channel_id(&self, symbol: &str) -> Result<usize, String> {
+    fn get_channel_id(&self, symbol: &str) -> Result<usize, ExchangeError> {
         match self.channel_by_symbol.get(symbol) {
             Some(id) => Ok(*id),
-            None => Err("market not found".to_string()),
+            None => Err(ExchangeError::MarketNotFound),
         }
     }
 
     /// Get the channel transmitter for given channel ID
-    fn get_channel(&self, id: usize) -> Result<&Sender<SignedRequest>, String> {
+    fn get_channel(&self, id: usize) -> Result<&WorkerSender, ExchangeError> {
         match self.channels.get(id) {
             Some(sender) => Ok(sender),
-            None => Err("market not found".to_string()),
+            None => Err(ExchangeError::MarketNotFound),
         }
     }
 
-    /// Encode binary payload from request object
-    fn build_payload<T: Encode>(&self, request: T) -> Result<Vec<u8>, String> {
-        match bincode::encode_to_vec(request, bincode::config::standard()) {
-            Ok(res) => Ok(res),
-            Err(err) => Err(format!("cannot build payload: {}", err)),
+    /// Allocate the ID of the next request
+    ///
+    /// IDs are never reused, so a result slot can tell a stale ID from the current one
+    fn next_request_id(&mut self) -> u128 {
+        self.last_req_id += 1;
+        self.last_req_id
+    }
+
+    /// Send a request with an allocated ID to the channel allocated to the specified
+    /// market
+    ///
+    /// With a journal, the request is written to the journal first, and is not
+    /// dispatched if that fails
+    fn dispatch_request<T: MarketRequest>(
+        &mut self,
+        id: u128,
+        request: T,
+        completion: Completion,
+    ) -> Result<(), ExchangeError> {
+        let channel_id = self.get_channel_id(request.get_symbol())?;
+        let request = request.into_record();
+        if let Some(journal) = &mut self.journal {
+            journal.append_request(id, &request)?;
+        }
+        if let Completion::Store = completion {
+            self.state.reserve_request_result(id);
         }
+        let sender = self.get_channel(channel_id)?;
+        sender.send(WorkerMessage::Request(SignedRequest {
+            id,
+            request,
+            completion,
+        }))
     }
 
     /// Submit incoming requests for async processing, by sending them to the
     /// channel allocated to the specified market
-    fn handle_request<T: Encode + MarketRequest>(
+    fn handle_request<T: MarketRequest>(
         &mut self,
         request: T,
-        request_type: RequestType,
-    ) -> Result<u128, String> {
-        if self.last_req_id == (self.state.get_request_results().len() as u128) - 1 {
-            self.last_req_id = 0;
-        }
-        self.last_req_id += 1;
-        let channel_id = self.get_channel_id(request.get_symbol())?;
+        completion: Completion,
+    ) -> Result<u128, ExchangeError> {
+        let id = self.next_request_id();
+        self.dispatch_request(id, request, completion)?;
+        Ok(id)
+    }
+
+    /// Submit a request whose result is delivered to the returned handle
+    ///
+    /// The result is not stored by the exchange, so it can only be taken from the handle
+    pub fn send_with_handle<T: MarketRequest>(
+        &mut self,
+        request: T,
+    ) -> Result<ResultHandle, ExchangeError> {
+        let id = self.next_request_id();
+        let (handle, completion) = ResultHandle::new(id);
+        self.dispatch_request(id, request, completion)?;
+        Ok(handle)
+    }
+
+    /// Submit a request whose result is pushed onto the completion queue
+    pub fn send_to_queue<T: MarketRequest>(&mut self, request: T) -> Result<u128, ExchangeError> {
+        self.handle_request(request, Completion::Queue)
+    }
+
+    /// Submit a request whose result is passed to the given callback
+    ///
+    /// Note: the callback runs on the worker thread of the market, so it should return
+    /// quickly to avoid delaying other requests
+    pub fn send_with_callback<T, F>(
+        &mut self,
+        request: T,
+        callback: F,
+    ) -> Result<u128, ExchangeError>
+    where
+        T: MarketRequest,
+        F: FnOnce(u128, RequestResult) + Send + 'static,
+    {
+        self.handle_request(request, Completion::Callback(Box::new(callback)))
+    }
+
+    /// Get the number of request results kept before the oldest are overwritten
+    pub fn get_result_capacity(&self) -> usize {
+        self.state.get_result_capacity()
+    }
+
+    /// Get the queue of completed requests sent with `send_to_queue`
+    pub fn get_completion_queue(&self) -> &CompletionQueue {
+        self.state.get_completion_queue()
+    }
+
+    /// Run a read-only query against the order book of the specified market
+    ///
+    /// The query runs on the worker thread that owns the book, between requests, and
+    /// this function blocks until it has answered
+    fn query_order_book<R, F>(&self, symbol: &str, query: F) -> Result<R, ExchangeError>
+    where
+        R: Send + 'static,
+        F: FnOnce(&OrderBook) -> R + Send + 'static,
+    {
+        let channel_id = self.get_channel_id(symbol)?;
         let sender = self.get_channel(channel_id)?;
-        let payload = self.build_payload(request)?;
-        let result = sender.send(SignedRequest {
-            id: self.last_req_id,
-            request_type,
-            payload,
+        let (reply, answer) = mpsc::sync_channel(1);
+        let query: BookQuery = Box::new(move |order_book| {
+            // the exchange may have stopped waiting, in which case the answer is dropped
+            let _ = reply.send(order_book.map(query));
         });
-        match result {
-            Ok(_) => Ok(self.last_req_id),
-            Err(err) => Err(err.to_string()),
+        sender.send(WorkerMessage::Query(symbol.to_string(), query))?;
+        match answer.recv() {
+            Ok(result) => result,
+            Err(_) => Err(ExchangeError::ChannelClosed),
         }
     }
 
     /// Get all markets
     pub fn get_markets(&self) -> Vec<Market> {
-        self.state.get_markets().values().cloned().collect()
+        self.markets.values().cloned().collect()
+    }
+
+    /// Get the market with the specified symbol
+    pub fn get_market(&self, symbol: &String) -> Result<&Market, ExchangeError> {
+        match self.markets.get(symbol) {
+            Some(market) => Ok(market),
+            None => Err(ExchangeError::MarketNotFound),
+        }
     }
 
     /// List a new market
     ///
-    /// Note: since this function modifies the size of the markets hashmap, it must
-    /// lock the internal state until the market is added to prevent the matching
-    /// engine from modifying memory addresses that have been de-allocated
-    pub fn list_market(
-        &mut self,
-        symbol: &str,
-        max_price: u64,
-        min_price: u64,
-    ) -> Result<&Market, String> {
-        let markets = self.state.get_markets();
-        if markets.get(symbol).is_none() {
-            let market = Market::new(symbol, max_price, min_price);
-            let channel_id = markets.len() % self.channels.len();
-            self.channel_by_symbol
-                .insert(symbol.to_string(), channel_id);
-            markets.insert(symbol.to_string(), market.clone());
-            // we need exclusive write access here as this operation allocates heap memory
-            let lock = self.markets_lock.write();
-            // create new order book
-            self.state
-                .get_order_books()
-                .insert(symbol.to_string(), OrderBook::new(market));
-            // drop the lock now that we're done
-            drop(lock);
-            Ok(markets.get(symbol).unwrap())
-        } else {
-            Err("market already exists".to_string())
+    /// The order book of the market is created with the ladder type of the market, and
+    /// handed over to the worker thread allocated to the market, which owns it from then on
+    pub fn list_market(&mut self, market: Market) -> Result<&Market, ExchangeError> {
+        market.validate()?;
+        let symbol = market.get_symbol().clone();
+        if self.markets.contains_key(&symbol) {
+            return Err(ExchangeError::MarketAlreadyExists);
         }
+        if let Some(journal) = &mut self.journal {
+            journal.append_market(&market)?;
+        }
+        self.add_order_book(OrderBook::new(market))?;
+        Ok(&self.markets[&symbol])
+    }
+
+    /// Hand over the order book of a market to the worker thread allocated to it
+    fn add_order_book(&mut self, mut order_book: OrderBook) -> Result<(), ExchangeError> {
+        order_book.set_published_depth_levels(self.depth_levels);
+        let market = order_book.get_market().clone();
+        let symbol = market.get_symbol().clone();
+        let channel_id = self.markets.len() % self.channels.len();
+        let quote = order_book.get_published_quote().clone();
+        let depth = order_book.get_published_depth().clone();
+        // the order book is sent down the same channel as the requests of the market,
+        // so it is in place before any of them are processed
+        self.channels[channel_id].send(WorkerMessage::AddOrderBook(Box::new(order_book)))?;
+        self.channel_by_symbol.insert(symbol.clone(), channel_id);
+        self.quotes.insert(symbol.clone(), quote);
+        self.depths.insert(symbol.clone(), depth);
+        self.symbols.push(symbol.clone());
+        self.markets.insert(symbol, market);
+        Ok(())
     }
 
     /// Submit a new order
-    pub fn submit_order(&mut self, request: SubmitOrderRequest) -> Result<u128, String> {
-        self.handle_request(request, RequestType::SubmitOrder)
+    pub fn submit_order(&mut self, request: SubmitOrderRequest) -> Result<u128, ExchangeError> {
+        self.handle_request(request, Completion::Store)
+    }
+
+    /// Submit a new order with decimal price and quantity
+    ///
+    /// The price and quantity are converted to the integer prices and sizes of the order
+    /// book using the scales of the market, and the order is rejected if they have more
+    /// decimal places than the market allows
+    pub fn submit_decimal_order(
+        &mut self,
+        request: DecimalSubmitOrderRequest,
+    ) -> Result<u128, ExchangeError> {
+        let request = request.to_request(self.get_market(&request.symbol)?)?;
+        self.submit_order(request)
     }
 
     /// Cancel an order
-    pub fn cancel_order(&mut self, request: CancelOrderRequest) -> Result<u128, String> {
-        self.handle_request(request, RequestType::CancelOrder)
+    pub fn cancel_order(&mut self, request: CancelOrderRequest) -> Result<u128, ExchangeError> {
+        self.handle_request(request, Completion::Store)
+    }
+
+    /// Amend the price and/or size of an order
+    pub fn amend_order(&mut self, request: AmendOrderRequest) -> Result<u128, ExchangeError> {
+        self.handle_request(request, Completion::Store)
+    }
+
+    /// Amend the price and/or size of an order with decimal price and quantity
+    pub fn amend_decimal_order(
+        &mut self,
+        request: DecimalAmendOrderRequest,
+    ) -> Result<u128, ExchangeError> {
+        let request = request.to_request(self.get_market(&request.symbol)?)?;
+        self.amend_order(request)
+    }
+
+    /// Advance the clock of a market, expiring good-till-date and day orders
+    pub fn advance_clock(&mut self, request: AdvanceClockRequest) -> Result<u128, ExchangeError> {
+        self.handle_request(request, Completion::Store)
     }
 
     /// Get the request results in binary format for a list of known request IDs
     ///
     /// Note: this function will block until all requests have either completed
-    /// successfully or otherwise failed
-    pub fn get_results(
+    /// successfully or otherwise failed. Each result is handed out once, so a request
+    /// ID can only be collected by a single call, and collecting it again gives a
+    /// `ResultExpired` error
+    pub fn get_results(&self, request_ids: Vec<u128>) -> Vec<RequestResult> {
+        request_ids
+            .into_iter()
+            .filter_map(|id| self.state.take_request_result(id, None))
+            .collect()
+    }
+
+    /// Get the request results in binary format for a list of known request IDs,
+    /// waiting at most for the given timeout
+    ///
+    /// Requests that have not completed before the timeout are returned as `None`,
+    /// and their results can be collected by a later call
+    pub fn get_results_timeout(
         &self,
-        request_ids: Vec<u128>,
-    ) -> Vec<&Result<(RequestType, Vec<u8>), String>> {
-        let mut results = Vec::new();
-        for id in request_ids {
-            loop {
-                if let Some(Some(result)) = self.state.get_request_results().get(&id) {
-                    results.push(result);
-                    break;
-                }
-            }
-        }
-        results
+        request_ids: &[u128],
+        timeout: Duration,
+    ) -> Vec<Option<RequestResult>> {
+        let deadline = Instant::now() + timeout;
+        request_ids
+            .iter()
+            .map(|id| self.state.take_request_result(*id, Some(deadline)))
+            .collect()
     }
 
     /// Get the best bid and best ask for the specified market
-    pub fn get_best_quote(&self, symbol: &String) -> Result<BestQuote, String> {
-        let order_book = self.state.get_order_book_by_symbol(symbol)?;
-        let best_bid_price = order_book.get_best_bid_price();
-        let best_bid_size = order_book.get_best_bid_size();
-        let best_ask_price = order_book.get_best_ask_price();
-        let best_ask_size = order_book.get_best_ask_size();
-        Ok(BestQuote {
-            best_bid_price,
-            best_bid_size,
-            best_ask_price,
-            best_ask_size,
-        })
+    ///
+    /// The quote is read from the seqlock published by the worker thread of the market,
+    /// so the prices and sizes are all from the same state of the book and the worker is
+    /// never blocked
+    pub fn get_best_quote(&self, symbol: &str) -> Result<BestQuote, ExchangeError> {
+        Ok(self.get_quote_snapshot(symbol)?.best_quote)
     }
 
-    /// Get all orders for the specified market
-    pub fn get_orders_by_symbol(&self, symbol: &String) -> Result<Vec<Order>, String> {
-        let order_book = self.state.get_order_book_by_symbol(symbol)?;
-        let bids = order_book.get_bids();
-        let asks = order_book.get_asks();
-        let mut orders = Vec::new();
-        for bid in bids {
-            for order in bid.get_orders() {
-                if order.get_size() == 0 {
-                    break;
-                }
-                orders.push(*order);
-            }
+    /// Get the best bid and best ask for the specified market, with the number of quote
+    /// updates published so far and the sequence number of the last event in the market
+    pub fn get_quote_snapshot(&self, symbol: &str) -> Result<QuoteSnapshot, ExchangeError> {
+        match self.quotes.get(symbol) {
+            Some(quote) => Ok(quote.read()),
+            None => Err(ExchangeError::MarketNotFound),
         }
-        for ask in asks {
-            for order in ask.get_orders() {
-                if order.get_size() == 0 {
-                    break;
-                }
-                orders.push(*order);
-            }
+    }
+
+    /// Get a handle to the best quote published for the specified market
+    ///
+    /// The exchange itself is not `Sync`, because each worker transport has a single
+    /// producer. The handle is, so it can be moved to other threads to read the quote
+    /// while the exchange keeps sending requests, without going through the transport
+    pub fn quote_reader(&self, symbol: &str) -> Result<Arc<PublishedQuote>, ExchangeError> {
+        match self.quotes.get(symbol) {
+            Some(quote) => Ok(quote.clone()),
+            None => Err(ExchangeError::MarketNotFound),
+        }
+    }
+
+    /// Get the best bid and best ask for the specified market with decimal prices and
+    /// quantities
+    pub fn get_decimal_best_quote(
+        &self,
+        symbol: &String,
+    ) -> Result<DecimalBestQuote, ExchangeError> {
+        let best_quote = self.get_best_quote(symbol)?;
+        let market = self.get_market(symbol)?;
+        Ok(DecimalBestQuote::from_best_quote(&best_quote, market))
+    }
+
+    /// Decode the result payload of an order submission or amendment in the specified
+    /// market, converting its trades to decimal prices and quantities
+    pub fn decode_submit_order_response(
+        &self,
+        symbol: &String,
+        payload: &[u8],
+    ) -> Result<DecimalSubmitOrderResponse, ExchangeError> {
+        let market = self.get_market(symbol)?;
+        let response: SubmitOrderResponse =
+            match bincode::decode_from_slice(payload, bincode::config::standard()) {
+                Ok((response, _)) => response,
+                Err(_) => return Err(ExchangeError::PayloadDecoding),
+            };
+        Ok(DecimalSubmitOrderResponse::from_response(&response, market))
+    }
+
+    /// Get up to the specified number of aggregated price levels on each side of the
+    /// specified market, best price first
+    ///
+    /// The levels are published by the worker thread that owns the book together with
+    /// the quote, so reading them never waits for the worker and both sides are from the
+    /// same state of the book as the returned sequence number. Returns an error if more
+    /// levels are asked for than the depth levels of the exchange config
+    pub fn get_depth(&self, symbol: &str, levels: usize) -> Result<Depth, ExchangeError> {
+        match self.depths.get(symbol) {
+            Some(depth) if levels > depth.get_levels() => Err(ExchangeError::DepthLevelsAboveMax),
+            Some(depth) => Ok(depth.read(levels)),
+            None => Err(ExchangeError::MarketNotFound),
         }
-        Ok(orders)
+    }
+
+    /// Get a handle to the price levels published for the specified market
+    ///
+    /// Like the quote reader, the handle can be moved to other threads to read the
+    /// levels while the exchange keeps sending requests
+    pub fn depth_reader(&self, symbol: &str) -> Result<Arc<PublishedDepth>, ExchangeError> {
+        match self.depths.get(symbol) {
+            Some(depth) => Ok(depth.clone()),
+            None => Err(ExchangeError::MarketNotFound),
+        }
+    }
+
+    /// Get all orders for the specified market
+    pub fn get_orders_by_symbol(&self, symbol: &str) -> Result<Vec<Order>, ExchangeError> {
+        self.query_order_book(symbol, |order_book| {
+            let mut orders = order_book.get_orders(Side::Buy);
+            orders.extend(order_book.get_orders(Side::Sell));
+            orders
+        })
     }
 }
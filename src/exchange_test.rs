@@ -0,0 +1,477 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::{Arc, mpsc};
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::config::{ExchangeConfig, FsyncPolicy, JournalConfig, Transport};
+    use crate::error::ExchangeError;
+    use crate::exchange::Exchange;
+    use crate::journal::JOURNAL_HEADER_SIZE;
+    use crate::market::Market;
+    use crate::order::{OrderType, Side};
+    use crate::published_depth::PublishedDepth;
+    use crate::published_quote::PublishedQuote;
+    use crate::request::{CancelOrderRequest, RequestType, SubmitOrderRequest};
+    use crate::response::SubmitOrderResponse;
+
+    fn assert_send<T: Send>() {}
+
+    fn assert_sync<T: Sync>() {}
+
+    fn limit_order(symbol: &str, side: Side, price: u64, size: u64) -> SubmitOrderRequest {
+        SubmitOrderRequest {
+            symbol: symbol.to_string(),
+            price,
+            size,
+            side,
+            order_type: OrderType::Limit,
+            ..Default::default()
+        }
+    }
+
+    fn decode_response(payload: &[u8]) -> SubmitOrderResponse {
+        bincode::decode_from_slice(payload, bincode::config::standard())
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_list_market_twice() {
+        let mut exchange = Exchange::with_result_capacity(16);
+        assert!(
+            exchange
+                .list_market(Market::new("BTCUSD", 1_000, 1))
+                .is_ok()
+        );
+        assert!(matches!(
+            exchange.list_market(Market::new("BTCUSD", 1_000, 1)),
+            Err(ExchangeError::MarketAlreadyExists)
+        ));
+        assert_eq!(exchange.get_markets().len(), 1);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_unknown_market() {
+        let mut exchange = Exchange::with_result_capacity(16);
+        let symbol = "BTCUSD".to_string();
+        assert_eq!(
+            exchange.submit_order(limit_order(&symbol, Side::Buy, 100, 1)),
+            Err(ExchangeError::MarketNotFound)
+        );
+        assert_eq!(
+            exchange.get_best_quote(&symbol),
+            Err(ExchangeError::MarketNotFound)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_reads_see_processed_requests() {
+        let mut exchange = Exchange::with_result_capacity(16);
+        let symbol = "BTCUSD".to_string();
+        exchange
+            .list_market(Market::new(&symbol, 1_000, 1))
+            .unwrap();
+        let ids = vec![
+            exchange
+                .submit_order(limit_order(&symbol, Side::Buy, 99, 3))
+                .unwrap(),
+            exchange
+                .submit_order(limit_order(&symbol, Side::Sell, 101, 2))
+                .unwrap(),
+        ];
+        let results = exchange.get_results(ids);
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|result| result.is_ok()));
+        // the quote is published before the results are delivered
+        let snapshot = exchange.get_quote_snapshot(&symbol).unwrap();
+        assert_eq!(snapshot.sequence, 2);
+        assert_eq!(snapshot.best_quote.best_bid_price, 99);
+        assert_eq!(snapshot.best_quote.best_bid_size, 3);
+        assert_eq!(snapshot.best_quote.best_ask_price, 101);
+        assert_eq!(snapshot.best_quote.best_ask_size, 2);
+        assert_eq!(
+            exchange.get_best_quote(&symbol).unwrap(),
+            snapshot.best_quote
+        );
+        // queries are processed after the requests sent before them
+        assert_eq!(exchange.get_orders_by_symbol(&symbol).unwrap().len(), 2);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_result_collected_once() {
+        let mut exchange = Exchange::with_result_capacity(16);
+        let symbol = "BTCUSD".to_string();
+        exchange
+            .list_market(Market::new(&symbol, 1_000, 1))
+            .unwrap();
+        let id = exchange
+            .submit_order(limit_order(&symbol, Side::Buy, 99, 1))
+            .unwrap();
+        let result = exchange.get_results(vec![id]).pop().unwrap();
+        let (request_type, payload) = result.unwrap();
+        assert_eq!(request_type, RequestType::SubmitOrder);
+        assert_eq!(decode_response(&payload).id, 1);
+        assert_eq!(
+            exchange.get_results(vec![id]),
+            vec![Err(ExchangeError::ResultExpired)]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_uncollected_result_expires() {
+        let mut exchange = Exchange::with_result_capacity(2);
+        assert_eq!(exchange.get_result_capacity(), 2);
+        let symbol = "BTCUSD".to_string();
+        exchange
+            .list_market(Market::new(&symbol, 1_000, 1))
+            .unwrap();
+        let ids: Vec<u128> = (0..3)
+            .map(|_| {
+                exchange
+                    .submit_order(limit_order(&symbol, Side::Buy, 99, 1))
+                    .unwrap()
+            })
+            .collect();
+        let results = exchange.get_results_timeout(&ids, Duration::from_secs(5));
+        assert_eq!(results[0], Some(Err(ExchangeError::ResultExpired)));
+        assert!(matches!(results[1], Some(Ok(_))));
+        assert!(matches!(results[2], Some(Ok(_))));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_send_with_handle() {
+        let mut exchange = Exchange::with_result_capacity(16);
+        let symbol = "BTCUSD".to_string();
+        exchange
+            .list_market(Market::new(&symbol, 1_000, 1))
+            .unwrap();
+        let handle = exchange
+            .send_with_handle(CancelOrderRequest {
+                id: 42,
+                symbol: symbol.clone(),
+            })
+            .unwrap();
+        assert_eq!(handle.wait(), Some(Err(ExchangeError::OrderNotFound)));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_send_to_queue() {
+        let mut exchange = Exchange::with_result_capacity(16);
+        let symbol = "BTCUSD".to_string();
+        exchange
+            .list_market(Market::new(&symbol, 1_000, 1))
+            .unwrap();
+        let id = exchange
+            .send_to_queue(limit_order(&symbol, Side::Sell, 101, 1))
+            .unwrap();
+        let (completed_id, result) = exchange
+            .get_completion_queue()
+            .wait_timeout(Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(completed_id, id);
+        assert!(result.is_ok());
+        assert!(exchange.get_completion_queue().is_empty());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_send_with_callback() {
+        let mut exchange = Exchange::with_result_capacity(16);
+        let symbol = "BTCUSD".to_string();
+        exchange
+            .list_market(Market::new(&symbol, 1_000, 1))
+            .unwrap();
+        let (sender, receiver) = mpsc::channel();
+        let id = exchange
+            .send_with_callback(limit_order(&symbol, Side::Buy, 0, 1), move |id, result| {
+                sender.send((id, result)).unwrap();
+            })
+            .unwrap();
+        let (completed_id, result) = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(completed_id, id);
+        assert_eq!(result, Err(ExchangeError::PriceBelowMin));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_recover_from_journal() {
+        let path = std::env::temp_dir().join(format!(
+            "order-book-exchange-{}.journal",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let mut journal_config = JournalConfig::new(&path);
+        journal_config.set_fsync_policy(FsyncPolicy::Batch { records: 4 });
+        let mut config = ExchangeConfig::default();
+        config.set_journal(Some(journal_config));
+        let symbol = "BTCUSD".to_string();
+
+        let mut exchange = Exchange::with_config(config.clone()).unwrap();
+        exchange
+            .list_market(Market::new(&symbol, 1_000, 1))
+            .unwrap();
+        let ids = vec![
+            exchange
+                .submit_order(limit_order(&symbol, Side::Buy, 99, 3))
+                .unwrap(),
+            exchange
+                .submit_order(limit_order(&symbol, Side::Sell, 101, 2))
+                .unwrap(),
+            exchange
+                .submit_order(limit_order(&symbol, Side::Buy, 98, 5))
+                .unwrap(),
+            exchange
+                .submit_order(limit_order(&symbol, Side::Buy, 101, 1))
+                .unwrap(),
+            exchange
+                .cancel_order(CancelOrderRequest {
+                    symbol: symbol.clone(),
+                    id: 3,
+                })
+                .unwrap(),
+        ];
+        // requests for unknown markets are rejected before they reach the journal
+        assert!(
+            exchange
+                .submit_order(limit_order("ETHUSD", Side::Buy, 99, 1))
+                .is_err()
+        );
+        assert!(
+            exchange
+                .get_results(ids)
+                .iter()
+                .all(|result| result.is_ok())
+        );
+        let orders = exchange.get_orders_by_symbol(&symbol).unwrap();
+        let quote = exchange.get_quote_snapshot(&symbol).unwrap();
+        // four accepts, one fill and one cancel
+        assert_eq!(quote.market_sequence, 6);
+        drop(exchange);
+
+        let mut exchange = Exchange::with_config(config).unwrap();
+        assert_eq!(exchange.get_markets().len(), 1);
+        assert_eq!(exchange.get_orders_by_symbol(&symbol).unwrap(), orders);
+        let recovered_quote = exchange.get_quote_snapshot(&symbol).unwrap();
+        assert_eq!(recovered_quote.best_quote, quote.best_quote);
+        assert_eq!(recovered_quote.market_sequence, quote.market_sequence);
+        // request IDs, order IDs and event sequence numbers carry on from where the
+        // journal left off
+        let id = exchange
+            .submit_order(limit_order(&symbol, Side::Sell, 105, 1))
+            .unwrap();
+        assert_eq!(id, 6);
+        let (_, payload) = exchange.get_results(vec![id]).pop().unwrap().unwrap();
+        let response = decode_response(&payload);
+        assert_eq!(response.id, 5);
+        assert_eq!(response.sequence, 7);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_recover_from_snapshot_and_journal_tail() {
+        let name = format!("order-book-exchange-tail-{}", std::process::id());
+        let journal_path = std::env::temp_dir().join(format!("{}.journal", name));
+        let snapshot_path = std::env::temp_dir().join(format!("{}.snapshot", name));
+        let _ = std::fs::remove_file(&journal_path);
+        let _ = std::fs::remove_file(&snapshot_path);
+        let mut config = ExchangeConfig::default();
+        config.set_journal(Some(JournalConfig::new(&journal_path)));
+        config.set_snapshot_path(Some(snapshot_path.clone()));
+        let symbols = ["BTCUSD".to_string(), "ETHUSD".to_string()];
+
+        let mut exchange = Exchange::with_config(config.clone()).unwrap();
+        let mut ids = Vec::new();
+        exchange
+            .list_market(Market::new(&symbols[0], 1_000, 1))
+            .unwrap();
+        ids.push(
+            exchange
+                .submit_order(limit_order(&symbols[0], Side::Buy, 99, 3))
+                .unwrap(),
+        );
+        ids.push(
+            exchange
+                .submit_order(limit_order(&symbols[0], Side::Sell, 102, 4))
+                .unwrap(),
+        );
+        exchange.save_snapshot(&snapshot_path).unwrap();
+        // the records covered by the snapshot are removed from the journal
+        assert_eq!(
+            std::fs::metadata(&journal_path).unwrap().len(),
+            JOURNAL_HEADER_SIZE as u64
+        );
+        // the journal tail lists a market and changes the book of the snapshot
+        exchange
+            .list_market(Market::new(&symbols[1], 1_000, 1))
+            .unwrap();
+        ids.push(
+            exchange
+                .submit_order(limit_order(&symbols[0], Side::Buy, 102, 1))
+                .unwrap(),
+        );
+        ids.push(
+            exchange
+                .submit_order(limit_order(&symbols[1], Side::Sell, 50, 7))
+                .unwrap(),
+        );
+        assert!(
+            exchange
+                .get_results(ids)
+                .iter()
+                .all(|result| result.is_ok())
+        );
+        let orders: Vec<_> = symbols
+            .iter()
+            .map(|symbol| exchange.get_orders_by_symbol(symbol).unwrap())
+            .collect();
+        drop(exchange);
+
+        let mut exchange = Exchange::with_config(config.clone()).unwrap();
+        assert_eq!(exchange.get_markets().len(), 2);
+        for (symbol, orders) in symbols.iter().zip(orders) {
+            assert_eq!(exchange.get_orders_by_symbol(symbol).unwrap(), orders);
+        }
+        let id = exchange
+            .submit_order(limit_order(&symbols[0], Side::Sell, 105, 1))
+            .unwrap();
+        assert_eq!(id, 5);
+        let (_, payload) = exchange.get_results(vec![id]).pop().unwrap().unwrap();
+        let response = decode_response(&payload);
+        assert_eq!(response.id, 4);
+        // two accepts from the snapshot, then an accept and a fill from the journal tail
+        assert_eq!(response.sequence, 5);
+        drop(exchange);
+
+        // the compacted journal cannot be replayed without the snapshot
+        std::fs::remove_file(&snapshot_path).unwrap();
+        assert!(matches!(
+            Exchange::with_config(config),
+            Err(ExchangeError::JournalCorrupt)
+        ));
+        std::fs::remove_file(&journal_path).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_get_depth() {
+        let mut config = ExchangeConfig::default();
+        config.set_depth_levels(2);
+        let mut exchange = Exchange::with_config(config).unwrap();
+        let symbol = "BTCUSD".to_string();
+        exchange
+            .list_market(Market::new(&symbol, 1_000, 1))
+            .unwrap();
+        let ids = [(Side::Buy, 99), (Side::Buy, 98), (Side::Sell, 101)]
+            .into_iter()
+            .map(|(side, price)| {
+                exchange
+                    .submit_order(limit_order(&symbol, side, price, 2))
+                    .unwrap()
+            })
+            .collect();
+        assert!(
+            exchange
+                .get_results(ids)
+                .iter()
+                .all(|result| result.is_ok())
+        );
+        // the depth is published before the results are delivered
+        let depth = exchange.get_depth(&symbol, 1).unwrap();
+        assert_eq!(depth.sequence, 3);
+        assert_eq!(depth.bids.len(), 1);
+        assert_eq!(depth.bids[0].price, 99);
+        assert_eq!(depth.asks.len(), 1);
+        assert_eq!(depth.asks[0].price, 101);
+        let depth = exchange.get_depth(&symbol, 2).unwrap();
+        assert_eq!(depth.bids.len(), 2);
+        assert_eq!(depth.bids[1].price, 98);
+        // asking for more levels than are published is an error, not a shorter answer
+        assert_eq!(
+            exchange.get_depth(&symbol, 3),
+            Err(ExchangeError::DepthLevelsAboveMax)
+        );
+        assert_eq!(
+            exchange.depth_reader(&symbol).unwrap().read(1).asks[0].size,
+            2
+        );
+        assert_eq!(
+            exchange.get_depth("ETHUSD", 1),
+            Err(ExchangeError::MarketNotFound)
+        );
+        assert!(matches!(
+            exchange.depth_reader("ETHUSD"),
+            Err(ExchangeError::MarketNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_thread_safety() {
+        assert_send::<Exchange>();
+        assert_send::<Arc<PublishedQuote>>();
+        assert_sync::<PublishedQuote>();
+        assert_send::<Arc<PublishedDepth>>();
+        assert_sync::<PublishedDepth>();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_quote_reader_on_other_thread() {
+        for transport in [Transport::Mpsc, Transport::Spsc { capacity: 4 }] {
+            let mut config = ExchangeConfig::default();
+            config.set_transport(transport);
+            let mut exchange = Exchange::with_config(config).unwrap();
+            let symbol = "BTCUSD".to_string();
+            exchange
+                .list_market(Market::new(&symbol, 1_000, 1))
+                .unwrap();
+            assert!(matches!(
+                exchange.quote_reader("ETHUSD"),
+                Err(ExchangeError::MarketNotFound)
+            ));
+            let quote = exchange.quote_reader(&symbol).unwrap();
+            let reader = thread::spawn(move || {
+                // wait for the bid sent by the exchange thread to be published
+                loop {
+                    let snapshot = quote.read();
+                    if snapshot.best_quote.best_bid_size > 0 {
+                        return snapshot.best_quote.best_bid_price;
+                    }
+                    thread::yield_now();
+                }
+            });
+            exchange
+                .submit_order(limit_order(&symbol, Side::Buy, 99, 3))
+                .unwrap();
+            assert_eq!(reader.join().unwrap(), 99);
+        }
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let mut config = ExchangeConfig::default();
+        config.set_result_capacity(0);
+        assert_eq!(config.validate(), Err(ExchangeError::InvalidResultCapacity));
+        assert!(matches!(
+            Exchange::with_config(config),
+            Err(ExchangeError::InvalidResultCapacity)
+        ));
+
+        let mut config = ExchangeConfig::default();
+        config.set_transport(Transport::Spsc { capacity: 0 });
+        assert_eq!(config.validate(), Err(ExchangeError::InvalidRingCapacity));
+        assert!(matches!(
+            Exchange::with_config(config),
+            Err(ExchangeError::InvalidRingCapacity)
+        ));
+    }
+}
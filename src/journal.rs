@@ -0,0 +1,366 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use bincode::{Decode, Encode};
+
+use crate::config::FsyncPolicy;
+use crate::crc32::{Crc32, crc32};
+use crate::error::ExchangeError;
+use crate::market::Market;
+use crate::request::RequestRecord;
+
+/// Bytes at the start of every journal file, ending with the version of the format
+pub const JOURNAL_MAGIC: [u8; 8] = *b"OBJRNL02";
+
+/// Size of the magic and the base sequence number written at the start of the file
+pub const JOURNAL_HEADER_SIZE: usize = JOURNAL_MAGIC.len() + 8;
+
+/// Size of the length and checksum written before the payload of each record
+const RECORD_HEADER_SIZE: usize = 8;
+
+/// Largest payload of a record
+///
+/// Entries are far smaller than this, so a record header claiming a longer payload was
+/// damaged rather than cut short by a crash
+const MAX_RECORD_SIZE: usize = 1 << 20;
+
+/// Change to the exchange recorded in the journal
+#[derive(Debug, Decode)]
+pub enum JournalEntry {
+    /// A market was listed
+    ListMarket(Market),
+    /// A request was accepted, with the ID it was given
+    Request { id: u128, request: RequestRecord },
+}
+
+/// Borrowed form of `JournalEntry`, encoded to the same bytes, so records can be
+/// written without cloning the request
+#[derive(Encode)]
+enum JournalEntryRef<'a> {
+    ListMarket(&'a Market),
+    Request {
+        id: u128,
+        request: &'a RequestRecord,
+    },
+}
+
+/// Entry read back from the journal, with its position in the journal
+#[derive(Debug, Decode)]
+pub struct JournalRecord {
+    /// Sequence number of the record, starting at 1 and without gaps
+    pub sequence: u64,
+    pub entry: JournalEntry,
+}
+
+/// Append-only journal of the markets listed and requests accepted by an exchange
+///
+/// The file starts with `JOURNAL_MAGIC` and the base sequence number, the sequence
+/// number of the last record removed by compaction as a little endian `u64`, followed by
+/// one record per entry. Each record is the length of its payload and the CRC-32 of its
+/// payload, as little endian `u32`s, followed by the payload: the sequence number and
+/// entry encoded with bincode
+pub struct Journal {
+    path: PathBuf,
+    file: File,
+    base_sequence: u64,
+    fsync_policy: FsyncPolicy,
+    next_sequence: u64,
+    unsynced: usize,
+    buffer: Vec<u8>,
+    /// Length of the file up to the end of the last record written in full
+    valid_len: u64,
+    /// Set when a failed write could not be undone, after which nothing more is written
+    poisoned: bool,
+    /// Number of bytes the next write stops after before failing, to test write errors
+    #[cfg(test)]
+    write_limit: Option<usize>,
+}
+
+impl Journal {
+    /// Open the journal in the given file, creating it if it does not exist
+    ///
+    /// Returns the journal, positioned to append after the last complete record, and
+    /// the records already in it. A record cut short by a crash while it was being
+    /// written is removed, as it was never dispatched. A damaged record followed by
+    /// more data gives a `JournalCorrupt` error
+    pub fn open(
+        path: &Path,
+        fsync_policy: FsyncPolicy,
+    ) -> Result<(Self, Vec<JournalRecord>), ExchangeError> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(|_| ExchangeError::JournalRead)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)
+            .map_err(|_| ExchangeError::JournalRead)?;
+        let (base_sequence, records, valid_len) = if bytes.len() < JOURNAL_HEADER_SIZE {
+            // the journal is new, or the crash happened while writing its header
+            if !encode_header(0).starts_with(&bytes) {
+                return Err(ExchangeError::JournalCorrupt);
+            }
+            (0, Vec::new(), 0)
+        } else {
+            let base_sequence = u64::from_le_bytes(
+                bytes[JOURNAL_MAGIC.len()..JOURNAL_HEADER_SIZE]
+                    .try_into()
+                    .unwrap(),
+            );
+            let (records, valid_len) = parse_records(&bytes, base_sequence)?;
+            (base_sequence, records, valid_len)
+        };
+        if valid_len < bytes.len() || valid_len == 0 {
+            file.set_len(valid_len as u64)
+                .map_err(|_| ExchangeError::JournalWrite)?;
+        }
+        file.seek(SeekFrom::Start(valid_len as u64))
+            .map_err(|_| ExchangeError::JournalWrite)?;
+        if valid_len == 0 {
+            file.write_all(&encode_header(0))
+                .and_then(|_| file.sync_data())
+                .map_err(|_| ExchangeError::JournalWrite)?;
+        }
+        let valid_len = valid_len.max(JOURNAL_HEADER_SIZE) as u64;
+        let next_sequence = match records.last() {
+            Some(record) => record.sequence + 1,
+            None => base_sequence + 1,
+        };
+        let journal = Self {
+            path: path.to_path_buf(),
+            file,
+            base_sequence,
+            fsync_policy,
+            next_sequence,
+            unsynced: 0,
+            buffer: Vec::new(),
+            valid_len,
+            poisoned: false,
+            #[cfg(test)]
+            write_limit: None,
+        };
+        Ok((journal, records))
+    }
+
+    /// Get the sequence number the next record will be written with
+    pub fn get_next_sequence(&self) -> u64 {
+        self.next_sequence
+    }
+
+    /// Get the sequence number of the first record that can be in the journal
+    ///
+    /// Records before it were removed by compaction once a snapshot covered them
+    pub fn get_first_sequence(&self) -> u64 {
+        self.base_sequence + 1
+    }
+
+    /// Get when the journal is flushed to disk
+    pub fn get_fsync_policy(&self) -> FsyncPolicy {
+        self.fsync_policy
+    }
+
+    /// Record the listing of a market
+    ///
+    /// Returns the sequence number of the record
+    pub fn append_market(&mut self, market: &Market) -> Result<u64, ExchangeError> {
+        self.append(JournalEntryRef::ListMarket(market))
+    }
+
+    /// Record a request accepted with the given ID
+    ///
+    /// Returns the sequence number of the record
+    pub fn append_request(
+        &mut self,
+        id: u128,
+        request: &RequestRecord,
+    ) -> Result<u64, ExchangeError> {
+        self.append(JournalEntryRef::Request { id, request })
+    }
+
+    /// Write a record to the file, flushing it to disk as required by the fsync policy
+    ///
+    /// If the write fails, any part of the record that reached the file is removed, so
+    /// the journal stays readable. If that fails too, the journal refuses every later
+    /// record, as they would follow a damaged one
+    fn append(&mut self, entry: JournalEntryRef) -> Result<u64, ExchangeError> {
+        if self.poisoned {
+            return Err(ExchangeError::JournalWrite);
+        }
+        let sequence = self.next_sequence;
+        self.buffer.clear();
+        self.buffer.extend_from_slice(&[0; RECORD_HEADER_SIZE]);
+        bincode::encode_into_std_write(
+            (sequence, entry),
+            &mut self.buffer,
+            bincode::config::standard(),
+        )
+        .map_err(|_| ExchangeError::PayloadEncoding)?;
+        let payload = &self.buffer[RECORD_HEADER_SIZE..];
+        if payload.len() > MAX_RECORD_SIZE {
+            return Err(ExchangeError::PayloadEncoding);
+        }
+        let length = payload.len() as u32;
+        let checksum = crc32(payload);
+        self.buffer[0..4].copy_from_slice(&length.to_le_bytes());
+        self.buffer[4..8].copy_from_slice(&checksum.to_le_bytes());
+        // records are only ever appended, so a crash leaves at most one partial record,
+        // at the end of the file
+        if self.write_buffer().is_err() {
+            let valid_len = self.valid_len;
+            let rolled_back = self
+                .file
+                .set_len(valid_len)
+                .and_then(|_| self.file.seek(SeekFrom::Start(valid_len)));
+            self.poisoned = rolled_back.is_err();
+            return Err(ExchangeError::JournalWrite);
+        }
+        self.valid_len += self.buffer.len() as u64;
+        self.next_sequence += 1;
+        self.unsynced += 1;
+        match self.fsync_policy {
+            FsyncPolicy::None => {}
+            FsyncPolicy::Batch { records } if self.unsynced < records => {}
+            FsyncPolicy::Batch { .. } | FsyncPolicy::Every => self.sync()?,
+        }
+        Ok(sequence)
+    }
+
+    /// Write the encoded record in the buffer to the file
+    fn write_buffer(&mut self) -> io::Result<()> {
+        #[cfg(test)]
+        if let Some(limit) = self.write_limit.take() {
+            let limit = limit.min(self.buffer.len());
+            self.file.write_all(&self.buffer[..limit])?;
+            return Err(io::Error::new(
+                io::ErrorKind::StorageFull,
+                "write limit reached",
+            ));
+        }
+        self.file.write_all(&self.buffer)
+    }
+
+    /// Make the next write stop after the given number of bytes and fail
+    #[cfg(test)]
+    pub(crate) fn fail_next_write_after(&mut self, bytes: usize) {
+        self.write_limit = Some(bytes);
+    }
+
+    /// Remove every record from the journal, keeping the sequence numbering
+    ///
+    /// Must only be called once the records are covered by a snapshot that is on disk.
+    /// The emptied journal is written to a temporary file that is renamed over the
+    /// journal, so a crash leaves either the old journal or the new one, and both can be
+    /// replayed after the snapshot
+    pub fn compact(&mut self) -> Result<(), ExchangeError> {
+        self.sync()?;
+        let base_sequence = self.next_sequence - 1;
+        let temp_path = self.path.with_extension("compacting");
+        let mut file = File::create(&temp_path).map_err(|_| ExchangeError::JournalWrite)?;
+        file.write_all(&encode_header(base_sequence))
+            .and_then(|_| file.sync_all())
+            .and_then(|_| fs::rename(&temp_path, &self.path))
+            .map_err(|_| ExchangeError::JournalWrite)?;
+        // the new file is positioned at its end, ready for the next record
+        self.file = file;
+        self.base_sequence = base_sequence;
+        self.valid_len = JOURNAL_HEADER_SIZE as u64;
+        self.poisoned = false;
+        Ok(())
+    }
+
+    /// Flush every record written so far to disk
+    pub fn sync(&mut self) -> Result<(), ExchangeError> {
+        if self.unsynced > 0 {
+            self.file
+                .sync_data()
+                .map_err(|_| ExchangeError::JournalWrite)?;
+            self.unsynced = 0;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Journal {
+    fn drop(&mut self) {
+        if self.fsync_policy != FsyncPolicy::None {
+            let _ = self.sync();
+        }
+    }
+}
+
+/// Returns the header of a journal file with the given base sequence number
+fn encode_header(base_sequence: u64) -> [u8; JOURNAL_HEADER_SIZE] {
+    let mut header = [0; JOURNAL_HEADER_SIZE];
+    header[..JOURNAL_MAGIC.len()].copy_from_slice(&JOURNAL_MAGIC);
+    header[JOURNAL_MAGIC.len()..].copy_from_slice(&base_sequence.to_le_bytes());
+    header
+}
+
+/// Decode the records of a journal file, which follow the given base sequence number
+///
+/// Returns the records and the length of the file up to the end of the last complete
+/// record. Only a record that runs to the end of the file with a plausible length is
+/// treated as partly written; any other damage is reported as `JournalCorrupt`, so the
+/// caller never truncates records that were written in full
+fn parse_records(
+    bytes: &[u8],
+    base_sequence: u64,
+) -> Result<(Vec<JournalRecord>, usize), ExchangeError> {
+    if bytes[..JOURNAL_MAGIC.len()] != JOURNAL_MAGIC {
+        return Err(ExchangeError::JournalCorrupt);
+    }
+    let mut records: Vec<JournalRecord> = Vec::new();
+    let mut offset = JOURNAL_HEADER_SIZE;
+    while bytes.len() - offset >= RECORD_HEADER_SIZE {
+        let length = u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize;
+        let checksum = u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into().unwrap());
+        let start = offset + RECORD_HEADER_SIZE;
+        if length > MAX_RECORD_SIZE {
+            return Err(ExchangeError::JournalCorrupt);
+        }
+        if bytes.len() - start < length {
+            // the last record was only partly written, unless the rest of the file starts
+            // with its whole payload and it is the length that is damaged
+            if has_complete_payload(&bytes[start..], checksum) {
+                return Err(ExchangeError::JournalCorrupt);
+            }
+            break;
+        }
+        let end = start + length;
+        let payload = &bytes[start..end];
+        if crc32(payload) != checksum {
+            if end == bytes.len() {
+                // the length was written but not all of the payload
+                break;
+            }
+            return Err(ExchangeError::JournalCorrupt);
+        }
+        let record: JournalRecord =
+            match bincode::decode_from_slice(payload, bincode::config::standard()) {
+                Ok((record, _)) => record,
+                Err(_) => return Err(ExchangeError::JournalCorrupt),
+            };
+        let expected = records
+            .last()
+            .map_or(base_sequence + 1, |last| last.sequence + 1);
+        if record.sequence != expected {
+            return Err(ExchangeError::JournalCorrupt);
+        }
+        records.push(record);
+        offset = end;
+    }
+    Ok((records, offset))
+}
+
+/// Returns true if some prefix of the bytes after a record header has the checksum of
+/// the record, meaning its payload was written in full
+fn has_complete_payload(bytes: &[u8], checksum: u32) -> bool {
+    let mut crc = Crc32::new();
+    bytes.iter().any(|byte| {
+        crc.update(std::slice::from_ref(byte));
+        crc.get_value() == checksum
+    })
+}
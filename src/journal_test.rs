@@ -0,0 +1,249 @@
+#[cfg(test)]
+mod tests {
+
+    use std::fs::{self, OpenOptions};
+    use std::path::PathBuf;
+
+    use crate::config::FsyncPolicy;
+    use crate::error::ExchangeError;
+    use crate::journal::{JOURNAL_HEADER_SIZE, JOURNAL_MAGIC, Journal, JournalEntry};
+    use crate::market::Market;
+    use crate::order::Side;
+    use crate::request::{CancelOrderRequest, RequestRecord, SubmitOrderRequest};
+
+    /// Returns the path of a journal file unique to the test, removing any left over
+    /// from a previous run
+    fn journal_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "order-book-{}-{}.journal",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn submit_record(price: u64) -> RequestRecord {
+        RequestRecord::SubmitOrder(SubmitOrderRequest {
+            symbol: "BTCUSD".to_string(),
+            price,
+            size: 2,
+            side: Side::Buy,
+            ..Default::default()
+        })
+    }
+
+    fn cancel_record(id: u64) -> RequestRecord {
+        RequestRecord::CancelOrder(CancelOrderRequest {
+            symbol: "BTCUSD".to_string(),
+            id,
+        })
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_open_creates_empty_journal() {
+        let path = journal_path("create");
+        let (journal, records) = Journal::open(&path, FsyncPolicy::Every).unwrap();
+        assert!(records.is_empty());
+        assert_eq!(journal.get_next_sequence(), 1);
+        drop(journal);
+        let bytes = fs::read(&path).unwrap();
+        assert_eq!(bytes.len(), JOURNAL_HEADER_SIZE);
+        assert_eq!(bytes[..JOURNAL_MAGIC.len()], JOURNAL_MAGIC);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_records_read_back_in_order() {
+        let path = journal_path("read-back");
+        let (mut journal, _) = Journal::open(&path, FsyncPolicy::Batch { records: 2 }).unwrap();
+        assert_eq!(
+            journal
+                .append_market(&Market::new("BTCUSD", 1_000, 1))
+                .unwrap(),
+            1
+        );
+        assert_eq!(journal.append_request(1, &submit_record(99)).unwrap(), 2);
+        assert_eq!(journal.append_request(2, &cancel_record(1)).unwrap(), 3);
+        drop(journal);
+
+        let (journal, records) = Journal::open(&path, FsyncPolicy::Every).unwrap();
+        assert_eq!(journal.get_next_sequence(), 4);
+        let sequences: Vec<u64> = records.iter().map(|record| record.sequence).collect();
+        assert_eq!(sequences, vec![1, 2, 3]);
+        assert!(matches!(
+            &records[0].entry,
+            JournalEntry::ListMarket(market) if market.get_symbol() == "BTCUSD"
+        ));
+        assert!(matches!(
+            &records[1].entry,
+            JournalEntry::Request {
+                id: 1,
+                request: RequestRecord::SubmitOrder(request),
+            } if request.price == 99 && request.size == 2
+        ));
+        assert!(matches!(
+            &records[2].entry,
+            JournalEntry::Request {
+                id: 2,
+                request: RequestRecord::CancelOrder(request),
+            } if request.id == 1
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_partial_last_record_is_dropped() {
+        let path = journal_path("torn");
+        let (mut journal, _) = Journal::open(&path, FsyncPolicy::None).unwrap();
+        journal.append_request(1, &submit_record(99)).unwrap();
+        let complete_len = fs::metadata(&path).unwrap().len();
+        journal.append_request(2, &submit_record(98)).unwrap();
+        drop(journal);
+        // cut the second record short, as if the process died while writing it
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(fs::metadata(&path).unwrap().len() - 3)
+            .unwrap();
+        drop(file);
+
+        let (mut journal, records) = Journal::open(&path, FsyncPolicy::None).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(fs::metadata(&path).unwrap().len(), complete_len);
+        // new records are appended after the last complete record
+        assert_eq!(journal.append_request(2, &submit_record(97)).unwrap(), 2);
+        drop(journal);
+
+        let (_, records) = Journal::open(&path, FsyncPolicy::None).unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(matches!(
+            &records[1].entry,
+            JournalEntry::Request {
+                request: RequestRecord::SubmitOrder(request),
+                ..
+            } if request.price == 97
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_damaged_record_is_corrupt() {
+        let path = journal_path("damaged");
+        let (mut journal, _) = Journal::open(&path, FsyncPolicy::None).unwrap();
+        journal.append_request(1, &submit_record(99)).unwrap();
+        journal.append_request(2, &submit_record(98)).unwrap();
+        drop(journal);
+        // flip a bit in the payload of the first record
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[JOURNAL_HEADER_SIZE + 10] ^= 0x01;
+        fs::write(&path, &bytes).unwrap();
+
+        assert!(matches!(
+            Journal::open(&path, FsyncPolicy::None),
+            Err(ExchangeError::JournalCorrupt)
+        ));
+        // the journal is left as it was, so it can be inspected
+        assert_eq!(fs::read(&path).unwrap(), bytes);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_other_file_is_not_a_journal() {
+        let path = journal_path("other");
+        fs::write(&path, b"symbol,price,size\n").unwrap();
+        assert!(matches!(
+            Journal::open(&path, FsyncPolicy::None),
+            Err(ExchangeError::JournalCorrupt)
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_damaged_length_is_corrupt() {
+        let path = journal_path("damaged-length");
+        let (mut journal, _) = Journal::open(&path, FsyncPolicy::None).unwrap();
+        journal.append_request(1, &submit_record(99)).unwrap();
+        journal.append_request(2, &submit_record(98)).unwrap();
+        journal.append_request(3, &cancel_record(1)).unwrap();
+        drop(journal);
+        let bytes = fs::read(&path).unwrap();
+        // a length past the end of the file, and one too large for any record
+        for (index, bit) in [
+            (JOURNAL_HEADER_SIZE + 1, 0x01),
+            (JOURNAL_HEADER_SIZE + 3, 0x01),
+        ] {
+            let mut damaged = bytes.clone();
+            damaged[index] ^= bit;
+            fs::write(&path, &damaged).unwrap();
+            assert!(matches!(
+                Journal::open(&path, FsyncPolicy::None),
+                Err(ExchangeError::JournalCorrupt)
+            ));
+            // no record is dropped from the journal
+            assert_eq!(fs::read(&path).unwrap(), damaged);
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_failed_write_is_rolled_back() {
+        let path = journal_path("failed-write");
+        let (mut journal, _) = Journal::open(&path, FsyncPolicy::Every).unwrap();
+        journal
+            .append_market(&Market::new("BTCUSD", 1_000, 1))
+            .unwrap();
+        let len = fs::metadata(&path).unwrap().len();
+        // the disk fills up partway through the record
+        journal.fail_next_write_after(5);
+        assert_eq!(
+            journal.append_request(1, &submit_record(99)),
+            Err(ExchangeError::JournalWrite)
+        );
+        assert_eq!(fs::metadata(&path).unwrap().len(), len);
+        assert_eq!(journal.get_next_sequence(), 2);
+        // later records follow the last complete one and can be read back
+        assert_eq!(journal.append_request(2, &cancel_record(1)), Ok(2));
+        drop(journal);
+        let (_, records) = Journal::open(&path, FsyncPolicy::Every).unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(matches!(
+            records[1].entry,
+            JournalEntry::Request {
+                id: 2,
+                request: RequestRecord::CancelOrder(_)
+            }
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_compact_keeps_sequence_numbers() {
+        let path = journal_path("compact");
+        let (mut journal, _) = Journal::open(&path, FsyncPolicy::None).unwrap();
+        journal.append_request(1, &submit_record(99)).unwrap();
+        journal.append_request(2, &submit_record(98)).unwrap();
+        journal.compact().unwrap();
+        assert_eq!(
+            fs::metadata(&path).unwrap().len(),
+            JOURNAL_HEADER_SIZE as u64
+        );
+        assert_eq!(journal.get_first_sequence(), 3);
+        assert_eq!(journal.append_request(3, &cancel_record(1)), Ok(3));
+        drop(journal);
+
+        // only the records after the compaction are read back
+        let (journal, records) = Journal::open(&path, FsyncPolicy::None).unwrap();
+        assert_eq!(journal.get_first_sequence(), 3);
+        assert_eq!(journal.get_next_sequence(), 4);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].sequence, 3);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,132 @@
+/// Number of bits in each word of the index
+const WORD_BITS: usize = 64;
+
+/// Hierarchical occupancy bitmap over the price levels of one side of a book
+///
+/// The bottom layer has one bit per price level. Each bit of a higher layer is set when
+/// the matching word of the layer below is non-zero, so the next occupied level can be
+/// found with a handful of word scans regardless of how many empty levels lie between
+#[derive(Debug, Clone)]
+pub struct LevelIndex {
+    layers: Vec<Vec<u64>>,
+}
+
+impl LevelIndex {
+    /// Creates an empty index covering the given number of price levels
+    pub fn new(capacity: usize) -> Self {
+        let mut layers = Vec::new();
+        let mut bits = capacity.max(1);
+        loop {
+            let words = bits.div_ceil(WORD_BITS);
+            layers.push(vec![0; words]);
+            if words == 1 {
+                break;
+            }
+            bits = words;
+        }
+        Self { layers }
+    }
+
+    /// Returns true if the level with the given index is marked as occupied
+    pub fn contains(&self, index: usize) -> bool {
+        self.layers[0][index / WORD_BITS] & (1 << (index % WORD_BITS)) != 0
+    }
+
+    /// Returns true if no level is marked as occupied
+    pub fn is_empty(&self) -> bool {
+        self.layers.last().unwrap()[0] == 0
+    }
+
+    /// Marks the level with the given index as occupied
+    pub fn insert(&mut self, index: usize) {
+        let mut index = index;
+        for layer in self.layers.iter_mut() {
+            let word = index / WORD_BITS;
+            let was_empty = layer[word] == 0;
+            layer[word] |= 1 << (index % WORD_BITS);
+            if !was_empty {
+                break;
+            }
+            index = word;
+        }
+    }
+
+    /// Marks the level with the given index as empty
+    pub fn remove(&mut self, index: usize) {
+        let mut index = index;
+        for layer in self.layers.iter_mut() {
+            let word = index / WORD_BITS;
+            layer[word] &= !(1 << (index % WORD_BITS));
+            if layer[word] != 0 {
+                break;
+            }
+            index = word;
+        }
+    }
+
+    /// Returns the lowest occupied level
+    pub fn first(&self) -> Option<usize> {
+        self.next_at_or_above(0)
+    }
+
+    /// Returns the highest occupied level
+    pub fn last(&self) -> Option<usize> {
+        self.prev_at_or_below(self.layers[0].len() * WORD_BITS - 1)
+    }
+
+    /// Returns the lowest occupied level with an index greater than or equal to the given index
+    pub fn next_at_or_above(&self, index: usize) -> Option<usize> {
+        let mut depth = 0;
+        let mut index = index;
+        // climb until a word has an occupied bit at or above the index
+        loop {
+            let layer = self.layers.get(depth)?;
+            let word = index / WORD_BITS;
+            let bits = layer.get(word)? & (u64::MAX << (index % WORD_BITS));
+            if bits != 0 {
+                index = word * WORD_BITS + bits.trailing_zeros() as usize;
+                break;
+            }
+            index = word + 1;
+            depth += 1;
+        }
+        // descend to the lowest occupied bit beneath it
+        while depth > 0 {
+            depth -= 1;
+            index = index * WORD_BITS + self.layers[depth][index].trailing_zeros() as usize;
+        }
+        Some(index)
+    }
+
+    /// Returns the highest occupied level with an index less than or equal to the given index
+    pub fn prev_at_or_below(&self, index: usize) -> Option<usize> {
+        let mut depth = 0;
+        let mut index = index.min(self.layers[0].len() * WORD_BITS - 1);
+        // climb until a word has an occupied bit at or below the index
+        loop {
+            let layer = self.layers.get(depth)?;
+            let word = index / WORD_BITS;
+            let bits = layer[word] & (u64::MAX >> (WORD_BITS - 1 - index % WORD_BITS));
+            if bits != 0 {
+                index = word * WORD_BITS + highest_bit(bits);
+                break;
+            }
+            if word == 0 {
+                return None;
+            }
+            index = word - 1;
+            depth += 1;
+        }
+        // descend to the highest occupied bit beneath it
+        while depth > 0 {
+            depth -= 1;
+            index = index * WORD_BITS + highest_bit(self.layers[depth][index]);
+        }
+        Some(index)
+    }
+}
+
+/// Returns the position of the highest set bit of a non-zero word
+fn highest_bit(word: u64) -> usize {
+    WORD_BITS - 1 - word.leading_zeros() as usize
+}
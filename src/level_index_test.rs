@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::level_index::LevelIndex;
+
+    #[test]
+    fn test_new() {
+        let index = LevelIndex::new(10_000);
+        assert!(index.is_empty());
+        assert!(index.first().is_none());
+        assert!(index.last().is_none());
+        assert!(!index.contains(0));
+    }
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut index = LevelIndex::new(10_000);
+        index.insert(4_321);
+        assert!(index.contains(4_321));
+        assert!(!index.is_empty());
+        assert_eq!(index.first(), Some(4_321));
+        assert_eq!(index.last(), Some(4_321));
+        index.remove(4_321);
+        assert!(!index.contains(4_321));
+        assert!(index.is_empty());
+        assert!(index.first().is_none());
+    }
+
+    #[test]
+    fn test_remove_keeps_neighbours_in_same_word() {
+        let mut index = LevelIndex::new(10_000);
+        index.insert(64);
+        index.insert(65);
+        index.remove(64);
+        assert_eq!(index.first(), Some(65));
+        assert_eq!(index.last(), Some(65));
+    }
+
+    #[test]
+    fn test_next_at_or_above() {
+        let mut index = LevelIndex::new(10_000);
+        for i in [3, 64, 4_095, 4_096, 9_999] {
+            index.insert(i);
+        }
+        assert_eq!(index.next_at_or_above(0), Some(3));
+        assert_eq!(index.next_at_or_above(3), Some(3));
+        assert_eq!(index.next_at_or_above(4), Some(64));
+        assert_eq!(index.next_at_or_above(65), Some(4_095));
+        assert_eq!(index.next_at_or_above(4_096), Some(4_096));
+        assert_eq!(index.next_at_or_above(4_097), Some(9_999));
+        assert_eq!(index.next_at_or_above(10_000), None);
+        assert_eq!(index.next_at_or_above(1_000_000), None);
+    }
+
+    #[test]
+    fn test_prev_at_or_below() {
+        let mut index = LevelIndex::new(10_000);
+        for i in [0, 3, 64, 4_095, 4_096, 9_999] {
+            index.insert(i);
+        }
+        assert_eq!(index.prev_at_or_below(9_999), Some(9_999));
+        assert_eq!(index.prev_at_or_below(9_998), Some(4_096));
+        assert_eq!(index.prev_at_or_below(4_095), Some(4_095));
+        assert_eq!(index.prev_at_or_below(4_094), Some(64));
+        assert_eq!(index.prev_at_or_below(63), Some(3));
+        assert_eq!(index.prev_at_or_below(2), Some(0));
+        assert_eq!(index.prev_at_or_below(1_000_000), Some(9_999));
+        index.remove(0);
+        assert_eq!(index.prev_at_or_below(2), None);
+    }
+
+    #[test]
+    fn test_single_word() {
+        let mut index = LevelIndex::new(10);
+        index.insert(9);
+        index.insert(0);
+        assert_eq!(index.first(), Some(0));
+        assert_eq!(index.last(), Some(9));
+        assert_eq!(index.next_at_or_above(1), Some(9));
+        assert_eq!(index.prev_at_or_below(8), Some(0));
+    }
+
+    #[test]
+    fn test_matches_linear_scan() {
+        let capacity = 300_000;
+        let mut index = LevelIndex::new(capacity);
+        let mut occupied = vec![false; capacity];
+        let mut seed: u64 = 42;
+        for _ in 0..20_000 {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            let i = (seed >> 33) as usize % capacity;
+            if occupied[i] {
+                index.remove(i);
+            } else {
+                index.insert(i);
+            }
+            occupied[i] = !occupied[i];
+            let probe = (seed >> 13) as usize % capacity;
+            let next = (probe..capacity).find(|&j| occupied[j]);
+            let prev = (0..=probe).rev().find(|&j| occupied[j]);
+            assert_eq!(index.next_at_or_above(probe), next);
+            assert_eq!(index.prev_at_or_below(probe), prev);
+        }
+    }
+}
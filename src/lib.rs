@@ -0,0 +1,54 @@
+pub mod completion;
+pub mod config;
+pub mod crc32;
+pub mod decimal;
+pub mod demo;
+pub mod error;
+pub mod exchange;
+pub mod journal;
+pub mod level_index;
+pub mod market;
+pub mod order;
+pub mod order_arena;
+pub mod order_book;
+pub mod price_ladder;
+pub mod price_level;
+pub mod published_depth;
+pub mod published_quote;
+pub mod replay;
+pub mod request;
+pub mod response;
+pub mod result_ring;
+pub mod snapshot;
+pub mod spsc;
+mod state;
+pub mod stop_book;
+mod sync;
+pub mod trade;
+pub mod worker;
+
+mod completion_test;
+mod crc32_test;
+mod decimal_test;
+mod error_test;
+mod exchange_test;
+mod journal_test;
+mod level_index_test;
+mod loom_test;
+mod market_test;
+mod order_arena_test;
+mod order_book_test;
+mod order_test;
+mod price_ladder_test;
+mod price_level_test;
+mod published_depth_test;
+mod published_quote_test;
+mod replay_test;
+mod request_test;
+mod response_test;
+mod result_ring_test;
+mod snapshot_test;
+mod spsc_test;
+mod stop_book_test;
+mod trade_test;
+mod worker_test;
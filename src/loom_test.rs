@@ -0,0 +1,170 @@
+//! Model-checked tests of the state shared between the exchange and its workers
+//!
+//! Run with `RUSTFLAGS="--cfg loom" cargo test --release --lib loom_test`
+#[cfg(all(test, loom))]
+mod tests {
+
+    use loom::thread;
+
+    use crate::completion::{Completion, CompletionQueue};
+    use crate::error::ExchangeError;
+    use crate::order_book::{BestQuote, Depth, DepthLevel};
+    use crate::published_depth::PublishedDepth;
+    use crate::published_quote::PublishedQuote;
+    use crate::request::RequestType;
+    use crate::spsc;
+    use crate::state::State;
+    use crate::sync::Arc;
+
+    #[test]
+    fn test_stored_result_reaches_waiting_client() {
+        loom::model(|| {
+            let state = Arc::new(State::new(2));
+            state.reserve_request_result(1);
+            let worker_state = state.clone();
+            let worker = thread::spawn(move || {
+                worker_state.save_request_result(
+                    1,
+                    Ok((RequestType::SubmitOrder, vec![1])),
+                    Completion::Store,
+                );
+            });
+            assert_eq!(
+                state.take_request_result(1, None),
+                Some(Ok((RequestType::SubmitOrder, vec![1])))
+            );
+            worker.join().unwrap();
+            assert_eq!(
+                state.take_request_result(1, None),
+                Some(Err(ExchangeError::ResultExpired))
+            );
+        });
+    }
+
+    #[test]
+    fn test_reused_slot_never_hands_out_stale_result() {
+        loom::model(|| {
+            let state = Arc::new(State::new(1));
+            state.reserve_request_result(1);
+            let worker_state = state.clone();
+            let stale_worker = thread::spawn(move || {
+                worker_state.save_request_result(
+                    1,
+                    Ok((RequestType::CancelOrder, vec![1])),
+                    Completion::Store,
+                );
+            });
+            // request 2 takes over the only slot, whether or not request 1 has completed
+            state.reserve_request_result(2);
+            let worker_state = state.clone();
+            let worker = thread::spawn(move || {
+                worker_state.save_request_result(
+                    2,
+                    Ok((RequestType::CancelOrder, vec![2])),
+                    Completion::Store,
+                );
+            });
+            assert_eq!(
+                state.take_request_result(1, None),
+                Some(Err(ExchangeError::ResultExpired))
+            );
+            assert_eq!(
+                state.take_request_result(2, None),
+                Some(Ok((RequestType::CancelOrder, vec![2])))
+            );
+            stale_worker.join().unwrap();
+            worker.join().unwrap();
+        });
+    }
+
+    #[test]
+    fn test_completion_queue_wakes_waiting_client() {
+        loom::model(|| {
+            let queue = Arc::new(CompletionQueue::new());
+            let producers: Vec<_> = (1..=2)
+                .map(|id| {
+                    let queue = queue.clone();
+                    thread::spawn(move || queue.push(id, Ok((RequestType::AmendOrder, vec![]))))
+                })
+                .collect();
+            let mut ids = vec![queue.wait().0, queue.wait().0];
+            ids.sort();
+            assert_eq!(ids, vec![1, 2]);
+            assert!(queue.poll().is_none());
+            for producer in producers {
+                producer.join().unwrap();
+            }
+        });
+    }
+
+    #[test]
+    fn test_published_quote_is_never_torn() {
+        fn quote(value: u64) -> BestQuote {
+            BestQuote {
+                best_bid_price: value,
+                best_bid_size: value,
+                best_ask_price: value,
+                best_ask_size: value,
+            }
+        }
+        loom::model(|| {
+            let published = Arc::new(PublishedQuote::new(quote(0), 0));
+            let writer = {
+                let published = published.clone();
+                thread::spawn(move || published.publish(&quote(1), 1))
+            };
+            // the snapshot is either entirely before or entirely after the update
+            let snapshot = published.read();
+            assert_eq!(snapshot.best_quote, quote(snapshot.sequence));
+            assert_eq!(snapshot.market_sequence, snapshot.sequence);
+            writer.join().unwrap();
+            assert_eq!(published.read().best_quote, quote(1));
+        });
+    }
+
+    #[test]
+    fn test_published_depth_is_never_torn() {
+        fn levels(value: u64) -> Vec<DepthLevel> {
+            vec![
+                DepthLevel {
+                    price: value,
+                    size: value,
+                    order_count: value,
+                };
+                value as usize
+            ]
+        }
+        loom::model(|| {
+            let published = Arc::new(PublishedDepth::new(&Depth::default(), 1));
+            let writer = {
+                let published = published.clone();
+                thread::spawn(move || {
+                    published.publish(1, levels(1).into_iter(), std::iter::empty())
+                })
+            };
+            // the snapshot is either entirely before or entirely after the update
+            let depth = published.read(1);
+            assert_eq!(depth.bids, levels(depth.sequence));
+            assert!(depth.asks.is_empty());
+            writer.join().unwrap();
+            assert_eq!(published.read(1).bids, levels(1));
+        });
+    }
+
+    #[test]
+    fn test_ring_hands_over_values_and_wakes_consumer() {
+        loom::model(|| {
+            let (producer, consumer) = spsc::channel(1);
+            let sender = thread::spawn(move || {
+                // the second push waits for the consumer to free the only slot
+                producer.push(1).unwrap();
+                producer.push(2).unwrap();
+            });
+            assert_eq!(consumer.pop(), Some(1));
+            assert_eq!(consumer.pop(), Some(2));
+            // the consumer is woken when the producer is dropped
+            assert_eq!(consumer.pop(), None);
+            sender.join().unwrap();
+        });
+    }
+}
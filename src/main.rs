@@ -1,17 +1,26 @@
-mod demo;
-mod exchange;
-mod market;
-mod order;
-mod order_book;
-mod price_level;
-mod request;
-mod state;
+use std::fs::File;
+use std::io::{self, BufReader};
 
-mod order_book_test;
-mod order_test;
-mod price_level_test;
+use rapid_rust_order_book::{demo, replay};
 
 fn main() -> Result<(), String> {
-    demo::run();
-    Ok(())
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(String::as_str) {
+        // replay <input.jsonl>, or standard input if no file is given
+        Some("replay") => {
+            let output = io::stdout().lock();
+            let result = match args.get(2) {
+                Some(path) => {
+                    let file = File::open(path).map_err(|err| format!("{}: {}", path, err))?;
+                    replay::run(BufReader::new(file), output)
+                }
+                None => replay::run(io::stdin().lock(), output),
+            };
+            result.map_err(|err| err.to_string())
+        }
+        _ => {
+            demo::run();
+            Ok(())
+        }
+    }
 }
@@ -1,8 +1,33 @@
-#[derive(Debug, Clone)]
+use bincode::{Decode, Encode};
+use serde::Deserialize;
+
+use crate::decimal::{MAX_SCALE, Price, Quantity};
+use crate::error::ExchangeError;
+
+/// Storage used for the price levels of a market's order book
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LadderType {
+    /// Every price level is allocated when the market is listed
+    #[default]
+    Dense,
+    /// Price levels are allocated when the first order arrives at their price
+    Sparse,
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
 pub struct Market {
     symbol: String,
     max_price: u64,
     min_price: u64,
+    tick_size: u64,
+    lot_size: u64,
+    min_quantity: u64,
+    max_quantity: u64,
+    min_notional: u64,
+    price_scale: u8,
+    quantity_scale: u8,
+    ladder_type: LadderType,
 }
 
 impl Market {
@@ -12,6 +37,31 @@ impl Market {
             symbol: symbol.to_owned(),
             max_price,
             min_price,
+            tick_size: 1,
+            lot_size: 1,
+            min_quantity: 0,
+            max_quantity: u64::MAX,
+            min_notional: 0,
+            price_scale: 0,
+            quantity_scale: 0,
+            ladder_type: LadderType::Dense,
+        }
+    }
+
+    /// Returns an error if the trading rules of the market are inconsistent
+    pub fn validate(&self) -> Result<(), ExchangeError> {
+        if self.min_price >= self.max_price {
+            Err(ExchangeError::InvalidPriceRange)
+        } else if self.tick_size == 0 {
+            Err(ExchangeError::InvalidTickSize)
+        } else if self.lot_size == 0 {
+            Err(ExchangeError::InvalidLotSize)
+        } else if self.min_quantity > self.max_quantity {
+            Err(ExchangeError::InvalidQuantityRange)
+        } else if self.price_scale > MAX_SCALE || self.quantity_scale > MAX_SCALE {
+            Err(ExchangeError::InvalidScale)
+        } else {
+            Ok(())
         }
     }
 
@@ -29,4 +79,116 @@ impl Market {
     pub fn get_min_price(&self) -> u64 {
         self.min_price
     }
+
+    /// Get the minimum price increment
+    pub fn get_tick_size(&self) -> u64 {
+        self.tick_size
+    }
+
+    /// Set the minimum price increment
+    pub fn set_tick_size(&mut self, tick_size: u64) {
+        self.tick_size = tick_size;
+    }
+
+    /// Get the minimum size increment
+    pub fn get_lot_size(&self) -> u64 {
+        self.lot_size
+    }
+
+    /// Set the minimum size increment
+    pub fn set_lot_size(&mut self, lot_size: u64) {
+        self.lot_size = lot_size;
+    }
+
+    /// Get min permitted order size
+    pub fn get_min_quantity(&self) -> u64 {
+        self.min_quantity
+    }
+
+    /// Set min permitted order size
+    pub fn set_min_quantity(&mut self, min_quantity: u64) {
+        self.min_quantity = min_quantity;
+    }
+
+    /// Get max permitted order size
+    pub fn get_max_quantity(&self) -> u64 {
+        self.max_quantity
+    }
+
+    /// Set max permitted order size
+    pub fn set_max_quantity(&mut self, max_quantity: u64) {
+        self.max_quantity = max_quantity;
+    }
+
+    /// Get min permitted value (price multiplied by size) of a limit order
+    pub fn get_min_notional(&self) -> u64 {
+        self.min_notional
+    }
+
+    /// Set min permitted value (price multiplied by size) of a limit order
+    pub fn set_min_notional(&mut self, min_notional: u64) {
+        self.min_notional = min_notional;
+    }
+
+    /// Get the number of decimal places of prices in the market
+    pub fn get_price_scale(&self) -> u8 {
+        self.price_scale
+    }
+
+    /// Set the number of decimal places of prices in the market
+    ///
+    /// Integer prices used by the order book, including the min price, max price and
+    /// tick size of the market, are in units of `10^-price_scale`
+    pub fn set_price_scale(&mut self, price_scale: u8) {
+        self.price_scale = price_scale;
+    }
+
+    /// Get the number of decimal places of quantities in the market
+    pub fn get_quantity_scale(&self) -> u8 {
+        self.quantity_scale
+    }
+
+    /// Set the number of decimal places of quantities in the market
+    ///
+    /// Integer sizes used by the order book, including the lot size and quantity limits
+    /// of the market, are in units of `10^-quantity_scale`
+    pub fn set_quantity_scale(&mut self, quantity_scale: u8) {
+        self.quantity_scale = quantity_scale;
+    }
+
+    /// Convert a decimal price to the integer price used by the order book
+    pub fn encode_price(&self, price: Price) -> Result<u64, ExchangeError> {
+        match price.rescale(self.price_scale) {
+            Ok(price) => Ok(price.get_units()),
+            Err(_) => Err(ExchangeError::PriceNotRepresentable),
+        }
+    }
+
+    /// Convert an integer price used by the order book to a decimal price
+    pub fn decode_price(&self, price: u64) -> Price {
+        Price::new(price, self.price_scale)
+    }
+
+    /// Convert a decimal quantity to the integer size used by the order book
+    pub fn encode_quantity(&self, quantity: Quantity) -> Result<u64, ExchangeError> {
+        match quantity.rescale(self.quantity_scale) {
+            Ok(quantity) => Ok(quantity.get_units()),
+            Err(_) => Err(ExchangeError::QuantityNotRepresentable),
+        }
+    }
+
+    /// Convert an integer size used by the order book to a decimal quantity
+    pub fn decode_quantity(&self, size: u64) -> Quantity {
+        Quantity::new(size, self.quantity_scale)
+    }
+
+    /// Get the type of price ladder used by the order book
+    pub fn get_ladder_type(&self) -> LadderType {
+        self.ladder_type
+    }
+
+    /// Set the type of price ladder used by the order book
+    pub fn set_ladder_type(&mut self, ladder_type: LadderType) {
+        self.ladder_type = ladder_type;
+    }
 }
@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::decimal::{Price, Quantity};
+    use crate::error::ExchangeError;
+    use crate::market::{LadderType, Market};
+
+    #[test]
+    fn test_new() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        assert_eq!(market.get_symbol(), "BTCUSD");
+        assert_eq!(market.get_max_price(), 10_000);
+        assert_eq!(market.get_min_price(), 1);
+        assert_eq!(market.get_tick_size(), 1);
+        assert_eq!(market.get_lot_size(), 1);
+        assert_eq!(market.get_min_quantity(), 0);
+        assert_eq!(market.get_max_quantity(), u64::MAX);
+        assert_eq!(market.get_min_notional(), 0);
+        assert_eq!(market.get_ladder_type(), LadderType::Dense);
+        assert!(market.validate().is_ok());
+    }
+
+    #[test]
+    fn test_setters() {
+        let mut market = Market::new("BTCUSD", 10_000, 1);
+        market.set_tick_size(5);
+        market.set_lot_size(10);
+        market.set_min_quantity(20);
+        market.set_max_quantity(1_000);
+        market.set_min_notional(500);
+        market.set_ladder_type(LadderType::Sparse);
+        assert_eq!(market.get_tick_size(), 5);
+        assert_eq!(market.get_lot_size(), 10);
+        assert_eq!(market.get_min_quantity(), 20);
+        assert_eq!(market.get_max_quantity(), 1_000);
+        assert_eq!(market.get_min_notional(), 500);
+        assert_eq!(market.get_ladder_type(), LadderType::Sparse);
+        assert!(market.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate() {
+        let market = Market::new("BTCUSD", 1, 1);
+        assert_eq!(market.validate(), Err(ExchangeError::InvalidPriceRange));
+        let mut market = Market::new("BTCUSD", 10_000, 1);
+        market.set_tick_size(0);
+        assert_eq!(market.validate(), Err(ExchangeError::InvalidTickSize));
+        let mut market = Market::new("BTCUSD", 10_000, 1);
+        market.set_lot_size(0);
+        assert_eq!(market.validate(), Err(ExchangeError::InvalidLotSize));
+        let mut market = Market::new("BTCUSD", 10_000, 1);
+        market.set_min_quantity(10);
+        market.set_max_quantity(5);
+        assert_eq!(market.validate(), Err(ExchangeError::InvalidQuantityRange));
+    }
+
+    #[test]
+    fn test_encode_and_decode_price() {
+        let mut market = Market::new("BTCUSD", 10_000_000, 1);
+        market.set_price_scale(2);
+        assert_eq!(market.get_price_scale(), 2);
+        assert_eq!(market.encode_price(Price::new(1_015, 1)), Ok(10_150));
+        assert_eq!(market.encode_price(Price::new(101, 0)), Ok(10_100));
+        assert_eq!(
+            market.encode_price(Price::new(101_125, 3)),
+            Err(ExchangeError::PriceNotRepresentable)
+        );
+        assert_eq!(market.decode_price(10_150), Price::new(10_150, 2));
+    }
+
+    #[test]
+    fn test_encode_and_decode_quantity() {
+        let mut market = Market::new("BTCUSD", 10_000_000, 1);
+        market.set_quantity_scale(3);
+        assert_eq!(market.get_quantity_scale(), 3);
+        assert_eq!(market.encode_quantity(Quantity::new(25, 2)), Ok(250));
+        assert_eq!(
+            market.encode_quantity(Quantity::new(1, 4)),
+            Err(ExchangeError::QuantityNotRepresentable)
+        );
+        assert_eq!(market.decode_quantity(250), Quantity::new(250, 3));
+    }
+
+    #[test]
+    fn test_validate_scale() {
+        let mut market = Market::new("BTCUSD", 10_000, 1);
+        market.set_price_scale(19);
+        assert_eq!(market.validate(), Err(ExchangeError::InvalidScale));
+    }
+}
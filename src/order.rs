@@ -1,32 +1,92 @@
 use bincode::{Decode, Encode};
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Eq, Clone, Copy, Debug, Encode, Decode)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Encode, Decode, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum OrderStatus {
     Open,
 }
 
-#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Encode, Decode)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum OrderType {
     #[default]
     Limit,
     Market,
+    /// Becomes a market order when the last trade price reaches the trigger price
+    StopMarket,
+    /// Becomes a limit order when the last trade price reaches the trigger price
+    StopLimit,
 }
 
-#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Encode, Decode)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeInForce {
+    /// Rests on the book until it is filled or cancelled
+    #[default]
+    GoodTillCancel,
+    /// Matches immediately and cancels whatever is left, which is reported as a cancel
+    /// event with the unfilled size
+    ImmediateOrCancel,
+    /// Matches immediately in full or is rejected
+    FillOrKill,
+    /// Rests on the book until the end of the current day
+    Day,
+    /// Rests on the book until the specified expire time
+    GoodTillDate,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PostOnly {
+    /// The order may take liquidity
+    #[default]
+    Disabled,
+    /// The order is rejected if it would take liquidity
+    Reject,
+    /// The order is repriced one tick behind the other side of the book if it would take liquidity
+    Slide,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelfTradePrevention {
+    /// Orders from the same account may trade with each other
+    #[default]
+    Disabled,
+    /// The incoming order is cancelled
+    CancelNewest,
+    /// The resting order is cancelled
+    CancelOldest,
+    /// Both the incoming and resting orders are cancelled
+    CancelBoth,
+    /// Both orders are decremented by the smaller size, and any order left with no size is cancelled
+    DecrementAndCancel,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Side {
     #[default]
     Buy,
     Sell,
 }
 
-#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Eq, Serialize)]
 pub struct Order {
     id: u64,
+    account_id: u64,
     price: u64,
     size: u64,
     remaining: u64,
+    hidden: u64,
+    display_size: u64,
+    post_only: PostOnly,
+    time_in_force: TimeInForce,
+    expire_time: u64,
     side: Side,
     status: OrderStatus,
+    #[serde(rename = "order_type")]
     typ: OrderType,
 }
 
@@ -35,8 +95,14 @@ impl Order {
     pub fn new(price: u64, size: u64, side: Side, status: OrderStatus, typ: OrderType) -> Self {
         Self {
             id: 0,
+            account_id: 0,
             price,
             remaining: size,
+            hidden: 0,
+            display_size: 0,
+            post_only: PostOnly::Disabled,
+            time_in_force: TimeInForce::GoodTillCancel,
+            expire_time: 0,
             side,
             size,
             status,
@@ -49,16 +115,51 @@ impl Order {
         self.id
     }
 
+    /// Returns the id of the account that owns the order
+    pub fn get_account_id(&self) -> u64 {
+        self.account_id
+    }
+
     /// Returns the order price
     pub fn get_price(&self) -> u64 {
         self.price
     }
 
+    /// Returns the order side
+    pub fn get_side(&self) -> Side {
+        self.side
+    }
+
     /// Returns the unfilled order size
     pub fn get_remaining(&self) -> u64 {
         self.remaining
     }
 
+    /// Returns the unfilled size held in reserve by an iceberg order
+    pub fn get_hidden(&self) -> u64 {
+        self.hidden
+    }
+
+    /// Returns the displayed peak size of an iceberg order, or zero if it is fully displayed
+    pub fn get_display_size(&self) -> u64 {
+        self.display_size
+    }
+
+    /// Returns the post-only instruction the order was submitted with
+    pub fn get_post_only(&self) -> PostOnly {
+        self.post_only
+    }
+
+    /// Returns the time in force the order was submitted with
+    pub fn get_time_in_force(&self) -> TimeInForce {
+        self.time_in_force
+    }
+
+    /// Returns the time at which the order expires, or zero if it does not expire
+    pub fn get_expire_time(&self) -> u64 {
+        self.expire_time
+    }
+
     /// Returns the order size
     pub fn get_size(&self) -> u64 {
         self.size
@@ -98,4 +199,30 @@ impl Order {
     pub fn set_id(&mut self, id: u64) {
         self.id = id;
     }
+
+    /// Sets the account id
+    pub fn set_account_id(&mut self, account_id: u64) {
+        self.account_id = account_id;
+    }
+
+    /// Sets the hidden size
+    pub fn set_hidden(&mut self, hidden: u64) {
+        self.hidden = hidden;
+    }
+
+    /// Sets the display size
+    pub fn set_display_size(&mut self, display_size: u64) {
+        self.display_size = display_size;
+    }
+
+    /// Sets the post-only instruction
+    pub fn set_post_only(&mut self, post_only: PostOnly) {
+        self.post_only = post_only;
+    }
+
+    /// Sets the time in force and the time at which the order expires
+    pub fn set_time_in_force(&mut self, time_in_force: TimeInForce, expire_time: u64) {
+        self.time_in_force = time_in_force;
+        self.expire_time = expire_time;
+    }
 }
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use crate::error::ExchangeError;
+use crate::order::Order;
+
+/// Marks the end of a linked list of orders
+pub const NIL: usize = usize::MAX;
+
+#[derive(Clone, Copy, Debug)]
+struct OrderNode {
+    order: Order,
+    prev: usize,
+    next: usize,
+}
+
+/// Storage shared by every price level of an order book
+///
+/// Each slot holds one resting order plus the links to its neighbours in the queue of
+/// its price level, so orders can be unlinked in constant time. Slots of removed orders
+/// are recycled before the arena grows
+#[derive(Debug)]
+pub struct OrderArena {
+    nodes: Vec<OrderNode>,
+    free_slots: Vec<usize>,
+    slot_by_id: HashMap<u64, usize>,
+}
+
+impl OrderArena {
+    /// Creates an arena with memory allocated up front for the given number of orders
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            nodes: Vec::with_capacity(capacity),
+            free_slots: Vec::new(),
+            slot_by_id: HashMap::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the number of orders in the arena
+    pub fn len(&self) -> usize {
+        self.slot_by_id.len()
+    }
+
+    /// Returns true if there are no orders in the arena
+    pub fn is_empty(&self) -> bool {
+        self.slot_by_id.is_empty()
+    }
+
+    /// Reserves memory for one more order
+    ///
+    /// Returns an error if the arena cannot grow, so that an order can be rejected before
+    /// it is matched against the book
+    pub fn reserve(&mut self) -> Result<(), ExchangeError> {
+        if !self.free_slots.is_empty() {
+            return Ok(());
+        }
+        if self.nodes.try_reserve(1).is_err() || self.slot_by_id.try_reserve(1).is_err() {
+            return Err(ExchangeError::CapacityExhausted);
+        }
+        Ok(())
+    }
+
+    /// Stores an unlinked order and returns its slot
+    pub fn insert(&mut self, order: Order) -> Result<usize, ExchangeError> {
+        self.reserve()?;
+        let node = OrderNode {
+            order,
+            prev: NIL,
+            next: NIL,
+        };
+        let slot = match self.free_slots.pop() {
+            Some(slot) => {
+                self.nodes[slot] = node;
+                slot
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+        self.slot_by_id.insert(order.get_id(), slot);
+        Ok(slot)
+    }
+
+    /// Frees the slot of an unlinked order and returns the order
+    pub fn remove(&mut self, slot: usize) -> Order {
+        let order = self.nodes[slot].order;
+        self.slot_by_id.remove(&order.get_id());
+        self.free_slots.push(slot);
+        order
+    }
+
+    /// Returns the slot holding the order with the given id
+    pub fn get_slot(&self, id: u64) -> Option<usize> {
+        self.slot_by_id.get(&id).copied()
+    }
+
+    /// Returns the order with the given id
+    pub fn get_by_id(&self, id: u64) -> Option<&Order> {
+        self.get_slot(id).map(|slot| self.get(slot))
+    }
+
+    /// Returns the order in the given slot
+    pub fn get(&self, slot: usize) -> &Order {
+        &self.nodes[slot].order
+    }
+
+    /// Returns mutable reference to the order in the given slot
+    pub fn get_mut(&mut self, slot: usize) -> &mut Order {
+        &mut self.nodes[slot].order
+    }
+
+    /// Returns the slot before the given slot in its queue
+    pub fn get_prev(&self, slot: usize) -> usize {
+        self.nodes[slot].prev
+    }
+
+    /// Returns the slot after the given slot in its queue
+    pub fn get_next(&self, slot: usize) -> usize {
+        self.nodes[slot].next
+    }
+
+    /// Sets the slot before the given slot in its queue
+    pub fn set_prev(&mut self, slot: usize, prev: usize) {
+        self.nodes[slot].prev = prev;
+    }
+
+    /// Sets the slot after the given slot in its queue
+    pub fn set_next(&mut self, slot: usize, next: usize) {
+        self.nodes[slot].next = next;
+    }
+}
@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::order::{Order, OrderStatus, OrderType, Side};
+    use crate::order_arena::{NIL, OrderArena};
+
+    fn new_order(id: u64) -> Order {
+        let mut order = Order::new(100, 1, Side::Buy, OrderStatus::Open, OrderType::Limit);
+        order.set_id(id);
+        order
+    }
+
+    #[test]
+    fn test_with_capacity() {
+        let arena = OrderArena::with_capacity(16);
+        assert_eq!(arena.len(), 0);
+        assert!(arena.is_empty());
+        assert!(arena.get_slot(1).is_none());
+        assert!(arena.get_by_id(1).is_none());
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut arena = OrderArena::with_capacity(16);
+        let slot = arena.insert(new_order(7)).unwrap();
+        assert_eq!(arena.len(), 1);
+        assert_eq!(arena.get_slot(7), Some(slot));
+        assert_eq!(arena.get(slot).get_id(), 7);
+        assert_eq!(arena.get_by_id(7).unwrap().get_id(), 7);
+        assert_eq!(arena.get_prev(slot), NIL);
+        assert_eq!(arena.get_next(slot), NIL);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut arena = OrderArena::with_capacity(16);
+        let slot = arena.insert(new_order(7)).unwrap();
+        let order = arena.remove(slot);
+        assert_eq!(order.get_id(), 7);
+        assert!(arena.is_empty());
+        assert!(arena.get_slot(7).is_none());
+    }
+
+    #[test]
+    fn test_insert_reuses_free_slots() {
+        let mut arena = OrderArena::with_capacity(16);
+        let first = arena.insert(new_order(1)).unwrap();
+        let second = arena.insert(new_order(2)).unwrap();
+        arena.remove(first);
+        let third = arena.insert(new_order(3)).unwrap();
+        assert_eq!(third, first);
+        assert_ne!(third, second);
+        assert_eq!(arena.len(), 2);
+    }
+
+    #[test]
+    fn test_links() {
+        let mut arena = OrderArena::with_capacity(16);
+        let first = arena.insert(new_order(1)).unwrap();
+        let second = arena.insert(new_order(2)).unwrap();
+        arena.set_next(first, second);
+        arena.set_prev(second, first);
+        assert_eq!(arena.get_next(first), second);
+        assert_eq!(arena.get_prev(second), first);
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut arena = OrderArena::with_capacity(16);
+        let slot = arena.insert(new_order(1)).unwrap();
+        arena.get_mut(slot).set_remaining(0);
+        assert_eq!(arena.get_by_id(1).unwrap().get_remaining(), 0);
+    }
+
+    #[test]
+    fn test_grows_beyond_initial_capacity() {
+        let mut arena = OrderArena::with_capacity(4);
+        for i in 0..100 {
+            assert!(arena.insert(new_order(i + 1)).is_ok());
+        }
+        assert_eq!(arena.len(), 100);
+        assert!(arena.reserve().is_ok());
+    }
+}
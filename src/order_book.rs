@@ -1,11 +1,29 @@
+use bincode::{Decode, Encode};
+use serde::Serialize;
+
+use crate::error::ExchangeError;
 use crate::market::Market;
-use crate::order::{OrderType, Side};
+use crate::order::{
+    Order, OrderStatus, OrderType, PostOnly, SelfTradePrevention, Side, TimeInForce,
+};
+use crate::order_arena::OrderArena;
+use crate::price_ladder::PriceLadder;
 use crate::price_level::PriceLevel;
-use crate::request::{CancelOrderRequest, SubmitOrderRequest};
+use crate::published_depth::{DEFAULT_DEPTH_LEVELS, PublishedDepth};
+use crate::published_quote::PublishedQuote;
+use crate::request::{AmendOrderRequest, CancelOrderRequest, SubmitOrderRequest};
+use crate::response::{OrderEvent, OrderEventKind, SubmitOrderResponse};
+use crate::snapshot::{LevelSnapshot, OrderBookSnapshot};
+use crate::stop_book::StopBook;
+use crate::sync::Arc;
+use crate::trade::Trade;
+
+use std::collections::BTreeSet;
 
-use std::collections::HashMap;
+const NANOS_PER_DAY: u64 = 86_400_000_000_000;
+const INITIAL_ORDER_CAPACITY: usize = 65_536;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, Serialize)]
 pub struct BestQuote {
     pub best_bid_price: u64,
     pub best_ask_price: u64,
@@ -13,22 +31,80 @@ pub struct BestQuote {
     pub best_ask_size: u64,
 }
 
+/// Aggregated resting orders at a single price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, Serialize)]
+pub struct DepthLevel {
+    pub price: u64,
+    /// Displayed size at the price, leaving out the hidden reserve of iceberg orders
+    pub size: u64,
+    pub order_count: u64,
+}
+
+/// Aggregated price levels on both sides of an order book, best price first
+#[derive(Debug, Clone, PartialEq, Eq, Default, Encode, Decode, Serialize)]
+pub struct Depth {
+    /// Sequence number of the last event in the book
+    pub sequence: u64,
+    pub bids: Vec<DepthLevel>,
+    pub asks: Vec<DepthLevel>,
+}
+
+/// Counter giving each event in a book the next sequence number
+#[derive(Debug, Default, Clone, Copy)]
+struct EventSequence {
+    last: u64,
+}
+
+impl EventSequence {
+    /// Take the sequence number of the next event
+    fn next(&mut self) -> u64 {
+        self.last += 1;
+        self.last
+    }
+
+    /// Returns the sequence number of the last event, or zero if there have been none
+    fn get_last(&self) -> u64 {
+        self.last
+    }
+
+    /// Returns an order event with the sequence number of the next event
+    fn order_event(&mut self, kind: OrderEventKind, id: u64, size: u64) -> OrderEvent {
+        OrderEvent {
+            id,
+            sequence: self.next(),
+            kind,
+            size,
+        }
+    }
+}
+
 pub struct OrderBook {
     market: Market,
     last_order_id: u64,
+    last_trade_id: u64,
+    last_trade_price: u64,
+    sequence: EventSequence,
     best_quote: BestQuote,
-    price_by_id: HashMap<u64, u64>,
-    price_levels: Vec<PriceLevel>,
+    published_quote: Arc<PublishedQuote>,
+    published_depth: Arc<PublishedDepth>,
+    last_published_quote: BestQuote,
+    last_published_sequence: u64,
+    orders: OrderArena,
+    price_ladder: PriceLadder,
+    time: u64,
+    expiries: BTreeSet<(u64, u64)>,
+    stop_book: StopBook,
 }
 
 impl OrderBook {
     /// Create a new order book for given market
     pub fn new(market: Market) -> Self {
-        let mut price_levels = Vec::<PriceLevel>::new();
-        let total_levels = market.get_max_price() - market.get_min_price();
-        for i in 0..total_levels {
-            price_levels.push(PriceLevel::new(market.get_min_price() + i));
-        }
+        let price_ladder = PriceLadder::new(
+            market.get_ladder_type(),
+            market.get_min_price(),
+            market.get_max_price(),
+            market.get_tick_size(),
+        );
         let best_quote = BestQuote {
             best_ask_price: market.get_max_price(),
             best_bid_price: market.get_min_price(),
@@ -37,11 +113,170 @@ impl OrderBook {
         };
         Self {
             last_order_id: 0,
-            price_levels,
+            last_trade_id: 0,
+            last_trade_price: 0,
+            sequence: EventSequence::default(),
+            price_ladder,
             best_quote,
+            published_quote: Arc::new(PublishedQuote::new(best_quote, 0)),
+            published_depth: Arc::new(PublishedDepth::new(&Depth::default(), DEFAULT_DEPTH_LEVELS)),
+            last_published_quote: best_quote,
+            last_published_sequence: 0,
             market,
-            price_by_id: HashMap::new(),
+            orders: OrderArena::with_capacity(INITIAL_ORDER_CAPACITY),
+            time: 0,
+            expiries: BTreeSet::new(),
+            stop_book: StopBook::new(),
+        }
+    }
+
+    /// Returns a copy of the complete state of the order book
+    pub fn snapshot(&self) -> OrderBookSnapshot {
+        let snapshot_side = |side| {
+            self.get_side_of_book(side)
+                .into_iter()
+                .map(|price_level| LevelSnapshot {
+                    price: price_level.get_price(),
+                    orders: price_level.get_orders(&self.orders),
+                })
+                .collect()
+        };
+        OrderBookSnapshot {
+            market: self.market.clone(),
+            last_order_id: self.last_order_id,
+            last_trade_id: self.last_trade_id,
+            last_trade_price: self.last_trade_price,
+            last_sequence: self.sequence.get_last(),
+            best_quote: self.best_quote,
+            time: self.time,
+            bids: snapshot_side(Side::Buy),
+            asks: snapshot_side(Side::Sell),
+            expiries: self.expiries.iter().copied().collect(),
+            stop_orders: self.stop_book.get_orders(),
+        }
+    }
+
+    /// Rebuild an order book from a snapshot
+    ///
+    /// Orders are added back to their price levels in queue order, so the restored book
+    /// matches the book the snapshot was taken from, including the priority of its orders.
+    /// The best quote is recomputed from the restored levels rather than trusted, and a
+    /// snapshot whose last order id is below the id of one of its orders is rejected, so
+    /// new orders can never reuse the id of a restored one
+    pub fn restore(snapshot: OrderBookSnapshot) -> Result<Self, ExchangeError> {
+        if snapshot.market.validate().is_err() {
+            return Err(ExchangeError::SnapshotCorrupt);
         }
+        let max_stop_id = snapshot.stop_orders.iter().map(|(id, _)| *id).max();
+        if max_stop_id.is_some_and(|id| id > snapshot.last_order_id) {
+            return Err(ExchangeError::SnapshotCorrupt);
+        }
+        let mut order_book = Self::new(snapshot.market);
+        for (side, levels) in [(Side::Buy, snapshot.bids), (Side::Sell, snapshot.asks)] {
+            for level in levels {
+                if level.price < order_book.market.get_min_price()
+                    || level.price >= order_book.market.get_max_price()
+                    || !order_book.is_valid_tick(level.price)
+                {
+                    return Err(ExchangeError::SnapshotCorrupt);
+                }
+                let index = order_book.get_price_level_index(level.price) as usize;
+                for order in level.orders {
+                    if order.get_side() != side
+                        || order.get_price() != level.price
+                        || order.get_id() > snapshot.last_order_id
+                        || order_book.orders.get_slot(order.get_id()).is_some()
+                    {
+                        return Err(ExchangeError::SnapshotCorrupt);
+                    }
+                    order_book
+                        .price_ladder
+                        .add_order(&mut order_book.orders, index, order)?;
+                }
+            }
+        }
+        order_book.last_order_id = snapshot.last_order_id;
+        order_book.last_trade_id = snapshot.last_trade_id;
+        order_book.last_trade_price = snapshot.last_trade_price;
+        order_book.sequence = EventSequence {
+            last: snapshot.last_sequence,
+        };
+        order_book.update_best_bid();
+        order_book.update_best_ask();
+        order_book.published_quote = Arc::new(PublishedQuote::new(
+            order_book.best_quote,
+            snapshot.last_sequence,
+        ));
+        order_book.set_published_depth_levels(DEFAULT_DEPTH_LEVELS);
+        order_book.last_published_quote = order_book.best_quote;
+        order_book.last_published_sequence = snapshot.last_sequence;
+        order_book.time = snapshot.time;
+        order_book.expiries = snapshot.expiries.into_iter().collect();
+        for (id, request) in snapshot.stop_orders {
+            order_book.stop_book.add_order(id, request);
+        }
+        Ok(order_book)
+    }
+
+    /// Returns the market of the order book
+    pub fn get_market(&self) -> &Market {
+        &self.market
+    }
+
+    /// Returns the best bid and best ask
+    pub fn get_best_quote(&self) -> BestQuote {
+        self.best_quote
+    }
+
+    /// Returns the best quote as published to readers on other threads
+    ///
+    /// The quote is published at the end of each request that changes the book, so
+    /// readers never see the intermediate states of a sweep
+    pub fn get_published_quote(&self) -> &Arc<PublishedQuote> {
+        &self.published_quote
+    }
+
+    /// Returns the best price levels as published to readers on other threads
+    ///
+    /// The levels are published together with the quote, and only the best levels of
+    /// each side are kept up to date, `DEFAULT_DEPTH_LEVELS` unless set otherwise
+    pub fn get_published_depth(&self) -> &Arc<PublishedDepth> {
+        &self.published_depth
+    }
+
+    /// Set the number of price levels on each side published to readers on other threads
+    ///
+    /// The published depth is replaced, so this must be called before it is handed out
+    pub fn set_published_depth_levels(&mut self, levels: usize) {
+        self.published_depth = Arc::new(PublishedDepth::new(&self.get_depth(levels), levels));
+    }
+
+    /// Publish the best quote and the best price levels to readers on other threads, if
+    /// the quote or the sequence number of the last event has changed
+    fn publish_quote(&mut self) {
+        if self.best_quote != self.last_published_quote
+            || self.sequence.get_last() != self.last_published_sequence
+        {
+            self.published_quote
+                .publish(&self.best_quote, self.sequence.get_last());
+            self.published_depth.publish(
+                self.sequence.get_last(),
+                self.get_depth_levels(Side::Buy),
+                self.get_depth_levels(Side::Sell),
+            );
+            self.last_published_quote = self.best_quote;
+            self.last_published_sequence = self.sequence.get_last();
+        }
+    }
+
+    /// Returns the sequence number of the last event in the book, or zero if there have
+    /// been none
+    ///
+    /// Every accept, amend, fill, cancel, expiry, self-trade reduction and stop trigger
+    /// takes the next number, so the events of a market are numbered from one without
+    /// gaps
+    pub fn get_sequence(&self) -> u64 {
+        self.sequence.get_last()
     }
 
     /// Returns the best bid price
@@ -64,24 +299,44 @@ impl OrderBook {
         self.best_quote.best_ask_size
     }
 
-    /// Get the index that represents the lowest price level on the bid side of the book
-    fn get_bid_from_index(&self) -> usize {
-        0
+    /// Returns the price of the most recent trade, or zero if there have been no trades
+    pub fn get_last_trade_price(&self) -> u64 {
+        self.last_trade_price
     }
 
-    /// Get the index that represents the highest price level on the bid side of the book
-    fn get_bid_to_index(&self) -> usize {
-        (self.best_quote.best_bid_price - self.market.get_min_price() + 1) as usize
+    /// Returns the number of stop orders waiting to be triggered
+    pub fn get_stop_order_count(&self) -> usize {
+        self.stop_book.len()
     }
 
-    /// Get the index that represents the lowest price level on the ask side of the book
-    fn get_ask_from_index(&self) -> usize {
-        (self.best_quote.best_ask_price - self.market.get_min_price()) as usize
+    /// Returns the current time of the order book clock in nanoseconds
+    pub fn get_time(&self) -> u64 {
+        self.time
     }
 
-    /// Get the index that represents the highest price level on the ask side of the book
-    fn get_ask_to_index(&self) -> usize {
-        self.price_levels.len()
+    /// Returns the storage holding every resting order in the book
+    pub fn get_order_arena(&self) -> &OrderArena {
+        &self.orders
+    }
+
+    /// Returns the storage holding every price level in the book
+    pub fn get_price_ladder(&self) -> &PriceLadder {
+        &self.price_ladder
+    }
+
+    /// Returns the resting order with the given id
+    pub fn get_order(&self, id: u64) -> Option<&Order> {
+        self.orders.get_by_id(id)
+    }
+
+    /// Returns the resting orders on specified side of the book, best price first and in
+    /// time priority within each price level
+    pub fn get_orders(&self, side: Side) -> Vec<Order> {
+        let mut orders = Vec::new();
+        for price_level in self.get_side_of_book(side) {
+            orders.extend(price_level.iter(&self.orders));
+        }
+        orders
     }
 
     /// Get the bids
@@ -94,128 +349,121 @@ impl OrderBook {
         self.get_side_of_book(Side::Sell)
     }
 
-    /// Get the price levels for specified side of the book
+    /// Get the price levels for specified side of the book, best price first
     pub fn get_side_of_book(&self, side: Side) -> Vec<&PriceLevel> {
         let mut side_of_book = Vec::new();
-        let price_levels = self.get_price_levels(side);
-        for price_level in price_levels.iter() {
-            if price_level.get_size() > 0 {
-                side_of_book.push(price_level);
-            }
+        let mut cursor = self.price_ladder.get_best_index(side);
+        while let Some(index) = cursor {
+            side_of_book.extend(self.price_ladder.get_level(side, index));
+            cursor = self.price_ladder.get_next_index(side, index);
         }
-        match side {
-            Side::Buy => side_of_book.into_iter().rev().collect(),
-            Side::Sell => side_of_book,
+        side_of_book
+    }
+
+    /// Returns up to the specified number of price levels on each side of the book, best
+    /// price first, with the size and order count of each level
+    ///
+    /// Only the returned levels are visited, so the cost does not grow with the number
+    /// of levels in the book
+    pub fn get_depth(&self, levels: usize) -> Depth {
+        Depth {
+            sequence: self.sequence.get_last(),
+            bids: self.get_side_depth(Side::Buy, levels),
+            asks: self.get_side_depth(Side::Sell, levels),
         }
     }
 
+    /// Get up to the specified number of aggregated price levels on one side of the book,
+    /// best price first
+    fn get_side_depth(&self, side: Side, levels: usize) -> Vec<DepthLevel> {
+        self.get_depth_levels(side).take(levels).collect()
+    }
+
+    /// Iterate over the aggregated price levels on one side of the book, best price first
+    ///
+    /// Levels are only visited as the iterator is advanced
+    fn get_depth_levels(&self, side: Side) -> impl Iterator<Item = DepthLevel> + '_ {
+        let mut cursor = self.price_ladder.get_best_index(side);
+        std::iter::from_fn(move || {
+            while let Some(index) = cursor {
+                cursor = self.price_ladder.get_next_index(side, index);
+                if let Some(price_level) = self.price_ladder.get_level(side, index) {
+                    return Some(DepthLevel {
+                        price: price_level.get_price(),
+                        size: price_level.get_size(),
+                        order_count: price_level.get_order_count(),
+                    });
+                }
+            }
+            None
+        })
+    }
+
     /// Get the price level index for a given price
     fn get_price_level_index(&self, price: u64) -> u64 {
-        price - self.market.get_min_price()
+        (price - self.market.get_min_price()) / self.market.get_tick_size()
     }
 
     /// Update the best bid price and size
     fn update_best_bid(&mut self) {
-        let bids = self.get_price_levels(Side::Buy);
-        let mut best_bid_price = 0;
-        let mut best_bid_size = 0;
-        for i in 0..bids.len() {
-            let bid = bids.get(bids.len() - i - 1).unwrap();
-            let size = bid.get_size();
-            if size > 0 {
-                best_bid_price = bid.get_price();
-                best_bid_size = size;
-                break;
+        let best_bid = self
+            .get_best_price_level(Side::Buy)
+            .map(|bid| (bid.get_price(), bid.get_size()));
+        match best_bid {
+            Some((price, size)) => {
+                self.best_quote.best_bid_price = price;
+                self.best_quote.best_bid_size = size;
+            }
+            None => {
+                self.best_quote.best_bid_price = self.market.get_min_price();
+                self.best_quote.best_bid_size = 0;
             }
-        }
-        if best_bid_price > 0 {
-            self.best_quote.best_bid_price = best_bid_price;
-            self.best_quote.best_bid_size = best_bid_size;
-        } else {
-            self.best_quote.best_bid_price = self.market.get_min_price();
-            self.best_quote.best_bid_size = 0;
         }
     }
 
     /// Update the best ask price and size
     fn update_best_ask(&mut self) {
-        let asks = self.get_price_levels(Side::Sell);
-        let mut best_ask_price = 0;
-        let mut best_ask_size = 0;
-        for i in 0..asks.len() {
-            let ask = asks.get(i).unwrap();
-            let size = ask.get_size();
-            if size > 0 {
-                best_ask_price = ask.get_price();
-                best_ask_size = size;
-                break;
-            }
-        }
-        if best_ask_price > 0 {
-            self.best_quote.best_ask_price = best_ask_price;
-            self.best_quote.best_ask_size = best_ask_size;
-        } else {
-            self.best_quote.best_ask_price = self.market.get_max_price();
-            self.best_quote.best_ask_size = 0;
-        }
-    }
-
-    /// Get immutable reference to the price levels representing specified side of the book
-    fn get_price_levels(&self, side: Side) -> &[PriceLevel] {
-        match side {
-            Side::Buy => {
-                let bid_from_index = self.get_bid_from_index();
-                let bid_to_index = self.get_bid_to_index();
-                &self.price_levels[bid_from_index..bid_to_index]
+        let best_ask = self
+            .get_best_price_level(Side::Sell)
+            .map(|ask| (ask.get_price(), ask.get_size()));
+        match best_ask {
+            Some((price, size)) => {
+                self.best_quote.best_ask_price = price;
+                self.best_quote.best_ask_size = size;
             }
-            Side::Sell => {
-                let ask_from_index = self.get_ask_from_index();
-                let ask_to_index = self.get_ask_to_index();
-                &self.price_levels[ask_from_index..ask_to_index]
+            None => {
+                self.best_quote.best_ask_price = self.market.get_max_price();
+                self.best_quote.best_ask_size = 0;
             }
         }
     }
 
-    /// Get a mutable reference to the price levels representing specified side of the book
-    fn get_price_levels_mut(&mut self, side: Side) -> &mut [PriceLevel] {
-        match side {
-            Side::Buy => {
-                let bid_from_index = self.get_bid_from_index();
-                let bid_to_index = self.get_bid_to_index();
-                &mut self.price_levels[bid_from_index..bid_to_index]
-            }
-            Side::Sell => {
-                let ask_from_index = self.get_ask_from_index();
-                let ask_to_index = self.get_ask_to_index();
-                &mut self.price_levels[ask_from_index..ask_to_index]
-            }
-        }
+    /// Get the best occupied price level on specified side of the book
+    fn get_best_price_level(&self, side: Side) -> Option<&PriceLevel> {
+        let index = self.price_ladder.get_best_index(side)?;
+        self.price_ladder.get_level(side, index)
     }
 
     /// Do matching when order crosses with the other side of the book
-    fn handle_crossing_order(&mut self, request: &mut SubmitOrderRequest) {
+    ///
+    /// Each execution against a resting order is appended to the trades of the response,
+    /// and orders cancelled or reduced by self-trade prevention are appended to its order
+    /// events, in the order they happen
+    fn handle_crossing_order(
+        &mut self,
+        request: &mut SubmitOrderRequest,
+        response: &mut SubmitOrderResponse,
+    ) {
+        let aggressor_id = response.id;
         // get the passive side of the book
         let other_side = match request.side {
             Side::Buy => Side::Sell,
             Side::Sell => Side::Buy,
         };
-        // get a mutable reference to the passive price levels
-        let price_levels = self.get_price_levels_mut(other_side);
-        // these variables are used to update the cursor for each side of the book
-        let mut best_bid_price = 0;
-        let mut best_ask_price = 0;
-        // loop over the price levels on the book
-        for i in 0..price_levels.len() {
-            // iterate from the back when sell
-            let offset = match request.side {
-                Side::Buy => i,
-                Side::Sell => price_levels.len() - i - 1,
-            };
-            let price_level = &mut price_levels[offset];
-            // skip levels with no size
-            if price_level.get_size() == 0 {
-                continue;
-            }
+        // walk the occupied passive price levels from the best price
+        let mut cursor = self.price_ladder.get_best_index(other_side);
+        while let Some(index) = cursor {
+            let price_level = self.price_ladder.get_level_mut(other_side, index).unwrap();
             // end matching when aggressive order is fully filled
             if request.size == 0 {
                 break;
@@ -227,88 +475,306 @@ impl OrderBook {
             {
                 break;
             }
-            let mut remove_ids = Vec::new();
-            // loop over the orders at the price level
-            let orders = price_level.get_orders_mut();
-            for i in 0..orders.len() {
-                let passive_order = orders.get_mut(i).unwrap();
-                // if there's no order at this memory location, we can move to next price level
-                if passive_order.get_price() == 0 {
+            let level_price = price_level.get_price();
+            // match with the front of the queue until the level or the aggressive order is exhausted
+            while request.size > 0 {
+                let Some(slot) = price_level.get_front() else {
                     break;
-                }
-                if passive_order.get_remaining() >= request.size {
-                    // aggressive order is fully matched by this passive order
-                    passive_order.set_remaining(passive_order.get_remaining() - request.size);
-                    request.size = 0;
-                    // update the best bid/ask price as we traverse the book
-                    match request.side {
-                        Side::Buy => best_ask_price = passive_order.get_price(),
-                        Side::Sell => best_bid_price = passive_order.get_price(),
+                };
+                let passive_order = *self.orders.get(slot);
+                let passive_id = passive_order.get_id();
+                // prevent the aggressive order from trading with a resting order of the same account
+                let is_self_trade = request.self_trade_prevention != SelfTradePrevention::Disabled
+                    && passive_order.get_account_id() == request.account_id;
+                if is_self_trade {
+                    let passive_size = passive_order.get_remaining() + passive_order.get_hidden();
+                    let events = &mut response.order_events;
+                    match request.self_trade_prevention {
+                        SelfTradePrevention::CancelNewest => {
+                            events.push(self.sequence.order_event(
+                                OrderEventKind::Cancelled,
+                                aggressor_id,
+                                request.size,
+                            ));
+                            request.size = 0;
+                        }
+                        SelfTradePrevention::CancelOldest => {
+                            price_level.resize_order(&mut self.orders, slot, 0, 0);
+                            events.push(self.sequence.order_event(
+                                OrderEventKind::Cancelled,
+                                passive_id,
+                                passive_size,
+                            ));
+                        }
+                        SelfTradePrevention::CancelBoth => {
+                            price_level.resize_order(&mut self.orders, slot, 0, 0);
+                            events.push(self.sequence.order_event(
+                                OrderEventKind::Cancelled,
+                                passive_id,
+                                passive_size,
+                            ));
+                            events.push(self.sequence.order_event(
+                                OrderEventKind::Cancelled,
+                                aggressor_id,
+                                request.size,
+                            ));
+                            request.size = 0;
+                        }
+                        SelfTradePrevention::DecrementAndCancel => {
+                            let decrement = passive_order.get_remaining().min(request.size);
+                            price_level.fill_order(&mut self.orders, slot, decrement);
+                            request.size -= decrement;
+                            // each order is cancelled if the decrement leaves it with no size
+                            let passive_kind = if decrement == passive_size {
+                                OrderEventKind::Cancelled
+                            } else {
+                                OrderEventKind::Reduced
+                            };
+                            let aggressor_kind = if request.size == 0 {
+                                OrderEventKind::Cancelled
+                            } else {
+                                OrderEventKind::Reduced
+                            };
+                            events.push(self.sequence.order_event(
+                                passive_kind,
+                                passive_id,
+                                decrement,
+                            ));
+                            events.push(self.sequence.order_event(
+                                aggressor_kind,
+                                aggressor_id,
+                                decrement,
+                            ));
+                        }
+                        SelfTradePrevention::Disabled => {}
                     }
                 } else {
-                    // passive order is fully matched by the aggressive order
-                    request.size -= passive_order.get_remaining();
-                    passive_order.set_remaining(0);
-                    // update the best bid/ask price as we traverse the book
-                    match request.side {
-                        Side::Buy => best_ask_price = passive_order.get_price(),
-                        Side::Sell => best_bid_price = passive_order.get_price(),
-                    }
+                    let fill_size = passive_order.get_remaining().min(request.size);
+                    self.last_trade_id += 1;
+                    self.last_trade_price = level_price;
+                    response.trades.push(Trade::new(
+                        self.last_trade_id,
+                        self.sequence.next(),
+                        aggressor_id,
+                        passive_id,
+                        level_price,
+                        fill_size,
+                        request.side,
+                    ));
+                    price_level.fill_order(&mut self.orders, slot, fill_size);
+                    request.size -= fill_size;
                 }
-                // if the passive order is fully matched, then remove it from the price level
-                if passive_order.get_remaining() == 0 {
-                    remove_ids.push(passive_order.get_id());
+                // if the passive order is fully matched, then either replenish its displayed
+                // size from the hidden reserve or remove it from the price level
+                let passive_order = self.orders.get(slot);
+                let remaining = passive_order.get_remaining();
+                let hidden = passive_order.get_hidden();
+                if remaining == 0 && hidden > 0 {
+                    price_level.replenish_order(&mut self.orders, passive_id);
+                } else if remaining == 0 {
+                    price_level.remove_order(&mut self.orders, passive_id);
                 }
-                // finish matching if the request is fully filled
-                if request.size == 0 {
-                    break;
-                }
-            }
-            // remove matched orders from price level
-            for id in remove_ids {
-                // TODO: pass the list into this function then we only have to loop over the orders once
-                price_level.remove_order(id);
             }
+            self.price_ladder.release_level(other_side, index);
+            cursor = self.price_ladder.get_next_index(other_side, index);
         }
-        // if there's leftover size, add it to the book and update best bid/ask
+        // if there's leftover size, add it to the book or cancel it
         if request.size > 0 {
-            match request.side {
-                Side::Buy => {
-                    best_bid_price = request.price;
-                    best_ask_price = request.price + 1;
+            if Self::is_resting_order(request) {
+                self.rest_order(request, aggressor_id, response);
+            } else {
+                self.cancel_remainder(request, aggressor_id, response);
+            }
+        }
+        self.update_best_bid();
+        self.update_best_ask();
+    }
+
+    /// Returns true if a limit order would match with the other side of the book
+    fn is_crossing_order(&self, request: &SubmitOrderRequest) -> bool {
+        match request.side {
+            Side::Buy => {
+                self.best_quote.best_ask_size > 0 && request.price >= self.best_quote.best_ask_price
+            }
+            Side::Sell => {
+                self.best_quote.best_bid_size > 0 && request.price <= self.best_quote.best_bid_price
+            }
+        }
+    }
+
+    /// Enforce the post-only instruction of an order that would take liquidity, by
+    /// either rejecting it or sliding its price one tick behind the other side of the book
+    fn apply_post_only(&self, request: &mut SubmitOrderRequest) -> Result<(), ExchangeError> {
+        if request.post_only == PostOnly::Disabled {
+            return Ok(());
+        }
+        if request.order_type != OrderType::Limit {
+            return Err(ExchangeError::PostOnlyNotLimit);
+        }
+        if !self.is_crossing_order(request) {
+            return Ok(());
+        }
+        if request.post_only == PostOnly::Reject {
+            return Err(ExchangeError::PostOnlyWouldTake);
+        }
+        match request.side {
+            Side::Buy => {
+                if self.best_quote.best_ask_price <= self.market.get_min_price() {
+                    return Err(ExchangeError::PostOnlySlideBelowMin);
                 }
-                Side::Sell => {
-                    best_ask_price = request.price;
-                    best_bid_price = request.price - 1;
+                request.price = self.best_quote.best_ask_price - self.market.get_tick_size();
+            }
+            Side::Sell => {
+                if self.best_quote.best_bid_price + self.market.get_tick_size()
+                    >= self.market.get_max_price()
+                {
+                    return Err(ExchangeError::PostOnlySlideAboveMax);
                 }
+                request.price = self.best_quote.best_bid_price + self.market.get_tick_size();
             }
-            self.handle_passive_order(request);
         }
-        if best_bid_price > 0 {
-            self.best_quote.best_bid_price = best_bid_price;
+        Ok(())
+    }
+
+    /// Returns true if the price is on the tick grid of the market
+    fn is_valid_tick(&self, price: u64) -> bool {
+        (price - self.market.get_min_price()).is_multiple_of(self.market.get_tick_size())
+    }
+
+    /// Check an order size is non-zero and meets the lot size, quantity limits and, for
+    /// orders with a limit price, the minimum notional of the market
+    fn validate_size(&self, size: u64, price: Option<u64>) -> Result<(), ExchangeError> {
+        // checked before the market rules, which all pass a zero size by default
+        if size == 0 {
+            return Err(ExchangeError::SizeZero);
         }
-        if best_ask_price > 0 {
-            self.best_quote.best_ask_price = best_ask_price;
+        if !size.is_multiple_of(self.market.get_lot_size()) {
+            return Err(ExchangeError::SizeNotOnLot);
         }
-        // this moves the best bid/ask to the first price level with size > 0
-        self.update_best_bid();
-        self.update_best_ask();
+        if size < self.market.get_min_quantity() {
+            return Err(ExchangeError::SizeBelowMin);
+        }
+        if size > self.market.get_max_quantity() {
+            return Err(ExchangeError::SizeAboveMax);
+        }
+        if let Some(price) = price
+            && (price as u128) * (size as u128) < self.market.get_min_notional() as u128
+        {
+            return Err(ExchangeError::NotionalBelowMin);
+        }
+        Ok(())
+    }
+
+    /// Returns true if any unfilled size of the order should rest on the book
+    fn is_resting_order(request: &SubmitOrderRequest) -> bool {
+        request.order_type == OrderType::Limit
+            && request.time_in_force != TimeInForce::ImmediateOrCancel
+            && request.time_in_force != TimeInForce::FillOrKill
+    }
+
+    /// Get the total size on the other side of the book that the order can match with
+    fn get_available_size(&self, request: &SubmitOrderRequest) -> u64 {
+        let other_side = match request.side {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        };
+        let mut available_size = 0;
+        let mut cursor = self.price_ladder.get_best_index(other_side);
+        while let Some(index) = cursor {
+            let price_level = self.price_ladder.get_level(other_side, index).unwrap();
+            // if order type == limit, then stop when price is exceeded
+            if request.order_type == OrderType::Limit
+                && ((price_level.get_price() > request.price && request.side == Side::Buy)
+                    || (price_level.get_price() < request.price && request.side == Side::Sell))
+            {
+                break;
+            }
+            available_size += price_level.get_total_size();
+            if available_size >= request.size {
+                break;
+            }
+            cursor = self.price_ladder.get_next_index(other_side, index);
+        }
+        available_size
     }
 
-    /// Add a passive order to the order book
-    fn handle_passive_order(&mut self, request: &mut SubmitOrderRequest) {
+    /// Get the time at which a resting order expires, if it has one
+    fn get_expire_time(&self, request: &SubmitOrderRequest) -> Option<u64> {
+        match request.time_in_force {
+            TimeInForce::GoodTillDate => Some(request.expire_time),
+            TimeInForce::Day => Some((self.time / NANOS_PER_DAY + 1) * NANOS_PER_DAY),
+            _ => None,
+        }
+    }
+
+    /// Add a passive order to the order book with the specified id, cancelling it if its
+    /// price level cannot grow
+    fn rest_order(
+        &mut self,
+        request: &mut SubmitOrderRequest,
+        id: u64,
+        response: &mut SubmitOrderResponse,
+    ) {
+        if !self.handle_passive_order(request, id) {
+            let event = self
+                .sequence
+                .order_event(OrderEventKind::Cancelled, id, request.size);
+            response.order_events.push(event);
+        }
+    }
+
+    /// Cancel the unfilled size of an order that is not allowed to rest, such as the
+    /// remainder of a market or immediate-or-cancel order
+    fn cancel_remainder(
+        &mut self,
+        request: &mut SubmitOrderRequest,
+        id: u64,
+        response: &mut SubmitOrderResponse,
+    ) {
+        let event = self
+            .sequence
+            .order_event(OrderEventKind::Cancelled, id, request.size);
+        response.order_events.push(event);
+        request.size = 0;
+    }
+
+    /// Add a passive order to the order book with the specified id
+    ///
+    /// Returns false if the price level of the order cannot grow
+    fn handle_passive_order(&mut self, request: &mut SubmitOrderRequest, id: u64) -> bool {
+        let expire_time = self.get_expire_time(request);
         let index = self.get_price_level_index(request.price);
-        let price_level = self.price_levels.get_mut(index as usize).unwrap();
         let order_price = request.price;
         let order_side = request.side;
-        price_level.add_order(
+        let mut order = Order::new(
             request.price,
             request.size,
             request.side,
+            OrderStatus::Open,
             request.order_type,
-            self.last_order_id,
         );
-        self.price_by_id.insert(self.last_order_id, request.price);
+        order.set_id(id);
+        order.set_account_id(request.account_id);
+        order.set_post_only(request.post_only);
+        order.set_time_in_force(request.time_in_force, expire_time.unwrap_or(0));
+        // iceberg orders only display their peak size, and keep the rest in reserve
+        if request.display_size > 0 && request.display_size < request.size {
+            order.set_display_size(request.display_size);
+            order.set_remaining(request.display_size);
+            order.set_hidden(request.size - request.display_size);
+        }
+        // capacity is reserved before new orders are matched, so this only fails for
+        // activated stop orders, which are cancelled if their price level cannot grow
+        let price_level = match self
+            .price_ladder
+            .add_order(&mut self.orders, index as usize, order)
+        {
+            Ok(price_level) => price_level,
+            Err(_) => return false,
+        };
+        if let Some(expire_time) = expire_time {
+            self.expiries.insert((expire_time, id));
+        }
         let price_level_size = price_level.get_size();
         match order_side {
             Side::Buy => {
@@ -316,7 +782,7 @@ impl OrderBook {
                     self.best_quote.best_bid_price = price_level.get_price();
                     self.best_quote.best_bid_size = price_level_size;
                 }
-                if order_price > self.best_quote.best_bid_price {
+                if order_price >= self.best_quote.best_bid_price {
                     self.best_quote.best_bid_price = price_level.get_price();
                     self.best_quote.best_bid_size = price_level_size;
                 }
@@ -326,63 +792,313 @@ impl OrderBook {
                     self.best_quote.best_ask_price = price_level.get_price();
                     self.best_quote.best_ask_size = price_level_size;
                 }
-                if order_price < self.best_quote.best_ask_price {
+                if order_price <= self.best_quote.best_ask_price {
                     self.best_quote.best_ask_price = price_level.get_price();
                     self.best_quote.best_ask_size = price_level_size;
                 }
             }
         }
+        true
     }
 
-    /// Cancel order by specified id
-    pub fn cancel_order(&mut self, request: &CancelOrderRequest) -> Result<bool, String> {
-        let best_bid_price = self.get_best_bid_price();
-        let best_ask_price = self.get_best_ask_price();
-        match self.price_by_id.get(&request.id) {
-            Some(price) => {
-                let index = self.get_price_level_index(*price);
-                let price_level = self.price_levels.get_mut(index as usize).unwrap();
-                price_level.remove_order(request.id);
-                // update the best bid/ask if the cancelled order is at the top of the book
-                if *price == best_bid_price && price_level.get_size() == 0 {
+    /// Remove a resting order from the book
+    ///
+    /// Returns the removed order, or `None` if the order is not on the book
+    fn remove_order(&mut self, id: u64) -> Option<Order> {
+        match self.orders.get_by_id(id).copied() {
+            Some(order) => {
+                let price = order.get_price();
+                let index = self.get_price_level_index(price) as usize;
+                self.price_ladder
+                    .remove_order(&mut self.orders, order.get_side(), index, id);
+                // update the best bid/ask if the removed order is at the top of the book
+                if price == self.best_quote.best_bid_price {
                     self.update_best_bid();
-                } else if *price == best_ask_price && price_level.get_size() == 0 {
+                }
+                if price == self.best_quote.best_ask_price {
                     self.update_best_ask();
                 }
-                Ok(true)
+                Some(order)
             }
-            None => Err("order not found".to_string()),
+            None => None,
+        }
+    }
+
+    /// Cancel order by specified id
+    ///
+    /// Returns the cancel, with the size taken off the book and its sequence number
+    pub fn cancel_order(
+        &mut self,
+        request: &CancelOrderRequest,
+    ) -> Result<OrderEvent, ExchangeError> {
+        let size = match self.remove_order(request.id) {
+            Some(order) => order.get_remaining() + order.get_hidden(),
+            None => match self.stop_book.remove_order(request.id) {
+                Some(stop_order) => stop_order.size,
+                None => return Err(ExchangeError::OrderNotFound),
+            },
+        };
+        let event = self
+            .sequence
+            .order_event(OrderEventKind::Cancelled, request.id, size);
+        self.publish_quote();
+        Ok(event)
+    }
+
+    /// Advance the order book clock to the specified time in nanoseconds
+    ///
+    /// Any good-till-date or day orders that have reached their expire time are
+    /// removed from the book or the stop book, and their expiries are returned in expiry
+    /// order
+    pub fn advance_clock(&mut self, time: u64) -> Result<Vec<OrderEvent>, ExchangeError> {
+        if time < self.time {
+            return Err(ExchangeError::ClockMovedBackwards);
+        }
+        self.time = time;
+        let mut expired = Vec::new();
+        while let Some(&(expire_time, id)) = self.expiries.first() {
+            if expire_time > time {
+                break;
+            }
+            self.expiries.pop_first();
+            // orders that have already been filled or cancelled are skipped
+            let size = match self.remove_order(id) {
+                Some(order) => order.get_remaining() + order.get_hidden(),
+                None => match self.stop_book.remove_order(id) {
+                    Some(stop_order) => stop_order.size,
+                    None => continue,
+                },
+            };
+            expired.push(self.sequence.order_event(OrderEventKind::Expired, id, size));
         }
+        self.publish_quote();
+        Ok(expired)
     }
 
     /// Handle a new order submission request
-    pub fn submit_order(&mut self, request: &mut SubmitOrderRequest) -> Result<u64, String> {
-        if request.order_type == OrderType::Limit && request.price < self.market.get_min_price() {
-            Err("order price is below min for market".to_string())
-        } else if request.order_type == OrderType::Limit
-            && request.price >= self.market.get_max_price()
+    ///
+    /// Returns the id assigned to the order, the sequence number of its accept and any
+    /// trades it generated
+    pub fn submit_order(
+        &mut self,
+        request: &mut SubmitOrderRequest,
+    ) -> Result<SubmitOrderResponse, ExchangeError> {
+        let is_limit = matches!(request.order_type, OrderType::Limit | OrderType::StopLimit);
+        let is_stop = matches!(
+            request.order_type,
+            OrderType::StopMarket | OrderType::StopLimit
+        );
+        if is_limit && request.price < self.market.get_min_price() {
+            Err(ExchangeError::PriceBelowMin)
+        } else if is_limit && request.price >= self.market.get_max_price() {
+            Err(ExchangeError::PriceAboveMax)
+        } else if is_limit && !self.is_valid_tick(request.price) {
+            Err(ExchangeError::PriceNotOnTick)
+        } else if !request
+            .display_size
+            .is_multiple_of(self.market.get_lot_size())
+        {
+            Err(ExchangeError::DisplaySizeNotOnLot)
+        } else if let Err(err) = self.validate_size(request.size, is_limit.then_some(request.price))
         {
-            Err("order price is above max for market".to_string())
+            Err(err)
+        } else if is_stop && request.trigger_price < self.market.get_min_price() {
+            Err(ExchangeError::TriggerPriceBelowMin)
+        } else if is_stop && request.trigger_price >= self.market.get_max_price() {
+            Err(ExchangeError::TriggerPriceAboveMax)
+        } else if request.time_in_force == TimeInForce::GoodTillDate
+            && request.expire_time <= self.time
+        {
+            Err(ExchangeError::ExpireTimeInPast)
+        } else if !is_stop
+            && request.time_in_force == TimeInForce::FillOrKill
+            && self.get_available_size(request) < request.size
+        {
+            Err(ExchangeError::FillOrKillNotFilled)
         } else {
+            self.apply_post_only(request)?;
+            if Self::is_resting_order(request) {
+                self.orders.reserve()?;
+            }
             self.last_order_id += 1;
-            match request.order_type {
-                OrderType::Limit => {
-                    if (request.price >= self.best_quote.best_ask_price
-                        && request.side == Side::Buy)
-                        || (request.price <= self.best_quote.best_bid_price
-                            && request.side == Side::Sell)
-                    {
-                        self.handle_crossing_order(request);
-                    } else {
-                        self.handle_passive_order(request);
-                    }
+            // the accept takes its sequence number before the fills of the order
+            let sequence = self.sequence.next();
+            let mut response = if is_stop {
+                let mut stop_order = request.clone();
+                // the expiry of a stop order runs from its submission, not its activation,
+                // so a day order is given the end of the current day as its expire time
+                if let Some(expire_time) = self.get_expire_time(request) {
+                    stop_order.time_in_force = TimeInForce::GoodTillDate;
+                    stop_order.expire_time = expire_time;
+                    self.expiries.insert((expire_time, self.last_order_id));
+                }
+                self.stop_book.add_order(self.last_order_id, stop_order);
+                SubmitOrderResponse {
+                    id: self.last_order_id,
+                    ..Default::default()
+                }
+            } else {
+                self.execute_order(request, self.last_order_id)
+            };
+            response.sequence = sequence;
+            self.trigger_stop_orders(&mut response);
+            self.publish_quote();
+            Ok(response)
+        }
+    }
+
+    /// Activate stop orders triggered by the last trade price
+    ///
+    /// Triggered orders are submitted to the book in the order they were received. Their
+    /// trades can move the last trade price and trigger further stop orders, so this repeats
+    /// until no more stop orders are triggered. All trades and order events are added to
+    /// the response of the request that started the cascade
+    fn trigger_stop_orders(&mut self, response: &mut SubmitOrderResponse) {
+        if self.last_trade_price == 0 || self.stop_book.is_empty() {
+            return;
+        }
+        loop {
+            let triggered = self.stop_book.take_triggered_orders(self.last_trade_price);
+            if triggered.is_empty() {
+                break;
+            }
+            for (id, mut request) in triggered {
+                response.triggered_ids.push(id);
+                let event = self
+                    .sequence
+                    .order_event(OrderEventKind::Triggered, id, request.size);
+                response.order_events.push(event);
+                request.order_type = match request.order_type {
+                    OrderType::StopLimit => OrderType::Limit,
+                    _ => OrderType::Market,
+                };
+                // a fill or kill stop order is cancelled if it cannot be fully filled
+                if request.time_in_force == TimeInForce::FillOrKill
+                    && self.get_available_size(&request) < request.size
+                {
+                    let event =
+                        self.sequence
+                            .order_event(OrderEventKind::Cancelled, id, request.size);
+                    response.order_events.push(event);
+                    continue;
                 }
-                OrderType::Market => {
-                    request.price = 0;
-                    self.handle_crossing_order(request);
+                let activated = self.execute_order(&mut request, id);
+                response.trades.extend(activated.trades);
+                response.order_events.extend(activated.order_events);
+            }
+        }
+    }
+
+    /// Match an order with the specified id against the book, resting any unfilled size
+    /// when its type and time-in-force allow
+    fn execute_order(&mut self, request: &mut SubmitOrderRequest, id: u64) -> SubmitOrderResponse {
+        let mut response = SubmitOrderResponse {
+            id,
+            ..Default::default()
+        };
+        match request.order_type {
+            OrderType::Limit => {
+                if self.is_crossing_order(request) {
+                    self.handle_crossing_order(request, &mut response);
+                } else if Self::is_resting_order(request) {
+                    self.rest_order(request, id, &mut response);
+                } else {
+                    self.cancel_remainder(request, id, &mut response);
                 }
             }
-            Ok(self.last_order_id)
+            OrderType::Market => {
+                request.price = 0;
+                self.handle_crossing_order(request, &mut response);
+            }
+            OrderType::StopMarket | OrderType::StopLimit => {
+                unreachable!("stop orders must be activated before execution")
+            }
         }
+        response
+    }
+
+    /// Amend the price and/or size of a resting order
+    ///
+    /// Reducing the size of an order at the same price keeps its queue priority. Any other
+    /// change cancels the order and replaces it at the back of the queue of its new price
+    /// level, keeping the same id and time in force. The replacement may cross the book, in which case the
+    /// generated trades are returned, unless the order is post-only: its replacement is
+    /// then rejected or slid like a new post-only order
+    pub fn amend_order(
+        &mut self,
+        request: &AmendOrderRequest,
+    ) -> Result<SubmitOrderResponse, ExchangeError> {
+        if request.size == 0 {
+            return Err(ExchangeError::AmendSizeZero);
+        }
+        if request.price < self.market.get_min_price() {
+            return Err(ExchangeError::PriceBelowMin);
+        }
+        if request.price >= self.market.get_max_price() {
+            return Err(ExchangeError::PriceAboveMax);
+        }
+        if !self.is_valid_tick(request.price) {
+            return Err(ExchangeError::PriceNotOnTick);
+        }
+        if !request.size.is_multiple_of(self.market.get_lot_size()) {
+            return Err(ExchangeError::SizeNotOnLot);
+        }
+        let slot = match self.orders.get_slot(request.id) {
+            Some(slot) => slot,
+            None => return Err(ExchangeError::OrderNotFound),
+        };
+        let order = *self.orders.get(slot);
+        let price = order.get_price();
+        let total_size = order.get_remaining() + order.get_hidden();
+        if request.price == price && request.size <= total_size {
+            // reduce size in place so the order keeps its queue priority, taking size from
+            // the hidden reserve of iceberg orders before their displayed size
+            let remaining = order.get_remaining().min(request.size);
+            let index = self.get_price_level_index(price);
+            let price_level = self
+                .price_ladder
+                .get_level_mut(order.get_side(), index as usize)
+                .unwrap();
+            price_level.resize_order(&mut self.orders, slot, remaining, request.size - remaining);
+            if price == self.best_quote.best_bid_price {
+                self.update_best_bid();
+            }
+            if price == self.best_quote.best_ask_price {
+                self.update_best_ask();
+            }
+            let sequence = self.sequence.next();
+            self.publish_quote();
+            return Ok(SubmitOrderResponse {
+                id: request.id,
+                sequence,
+                ..Default::default()
+            });
+        }
+        // the replacement is a new order, so it must meet the size rules of the market
+        self.validate_size(request.size, Some(request.price))?;
+        let mut replacement = SubmitOrderRequest {
+            symbol: request.symbol.clone(),
+            price: request.price,
+            size: request.size,
+            side: order.get_side(),
+            order_type: OrderType::Limit,
+            account_id: order.get_account_id(),
+            self_trade_prevention: request.self_trade_prevention,
+            display_size: order.get_display_size(),
+            post_only: order.get_post_only(),
+            time_in_force: order.get_time_in_force(),
+            expire_time: order.get_expire_time(),
+            ..Default::default()
+        };
+        // a post-only order must not take liquidity by being amended through the spread
+        self.apply_post_only(&mut replacement)?;
+        self.orders.reserve()?;
+        self.remove_order(request.id);
+        let sequence = self.sequence.next();
+        let mut response = self.execute_order(&mut replacement, request.id);
+        response.sequence = sequence;
+        self.trigger_stop_orders(&mut response);
+        self.publish_quote();
+        Ok(response)
     }
 }
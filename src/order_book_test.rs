@@ -1,10 +1,14 @@
 #[cfg(test)]
 mod tests {
 
-    use crate::market::Market;
-    use crate::order::{OrderType, Side};
-    use crate::order_book::OrderBook;
-    use crate::request::{CancelOrderRequest, SubmitOrderRequest};
+    use crate::error::ExchangeError;
+    use crate::market::{LadderType, Market};
+    use crate::order::{OrderType, PostOnly, SelfTradePrevention, Side, TimeInForce};
+    use crate::order_book::{DepthLevel, OrderBook};
+    use crate::published_depth::DEFAULT_DEPTH_LEVELS;
+    use crate::request::{AmendOrderRequest, CancelOrderRequest, SubmitOrderRequest};
+    use crate::response::{OrderEvent, OrderEventKind, SubmitOrderResponse};
+    use crate::trade::Trade;
 
     #[test]
     fn test_passive_non_crossing_orders() {
@@ -16,48 +20,54 @@ mod tests {
             size: 1,
             order_type: OrderType::Limit,
             side: Side::Buy,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 101,
             size: 1,
             order_type: OrderType::Limit,
             side: Side::Buy,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 100,
             size: 1,
             order_type: OrderType::Limit,
             side: Side::Buy,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 104,
             size: 1,
             order_type: OrderType::Limit,
             side: Side::Sell,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 102,
             size: 1,
             order_type: OrderType::Limit,
             side: Side::Sell,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 103,
             size: 1,
             order_type: OrderType::Limit,
             side: Side::Sell,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let bids = order_book.get_side_of_book(Side::Buy);
         let asks = order_book.get_side_of_book(Side::Sell);
         assert_eq!(order_book.get_best_bid_price(), 101);
@@ -66,14 +76,14 @@ mod tests {
         assert_eq!(order_book.get_best_ask_size(), 1);
         assert_eq!(bids.len(), 3);
         assert_eq!(asks.len(), 3);
-        assert_eq!(bids.get(0).unwrap().get_price(), 101);
-        assert_eq!(bids.get(0).unwrap().get_size(), 1);
+        assert_eq!(bids.first().unwrap().get_price(), 101);
+        assert_eq!(bids.first().unwrap().get_size(), 1);
         assert_eq!(bids.get(1).unwrap().get_price(), 100);
         assert_eq!(bids.get(1).unwrap().get_size(), 1);
         assert_eq!(bids.get(2).unwrap().get_price(), 99);
         assert_eq!(bids.get(2).unwrap().get_size(), 1);
-        assert_eq!(asks.get(0).unwrap().get_price(), 102);
-        assert_eq!(asks.get(0).unwrap().get_size(), 1);
+        assert_eq!(asks.first().unwrap().get_price(), 102);
+        assert_eq!(asks.first().unwrap().get_size(), 1);
         assert_eq!(asks.get(1).unwrap().get_price(), 103);
         assert_eq!(asks.get(1).unwrap().get_size(), 1);
         assert_eq!(asks.get(2).unwrap().get_price(), 104);
@@ -91,16 +101,18 @@ mod tests {
             size: 2,
             order_type: OrderType::Limit,
             side: Side::Sell,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 99,
             size: 1,
             order_type: OrderType::Limit,
             side: Side::Buy,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let bids = order_book.get_side_of_book(Side::Buy);
         let asks = order_book.get_side_of_book(Side::Sell);
         assert_eq!(order_book.get_best_bid_price(), min_price);
@@ -109,8 +121,8 @@ mod tests {
         assert_eq!(order_book.get_best_ask_size(), 1);
         assert_eq!(bids.len(), 0);
         assert_eq!(asks.len(), 1);
-        assert_eq!(asks.get(0).unwrap().get_price(), 99);
-        assert_eq!(asks.get(0).unwrap().get_size(), 1);
+        assert_eq!(asks.first().unwrap().get_price(), 99);
+        assert_eq!(asks.first().unwrap().get_size(), 1);
     }
 
     #[test]
@@ -124,16 +136,18 @@ mod tests {
             size: 2,
             order_type: OrderType::Limit,
             side: Side::Buy,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 99,
             size: 1,
             order_type: OrderType::Limit,
             side: Side::Sell,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let bids = order_book.get_side_of_book(Side::Buy);
         let asks = order_book.get_side_of_book(Side::Sell);
         assert_eq!(order_book.get_best_bid_price(), 99);
@@ -142,8 +156,8 @@ mod tests {
         assert_eq!(order_book.get_best_ask_size(), 0);
         assert_eq!(bids.len(), 1);
         assert_eq!(asks.len(), 0);
-        assert_eq!(bids.get(0).unwrap().get_price(), 99);
-        assert_eq!(bids.get(0).unwrap().get_size(), 1);
+        assert_eq!(bids.first().unwrap().get_price(), 99);
+        assert_eq!(bids.first().unwrap().get_size(), 1);
     }
 
     #[test]
@@ -156,32 +170,36 @@ mod tests {
             size: 2,
             order_type: OrderType::Limit,
             side: Side::Sell,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 100,
             size: 2,
             order_type: OrderType::Limit,
             side: Side::Sell,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 105,
             size: 10,
             order_type: OrderType::Limit,
             side: Side::Sell,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 100,
             size: 10,
             order_type: OrderType::Limit,
             side: Side::Buy,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let bids = order_book.get_side_of_book(Side::Buy);
         let asks = order_book.get_side_of_book(Side::Sell);
         assert_eq!(order_book.get_best_bid_price(), 100);
@@ -190,10 +208,10 @@ mod tests {
         assert_eq!(order_book.get_best_ask_size(), 10);
         assert_eq!(bids.len(), 1);
         assert_eq!(asks.len(), 1);
-        assert_eq!(bids.get(0).unwrap().get_price(), 100);
-        assert_eq!(bids.get(0).unwrap().get_size(), 6);
-        assert_eq!(asks.get(0).unwrap().get_price(), 105);
-        assert_eq!(asks.get(0).unwrap().get_size(), 10);
+        assert_eq!(bids.first().unwrap().get_price(), 100);
+        assert_eq!(bids.first().unwrap().get_size(), 6);
+        assert_eq!(asks.first().unwrap().get_price(), 105);
+        assert_eq!(asks.first().unwrap().get_size(), 10);
     }
 
     #[test]
@@ -206,32 +224,36 @@ mod tests {
             size: 2,
             order_type: OrderType::Limit,
             side: Side::Buy,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 103,
             size: 2,
             order_type: OrderType::Limit,
             side: Side::Buy,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 95,
             size: 10,
             order_type: OrderType::Limit,
             side: Side::Buy,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 103,
             size: 10,
             order_type: OrderType::Limit,
             side: Side::Sell,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let bids = order_book.get_side_of_book(Side::Buy);
         let asks = order_book.get_side_of_book(Side::Sell);
         assert_eq!(order_book.get_best_bid_price(), 95);
@@ -240,10 +262,10 @@ mod tests {
         assert_eq!(order_book.get_best_ask_size(), 6);
         assert_eq!(bids.len(), 1);
         assert_eq!(asks.len(), 1);
-        assert_eq!(bids.get(0).unwrap().get_price(), 95);
-        assert_eq!(bids.get(0).unwrap().get_size(), 10);
-        assert_eq!(asks.get(0).unwrap().get_price(), 103);
-        assert_eq!(asks.get(0).unwrap().get_size(), 6);
+        assert_eq!(bids.first().unwrap().get_price(), 95);
+        assert_eq!(bids.first().unwrap().get_size(), 10);
+        assert_eq!(asks.first().unwrap().get_price(), 103);
+        assert_eq!(asks.first().unwrap().get_size(), 6);
     }
 
     #[test]
@@ -257,24 +279,27 @@ mod tests {
             size: 1,
             order_type: OrderType::Limit,
             side: Side::Sell,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 99,
             size: 1,
             order_type: OrderType::Limit,
             side: Side::Sell,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 99,
             size: 1,
             order_type: OrderType::Limit,
             side: Side::Buy,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let bids = order_book.get_side_of_book(Side::Buy);
         let asks = order_book.get_side_of_book(Side::Sell);
         assert_eq!(order_book.get_best_bid_price(), min_price);
@@ -283,8 +308,8 @@ mod tests {
         assert_eq!(order_book.get_best_ask_size(), 1);
         assert_eq!(bids.len(), 0);
         assert_eq!(asks.len(), 1);
-        assert_eq!(asks.get(0).unwrap().get_price(), 102);
-        assert_eq!(asks.get(0).unwrap().get_size(), 1);
+        assert_eq!(asks.first().unwrap().get_price(), 102);
+        assert_eq!(asks.first().unwrap().get_size(), 1);
     }
 
     #[test]
@@ -298,24 +323,27 @@ mod tests {
             size: 1,
             order_type: OrderType::Limit,
             side: Side::Buy,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 95,
             size: 1,
             order_type: OrderType::Limit,
             side: Side::Buy,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 99,
             size: 1,
             order_type: OrderType::Limit,
             side: Side::Sell,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let bids = order_book.get_side_of_book(Side::Buy);
         let asks = order_book.get_side_of_book(Side::Sell);
         assert_eq!(order_book.get_best_bid_price(), 95);
@@ -324,8 +352,8 @@ mod tests {
         assert_eq!(order_book.get_best_ask_size(), 0);
         assert_eq!(bids.len(), 1);
         assert_eq!(asks.len(), 0);
-        assert_eq!(bids.get(0).unwrap().get_price(), 95);
-        assert_eq!(bids.get(0).unwrap().get_size(), 1);
+        assert_eq!(bids.first().unwrap().get_price(), 95);
+        assert_eq!(bids.first().unwrap().get_size(), 1);
     }
 
     #[test]
@@ -338,24 +366,27 @@ mod tests {
             size: 10,
             order_type: OrderType::Limit,
             side: Side::Buy,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 100,
             size: 10,
             order_type: OrderType::Limit,
             side: Side::Sell,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 100,
             size: 1,
             order_type: OrderType::Market,
             side: Side::Buy,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let bids = order_book.get_side_of_book(Side::Buy);
         let asks = order_book.get_side_of_book(Side::Sell);
         assert_eq!(order_book.get_best_bid_price(), 99);
@@ -364,10 +395,10 @@ mod tests {
         assert_eq!(order_book.get_best_ask_size(), 9);
         assert_eq!(bids.len(), 1);
         assert_eq!(asks.len(), 1);
-        assert_eq!(bids.get(0).unwrap().get_price(), 99);
-        assert_eq!(bids.get(0).unwrap().get_size(), 10);
-        assert_eq!(asks.get(0).unwrap().get_price(), 100);
-        assert_eq!(asks.get(0).unwrap().get_size(), 9);
+        assert_eq!(bids.first().unwrap().get_price(), 99);
+        assert_eq!(bids.first().unwrap().get_size(), 10);
+        assert_eq!(asks.first().unwrap().get_price(), 100);
+        assert_eq!(asks.first().unwrap().get_size(), 9);
     }
 
     #[test]
@@ -380,24 +411,27 @@ mod tests {
             size: 10,
             order_type: OrderType::Limit,
             side: Side::Buy,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 100,
             size: 10,
             order_type: OrderType::Limit,
             side: Side::Sell,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = order_book.submit_order(&mut SubmitOrderRequest {
             symbol: market.get_symbol().to_string(),
             price: 100,
             size: 1,
             order_type: OrderType::Market,
             side: Side::Sell,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let bids = order_book.get_side_of_book(Side::Buy);
         let asks = order_book.get_side_of_book(Side::Sell);
         assert_eq!(order_book.get_best_bid_price(), 99);
@@ -406,10 +440,10 @@ mod tests {
         assert_eq!(order_book.get_best_ask_size(), 10);
         assert_eq!(bids.len(), 1);
         assert_eq!(asks.len(), 1);
-        assert_eq!(bids.get(0).unwrap().get_price(), 99);
-        assert_eq!(bids.get(0).unwrap().get_size(), 9);
-        assert_eq!(asks.get(0).unwrap().get_price(), 100);
-        assert_eq!(asks.get(0).unwrap().get_size(), 10);
+        assert_eq!(bids.first().unwrap().get_price(), 99);
+        assert_eq!(bids.first().unwrap().get_size(), 9);
+        assert_eq!(asks.first().unwrap().get_price(), 100);
+        assert_eq!(asks.first().unwrap().get_size(), 10);
     }
 
     #[test]
@@ -422,9 +456,10 @@ mod tests {
             size: 10,
             order_type: OrderType::Limit,
             side: Side::Buy,
+            ..Default::default()
         });
-        assert_eq!(res.is_err(), true);
-        assert_eq!(res.unwrap_err(), "order price is below min for market");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err(), ExchangeError::PriceBelowMin);
     }
 
     #[test]
@@ -437,9 +472,10 @@ mod tests {
             size: 10,
             order_type: OrderType::Limit,
             side: Side::Buy,
+            ..Default::default()
         });
-        assert_eq!(res.is_err(), true);
-        assert_eq!(res.unwrap_err(), "order price is above max for market");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err(), ExchangeError::PriceAboveMax);
     }
 
     #[test]
@@ -452,16 +488,17 @@ mod tests {
             size: 100,
             order_type: OrderType::Limit,
             side: Side::Buy,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let bids = order_book.get_side_of_book(Side::Buy);
         assert_eq!(bids.len(), 1);
-        let order_id = res.unwrap();
-        let res = order_book.cancel_order(&mut CancelOrderRequest {
+        let order_id = res.unwrap().id;
+        let res = order_book.cancel_order(&CancelOrderRequest {
             symbol: market.get_symbol().to_string(),
             id: order_id,
         });
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let bids = order_book.get_side_of_book(Side::Buy);
         assert_eq!(bids.len(), 0);
     }
@@ -476,13 +513,1685 @@ mod tests {
             size: 100,
             order_type: OrderType::Limit,
             side: Side::Buy,
+            ..Default::default()
         });
-        assert_eq!(res.is_ok(), true);
-        let res = order_book.cancel_order(&mut CancelOrderRequest {
+        assert!(res.is_ok());
+        let res = order_book.cancel_order(&CancelOrderRequest {
             symbol: market.get_symbol().to_string(),
             id: 100,
         });
-        assert_eq!(res.is_err(), true);
-        assert_eq!(res.unwrap_err(), "order not found");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err(), ExchangeError::OrderNotFound);
+    }
+
+    #[test]
+    fn test_passive_order_has_no_trades() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 100,
+            size: 5,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let res = res.unwrap();
+        assert_eq!(res.id, 1);
+        assert_eq!(res.trades.len(), 0);
+    }
+
+    #[test]
+    fn test_crossing_order_reports_trades() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        for (price, size) in [(100, 2), (100, 3), (101, 4)] {
+            let res = order_book.submit_order(&mut SubmitOrderRequest {
+                symbol: market.get_symbol().to_string(),
+                price,
+                size,
+                order_type: OrderType::Limit,
+                side: Side::Sell,
+                ..Default::default()
+            });
+            assert!(res.is_ok());
+        }
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 101,
+            size: 7,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let res = res.unwrap();
+        assert_eq!(res.id, 4);
+        assert_eq!(
+            res.trades,
+            vec![
+                Trade::new(1, 5, 4, 1, 100, 2, Side::Buy),
+                Trade::new(2, 6, 4, 2, 100, 3, Side::Buy),
+                Trade::new(3, 7, 4, 3, 101, 2, Side::Buy),
+            ]
+        );
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 0,
+            size: 1,
+            order_type: OrderType::Market,
+            side: Side::Buy,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        assert_eq!(
+            res.unwrap().trades,
+            vec![Trade::new(4, 9, 5, 3, 101, 1, Side::Buy)]
+        );
+    }
+
+    #[test]
+    fn test_cancel_order_fails_when_fully_filled() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 100,
+            size: 1,
+            order_type: OrderType::Limit,
+            side: Side::Sell,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let order_id = res.unwrap().id;
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 100,
+            size: 1,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap().trades.len(), 1);
+        let res = order_book.cancel_order(&CancelOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            id: order_id,
+        });
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err(), ExchangeError::OrderNotFound);
+    }
+
+    #[test]
+    fn test_immediate_or_cancel_remainder_is_cancelled() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 100,
+            size: 2,
+            order_type: OrderType::Limit,
+            side: Side::Sell,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 101,
+            size: 5,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            time_in_force: TimeInForce::ImmediateOrCancel,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let res = res.unwrap();
+        assert_eq!(res.trades.len(), 1);
+        // the unfilled size is reported as cancelled, so the order is known to be done
+        assert_eq!(
+            res.order_events,
+            vec![order_event(OrderEventKind::Cancelled, 2, 4, 3)]
+        );
+        let bids = order_book.get_side_of_book(Side::Buy);
+        let asks = order_book.get_side_of_book(Side::Sell);
+        assert_eq!(bids.len(), 0);
+        assert_eq!(asks.len(), 0);
+        assert_eq!(order_book.get_best_bid_size(), 0);
+        assert_eq!(order_book.get_best_ask_size(), 0);
+        // an immediate-or-cancel order that does not cross never rests
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 99,
+            size: 5,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            time_in_force: TimeInForce::ImmediateOrCancel,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let res = res.unwrap();
+        assert_eq!(res.sequence, 5);
+        assert_eq!(
+            res.order_events,
+            vec![order_event(OrderEventKind::Cancelled, 3, 6, 5)]
+        );
+        assert_eq!(order_book.get_side_of_book(Side::Buy).len(), 0);
+    }
+
+    #[test]
+    fn test_market_order_remainder_is_cancelled() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 100,
+            size: 1,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 0,
+            size: 3,
+            order_type: OrderType::Market,
+            side: Side::Sell,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap().trades.len(), 1);
+        assert_eq!(order_book.get_side_of_book(Side::Buy).len(), 0);
+        assert_eq!(order_book.get_side_of_book(Side::Sell).len(), 0);
+    }
+
+    #[test]
+    fn test_fill_or_kill() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        for price in [100, 101] {
+            let res = order_book.submit_order(&mut SubmitOrderRequest {
+                symbol: market.get_symbol().to_string(),
+                price,
+                size: 2,
+                order_type: OrderType::Limit,
+                side: Side::Sell,
+                ..Default::default()
+            });
+            assert!(res.is_ok());
+        }
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 100,
+            size: 3,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            time_in_force: TimeInForce::FillOrKill,
+            ..Default::default()
+        });
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err(), ExchangeError::FillOrKillNotFilled);
+        assert_eq!(order_book.get_best_ask_size(), 2);
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 101,
+            size: 3,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            time_in_force: TimeInForce::FillOrKill,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap().trades.len(), 2);
+        assert_eq!(order_book.get_best_ask_price(), 101);
+        assert_eq!(order_book.get_best_ask_size(), 1);
+        assert_eq!(order_book.get_side_of_book(Side::Buy).len(), 0);
+    }
+
+    #[test]
+    fn test_good_till_date_orders_expire() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let max_price = market.get_max_price();
+        let mut order_book = OrderBook::new(market.clone());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 100,
+            size: 1,
+            order_type: OrderType::Limit,
+            side: Side::Sell,
+            time_in_force: TimeInForce::GoodTillDate,
+            expire_time: 1_000,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let expiring_id = res.unwrap().id;
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 101,
+            size: 1,
+            order_type: OrderType::Limit,
+            side: Side::Sell,
+            time_in_force: TimeInForce::GoodTillDate,
+            expire_time: 2_000,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        assert_eq!(order_book.advance_clock(999), Ok(vec![]));
+        assert_eq!(order_book.get_best_ask_price(), 100);
+        assert_eq!(
+            order_book.advance_clock(1_000),
+            Ok(vec![order_event(
+                OrderEventKind::Expired,
+                expiring_id,
+                3,
+                1
+            )])
+        );
+        assert_eq!(order_book.get_best_ask_price(), 101);
+        assert_eq!(order_book.get_best_ask_size(), 1);
+        assert_eq!(order_book.get_side_of_book(Side::Sell).len(), 1);
+        assert_eq!(order_book.advance_clock(5_000).unwrap().len(), 1);
+        assert_eq!(order_book.get_best_ask_price(), max_price);
+        assert_eq!(order_book.get_best_ask_size(), 0);
+        assert_eq!(order_book.get_side_of_book(Side::Sell).len(), 0);
+    }
+
+    #[test]
+    fn test_good_till_date_order_fails_with_expire_time_in_past() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        assert!(order_book.advance_clock(1_000).is_ok());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 100,
+            size: 1,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            time_in_force: TimeInForce::GoodTillDate,
+            expire_time: 1_000,
+            ..Default::default()
+        });
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err(), ExchangeError::ExpireTimeInPast);
+    }
+
+    #[test]
+    fn test_day_orders_expire_at_end_of_day() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let min_price = market.get_min_price();
+        let mut order_book = OrderBook::new(market.clone());
+        let day = 86_400_000_000_000;
+        assert!(order_book.advance_clock(day + 5).is_ok());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 100,
+            size: 1,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            time_in_force: TimeInForce::Day,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let order_id = res.unwrap().id;
+        assert_eq!(order_book.advance_clock(2 * day - 1), Ok(vec![]));
+        assert_eq!(order_book.get_best_bid_price(), 100);
+        assert_eq!(
+            order_book.advance_clock(2 * day),
+            Ok(vec![order_event(OrderEventKind::Expired, order_id, 2, 1)])
+        );
+        assert_eq!(order_book.get_best_bid_price(), min_price);
+        assert_eq!(order_book.get_best_bid_size(), 0);
+    }
+
+    #[test]
+    fn test_advance_clock_fails_when_moving_backwards() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market);
+        assert!(order_book.advance_clock(1_000).is_ok());
+        let res = order_book.advance_clock(999);
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err(), ExchangeError::ClockMovedBackwards);
+        assert_eq!(order_book.get_time(), 1_000);
+    }
+
+    #[test]
+    fn test_post_only_reject() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 100,
+            size: 1,
+            order_type: OrderType::Limit,
+            side: Side::Sell,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 100,
+            size: 1,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            post_only: PostOnly::Reject,
+            ..Default::default()
+        });
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err(), ExchangeError::PostOnlyWouldTake);
+        assert_eq!(order_book.get_best_ask_size(), 1);
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 99,
+            size: 1,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            post_only: PostOnly::Reject,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        assert_eq!(order_book.get_best_bid_price(), 99);
+        assert_eq!(order_book.get_best_bid_size(), 1);
+    }
+
+    #[test]
+    fn test_post_only_slide() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 100,
+            size: 1,
+            order_type: OrderType::Limit,
+            side: Side::Sell,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 90,
+            size: 1,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 105,
+            size: 2,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            post_only: PostOnly::Slide,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap().trades.len(), 0);
+        assert_eq!(order_book.get_best_bid_price(), 99);
+        assert_eq!(order_book.get_best_bid_size(), 2);
+        assert_eq!(order_book.get_best_ask_price(), 100);
+        assert_eq!(order_book.get_best_ask_size(), 1);
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 50,
+            size: 3,
+            order_type: OrderType::Limit,
+            side: Side::Sell,
+            post_only: PostOnly::Slide,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap().trades.len(), 0);
+        assert_eq!(order_book.get_best_ask_price(), 100);
+        assert_eq!(order_book.get_best_ask_size(), 4);
+        assert_eq!(order_book.get_best_bid_price(), 99);
+    }
+
+    #[test]
+    fn test_post_only_fails_with_market_order() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 0,
+            size: 1,
+            order_type: OrderType::Market,
+            side: Side::Buy,
+            post_only: PostOnly::Reject,
+            ..Default::default()
+        });
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err(), ExchangeError::PostOnlyNotLimit);
+    }
+
+    fn order_event(kind: OrderEventKind, id: u64, sequence: u64, size: u64) -> OrderEvent {
+        OrderEvent {
+            id,
+            sequence,
+            kind,
+            size,
+        }
+    }
+
+    fn submit_self_trade(
+        stp: SelfTradePrevention,
+        aggressor_size: u64,
+    ) -> (OrderBook, SubmitOrderResponse) {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        for (size, account_id) in [(2, 1), (3, 2), (4, 1)] {
+            let res = order_book.submit_order(&mut SubmitOrderRequest {
+                symbol: market.get_symbol().to_string(),
+                price: 100,
+                size,
+                order_type: OrderType::Limit,
+                side: Side::Sell,
+                account_id,
+                ..Default::default()
+            });
+            assert!(res.is_ok());
+        }
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 100,
+            size: aggressor_size,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            account_id: 1,
+            self_trade_prevention: stp,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        (order_book, res.unwrap())
+    }
+
+    #[test]
+    fn test_self_trade_prevention_disabled() {
+        let (order_book, res) = submit_self_trade(SelfTradePrevention::Disabled, 3);
+        assert_eq!(res.trades.len(), 2);
+        assert_eq!(res.trades[0].get_passive_id(), 1);
+        assert_eq!(res.order_events.len(), 0);
+        assert_eq!(order_book.get_best_ask_size(), 6);
+    }
+
+    #[test]
+    fn test_self_trade_prevention_cancel_newest() {
+        let (order_book, res) = submit_self_trade(SelfTradePrevention::CancelNewest, 3);
+        assert_eq!(res.trades.len(), 0);
+        // only the incoming order is cancelled
+        assert_eq!(
+            res.order_events,
+            vec![order_event(OrderEventKind::Cancelled, 4, 5, 3)]
+        );
+        assert_eq!(order_book.get_best_ask_size(), 9);
+        assert_eq!(order_book.get_best_bid_size(), 0);
+    }
+
+    #[test]
+    fn test_self_trade_prevention_cancel_oldest() {
+        let (order_book, res) = submit_self_trade(SelfTradePrevention::CancelOldest, 5);
+        assert_eq!(res.trades, vec![Trade::new(1, 6, 4, 2, 100, 3, Side::Buy)]);
+        assert_eq!(
+            res.order_events,
+            vec![
+                order_event(OrderEventKind::Cancelled, 1, 5, 2),
+                order_event(OrderEventKind::Cancelled, 3, 7, 4),
+            ]
+        );
+        assert_eq!(order_book.get_best_ask_size(), 0);
+        assert_eq!(order_book.get_best_bid_price(), 100);
+        assert_eq!(order_book.get_best_bid_size(), 2);
+    }
+
+    #[test]
+    fn test_self_trade_prevention_cancel_both() {
+        let (order_book, res) = submit_self_trade(SelfTradePrevention::CancelBoth, 5);
+        assert_eq!(res.trades.len(), 0);
+        assert_eq!(
+            res.order_events,
+            vec![
+                order_event(OrderEventKind::Cancelled, 1, 5, 2),
+                order_event(OrderEventKind::Cancelled, 4, 6, 5),
+            ]
+        );
+        assert_eq!(order_book.get_best_ask_size(), 7);
+        assert_eq!(order_book.get_best_bid_size(), 0);
+    }
+
+    #[test]
+    fn test_self_trade_prevention_decrement_and_cancel() {
+        let (order_book, res) = submit_self_trade(SelfTradePrevention::DecrementAndCancel, 6);
+        assert_eq!(res.trades, vec![Trade::new(1, 7, 4, 2, 100, 3, Side::Buy)]);
+        // both orders are decremented by the smaller size, and cancelled when that
+        // leaves them with no size
+        assert_eq!(
+            res.order_events,
+            vec![
+                order_event(OrderEventKind::Cancelled, 1, 5, 2),
+                order_event(OrderEventKind::Reduced, 4, 6, 2),
+                order_event(OrderEventKind::Reduced, 3, 8, 1),
+                order_event(OrderEventKind::Cancelled, 4, 9, 1),
+            ]
+        );
+        assert_eq!(order_book.get_best_ask_price(), 100);
+        assert_eq!(order_book.get_best_ask_size(), 3);
+        assert_eq!(order_book.get_best_bid_size(), 0);
+    }
+
+    fn submit_resting_orders(order_book: &mut OrderBook, side: Side, orders: &[(u64, u64)]) {
+        for (price, size) in orders {
+            let res = order_book.submit_order(&mut SubmitOrderRequest {
+                symbol: "BTCUSD".to_string(),
+                price: *price,
+                size: *size,
+                order_type: OrderType::Limit,
+                side,
+                ..Default::default()
+            });
+            assert!(res.is_ok());
+        }
+    }
+
+    #[test]
+    fn test_amend_order_reduce_size_keeps_priority() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        submit_resting_orders(&mut order_book, Side::Buy, &[(100, 5), (100, 5)]);
+        let res = order_book.amend_order(&AmendOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            id: 1,
+            price: 100,
+            size: 2,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        assert_eq!(order_book.get_best_bid_size(), 7);
+        let orders = order_book.get_orders(Side::Buy);
+        assert_eq!(orders.first().unwrap().get_id(), 1);
+        assert_eq!(orders.first().unwrap().get_remaining(), 2);
+    }
+
+    #[test]
+    fn test_amend_order_increase_size_loses_priority() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        submit_resting_orders(&mut order_book, Side::Buy, &[(100, 5), (100, 5)]);
+        let res = order_book.amend_order(&AmendOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            id: 1,
+            price: 100,
+            size: 8,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        assert_eq!(order_book.get_best_bid_size(), 13);
+        let orders = order_book.get_orders(Side::Buy);
+        assert_eq!(orders.first().unwrap().get_id(), 2);
+        assert_eq!(orders.get(1).unwrap().get_id(), 1);
+        assert_eq!(orders.get(1).unwrap().get_remaining(), 8);
+    }
+
+    #[test]
+    fn test_amend_order_change_price() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let min_price = market.get_min_price();
+        let mut order_book = OrderBook::new(market.clone());
+        submit_resting_orders(&mut order_book, Side::Buy, &[(100, 5)]);
+        let res = order_book.amend_order(&AmendOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            id: 1,
+            price: 98,
+            size: 5,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        assert_eq!(order_book.get_best_bid_price(), 98);
+        assert_eq!(order_book.get_best_bid_size(), 5);
+        assert_eq!(order_book.get_side_of_book(Side::Buy).len(), 1);
+        let res = order_book.cancel_order(&CancelOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            id: 1,
+        });
+        assert!(res.is_ok());
+        assert_eq!(order_book.get_best_bid_price(), min_price);
+        assert_eq!(order_book.get_best_bid_size(), 0);
+    }
+
+    #[test]
+    fn test_amend_order_crosses_book() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        submit_resting_orders(&mut order_book, Side::Sell, &[(101, 2)]);
+        submit_resting_orders(&mut order_book, Side::Buy, &[(99, 5)]);
+        let res = order_book.amend_order(&AmendOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            id: 2,
+            price: 101,
+            size: 5,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let res = res.unwrap();
+        assert_eq!(res.id, 2);
+        assert_eq!(res.trades, vec![Trade::new(1, 4, 2, 1, 101, 2, Side::Buy)]);
+        assert_eq!(order_book.get_best_bid_price(), 101);
+        assert_eq!(order_book.get_best_bid_size(), 3);
+        assert_eq!(order_book.get_best_ask_size(), 0);
+    }
+
+    #[test]
+    fn test_amend_order_fails_with_invalid_id() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        let res = order_book.amend_order(&AmendOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            id: 1,
+            price: 100,
+            size: 1,
+            ..Default::default()
+        });
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err(), ExchangeError::OrderNotFound);
+        submit_resting_orders(&mut order_book, Side::Buy, &[(100, 5)]);
+        let res = order_book.amend_order(&AmendOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            id: 1,
+            price: 100,
+            size: 0,
+            ..Default::default()
+        });
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err(), ExchangeError::AmendSizeZero);
+    }
+
+    #[test]
+    fn test_stop_market_order_cascade() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        submit_resting_orders(&mut order_book, Side::Sell, &[(101, 1), (102, 1), (103, 5)]);
+        for trigger_price in [102, 101] {
+            let res = order_book.submit_order(&mut SubmitOrderRequest {
+                symbol: market.get_symbol().to_string(),
+                size: 1,
+                order_type: OrderType::StopMarket,
+                side: Side::Buy,
+                trigger_price,
+                ..Default::default()
+            });
+            assert!(res.is_ok());
+            assert_eq!(res.unwrap().trades.len(), 0);
+        }
+        assert_eq!(order_book.get_stop_order_count(), 2);
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 101,
+            size: 1,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let res = res.unwrap();
+        assert_eq!(res.triggered_ids, vec![5, 4]);
+        assert_eq!(
+            res.trades,
+            vec![
+                Trade::new(1, 7, 6, 1, 101, 1, Side::Buy),
+                Trade::new(2, 9, 5, 2, 102, 1, Side::Buy),
+                Trade::new(3, 11, 4, 3, 103, 1, Side::Buy),
+            ]
+        );
+        // each trigger is numbered before the trades of the activated order
+        assert_eq!(
+            res.order_events,
+            vec![
+                order_event(OrderEventKind::Triggered, 5, 8, 1),
+                order_event(OrderEventKind::Triggered, 4, 10, 1),
+            ]
+        );
+        assert_eq!(order_book.get_stop_order_count(), 0);
+        assert_eq!(order_book.get_last_trade_price(), 103);
+        assert_eq!(order_book.get_best_ask_price(), 103);
+        assert_eq!(order_book.get_best_ask_size(), 4);
+    }
+
+    #[test]
+    fn test_stop_limit_order_rests_when_triggered() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        submit_resting_orders(&mut order_book, Side::Buy, &[(100, 1), (98, 5)]);
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 99,
+            size: 2,
+            order_type: OrderType::StopLimit,
+            side: Side::Sell,
+            trigger_price: 100,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let stop_id = res.unwrap().id;
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 0,
+            size: 1,
+            order_type: OrderType::Market,
+            side: Side::Sell,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let res = res.unwrap();
+        assert_eq!(res.triggered_ids, vec![stop_id]);
+        assert_eq!(res.trades.len(), 1);
+        assert_eq!(order_book.get_best_ask_price(), 99);
+        assert_eq!(order_book.get_best_ask_size(), 2);
+        assert_eq!(order_book.get_best_bid_price(), 98);
+    }
+
+    #[test]
+    fn test_cancel_stop_order() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            size: 1,
+            order_type: OrderType::StopMarket,
+            side: Side::Buy,
+            trigger_price: 100,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let res = order_book.cancel_order(&CancelOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            id: res.unwrap().id,
+        });
+        assert!(res.is_ok());
+        assert_eq!(order_book.get_stop_order_count(), 0);
+    }
+
+    #[test]
+    fn test_stop_order_fails_with_trigger_price_above_max() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            size: 1,
+            order_type: OrderType::StopMarket,
+            side: Side::Buy,
+            trigger_price: 10_000,
+            ..Default::default()
+        });
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err(), ExchangeError::TriggerPriceAboveMax);
+    }
+
+    #[test]
+    fn test_iceberg_order_displays_peak_and_replenishes() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 100,
+            size: 10,
+            order_type: OrderType::Limit,
+            side: Side::Sell,
+            display_size: 3,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        submit_resting_orders(&mut order_book, Side::Sell, &[(100, 2)]);
+        assert_eq!(order_book.get_best_ask_size(), 5);
+        let asks = order_book.get_side_of_book(Side::Sell);
+        assert_eq!(asks.first().unwrap().get_size(), 5);
+        assert_eq!(asks.first().unwrap().get_total_size(), 12);
+        // consume the first peak, which replenishes behind the second order
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 100,
+            size: 4,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        assert_eq!(
+            res.unwrap().trades,
+            vec![
+                Trade::new(1, 4, 3, 1, 100, 3, Side::Buy),
+                Trade::new(2, 5, 3, 2, 100, 1, Side::Buy),
+            ]
+        );
+        assert_eq!(order_book.get_best_ask_size(), 4);
+        let orders = order_book.get_orders(Side::Sell);
+        assert_eq!(orders.first().unwrap().get_id(), 2);
+        assert_eq!(orders.get(1).unwrap().get_id(), 1);
+        assert_eq!(orders.get(1).unwrap().get_remaining(), 3);
+        assert_eq!(orders.get(1).unwrap().get_hidden(), 4);
+    }
+
+    #[test]
+    fn test_iceberg_order_fully_filled_across_peaks() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let max_price = market.get_max_price();
+        let mut order_book = OrderBook::new(market.clone());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 100,
+            size: 5,
+            order_type: OrderType::Limit,
+            side: Side::Sell,
+            display_size: 2,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 100,
+            size: 5,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            time_in_force: TimeInForce::FillOrKill,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let trades = res.unwrap().trades;
+        assert_eq!(trades.len(), 3);
+        assert_eq!(trades.iter().map(|trade| trade.get_size()).sum::<u64>(), 5);
+        assert_eq!(order_book.get_best_ask_price(), max_price);
+        assert_eq!(order_book.get_best_ask_size(), 0);
+        assert_eq!(order_book.get_side_of_book(Side::Buy).len(), 0);
+    }
+
+    #[test]
+    fn test_many_orders_at_one_price_level() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        for _ in 0..500 {
+            submit_resting_orders(&mut order_book, Side::Buy, &[(100, 1)]);
+        }
+        assert_eq!(order_book.get_best_bid_price(), 100);
+        assert_eq!(order_book.get_best_bid_size(), 500);
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 0,
+            size: 300,
+            order_type: OrderType::Market,
+            side: Side::Sell,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap().trades.len(), 300);
+        assert_eq!(order_book.get_best_bid_size(), 200);
+    }
+
+    #[test]
+    fn test_best_quote_skips_empty_levels_after_sweep() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        submit_resting_orders(&mut order_book, Side::Buy, &[(2, 1), (9_000, 1)]);
+        submit_resting_orders(&mut order_book, Side::Sell, &[(9_001, 1), (9_999, 1)]);
+        let res = order_book.cancel_order(&CancelOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            id: 2,
+        });
+        assert!(res.is_ok());
+        assert_eq!(order_book.get_best_bid_price(), 2);
+        assert_eq!(order_book.get_best_bid_size(), 1);
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 0,
+            size: 1,
+            order_type: OrderType::Market,
+            side: Side::Buy,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        assert_eq!(order_book.get_best_ask_price(), 9_999);
+        assert_eq!(order_book.get_best_ask_size(), 1);
+        let asks = order_book.get_asks();
+        assert_eq!(asks.len(), 1);
+        assert_eq!(asks.first().unwrap().get_price(), 9_999);
+        let bids = order_book.get_bids();
+        assert_eq!(bids.len(), 1);
+        assert_eq!(bids.first().unwrap().get_price(), 2);
+    }
+
+    #[test]
+    fn test_sparse_ladder_with_huge_price_range() {
+        let mut market = Market::new("BTCUSD", 1_000_000_001, 1);
+        market.set_ladder_type(LadderType::Sparse);
+        let mut order_book = OrderBook::new(market.clone());
+        assert_eq!(order_book.get_price_ladder().get_allocated_levels(), 0);
+        submit_resting_orders(&mut order_book, Side::Buy, &[(10, 1), (500_000_000, 2)]);
+        submit_resting_orders(
+            &mut order_book,
+            Side::Sell,
+            &[(500_000_001, 3), (999_999_999, 4)],
+        );
+        assert_eq!(order_book.get_price_ladder().get_allocated_levels(), 4);
+        assert_eq!(order_book.get_best_bid_price(), 500_000_000);
+        assert_eq!(order_book.get_best_bid_size(), 2);
+        assert_eq!(order_book.get_best_ask_price(), 500_000_001);
+        assert_eq!(order_book.get_best_ask_size(), 3);
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 0,
+            size: 5,
+            order_type: OrderType::Market,
+            side: Side::Buy,
+            ..Default::default()
+        });
+        assert_eq!(res.unwrap().trades.len(), 2);
+        assert_eq!(order_book.get_best_ask_price(), 999_999_999);
+        assert_eq!(order_book.get_best_ask_size(), 2);
+        assert_eq!(order_book.get_price_ladder().get_allocated_levels(), 3);
+        let res = order_book.cancel_order(&CancelOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            id: 2,
+        });
+        assert!(res.is_ok());
+        assert_eq!(order_book.get_best_bid_price(), 10);
+        assert_eq!(order_book.get_best_bid_size(), 1);
+        assert_eq!(order_book.get_price_ladder().get_allocated_levels(), 2);
+    }
+
+    #[test]
+    fn test_sparse_ladder_matches_dense_ladder() {
+        let dense_market = Market::new("BTCUSD", 1_001, 1);
+        let mut sparse_market = dense_market.clone();
+        sparse_market.set_ladder_type(LadderType::Sparse);
+        let mut dense_book = OrderBook::new(dense_market);
+        let mut sparse_book = OrderBook::new(sparse_market);
+        let mut seed: u64 = 7;
+        for _ in 0..5_000 {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            let value = seed >> 33;
+            let side = if value.is_multiple_of(2) {
+                Side::Buy
+            } else {
+                Side::Sell
+            };
+            let (order_type, price) = if value.is_multiple_of(10) {
+                (OrderType::Market, 0)
+            } else {
+                (OrderType::Limit, 400 + (value >> 8) % 200)
+            };
+            let mut request = SubmitOrderRequest {
+                symbol: "BTCUSD".to_string(),
+                price,
+                size: 1 + (value >> 4) % 5,
+                order_type,
+                side,
+                display_size: (value >> 16) % 3,
+                ..Default::default()
+            };
+            if value.is_multiple_of(7) {
+                let id = (value >> 20) % 5_000;
+                let cancel = CancelOrderRequest {
+                    symbol: "BTCUSD".to_string(),
+                    id,
+                };
+                assert_eq!(
+                    dense_book.cancel_order(&cancel).is_ok(),
+                    sparse_book.cancel_order(&cancel).is_ok()
+                );
+            } else {
+                assert_eq!(
+                    dense_book.submit_order(&mut request.clone()),
+                    sparse_book.submit_order(&mut request)
+                );
+            }
+            assert_eq!(
+                dense_book.get_best_bid_price(),
+                sparse_book.get_best_bid_price()
+            );
+            assert_eq!(
+                dense_book.get_best_bid_size(),
+                sparse_book.get_best_bid_size()
+            );
+            assert_eq!(
+                dense_book.get_best_ask_price(),
+                sparse_book.get_best_ask_price()
+            );
+            assert_eq!(
+                dense_book.get_best_ask_size(),
+                sparse_book.get_best_ask_size()
+            );
+        }
+        for side in [Side::Buy, Side::Sell] {
+            let dense_ids: Vec<u64> = dense_book
+                .get_orders(side)
+                .iter()
+                .map(|o| o.get_id())
+                .collect();
+            let sparse_ids: Vec<u64> = sparse_book
+                .get_orders(side)
+                .iter()
+                .map(|o| o.get_id())
+                .collect();
+            assert_eq!(dense_ids, sparse_ids);
+        }
+    }
+
+    fn new_market_with_rules() -> Market {
+        let mut market = Market::new("BTCUSD", 20_000, 10_000);
+        market.set_tick_size(5);
+        market.set_lot_size(10);
+        market.set_min_quantity(20);
+        market.set_max_quantity(1_000);
+        market.set_min_notional(300_000);
+        market
+    }
+
+    fn submit_limit_order(
+        order_book: &mut OrderBook,
+        side: Side,
+        price: u64,
+        size: u64,
+    ) -> Result<SubmitOrderResponse, ExchangeError> {
+        order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: "BTCUSD".to_string(),
+            price,
+            size,
+            order_type: OrderType::Limit,
+            side,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_submit_order_rejects_price_off_tick() {
+        let mut order_book = OrderBook::new(new_market_with_rules());
+        assert_eq!(
+            submit_limit_order(&mut order_book, Side::Buy, 15_002, 20),
+            Err(ExchangeError::PriceNotOnTick)
+        );
+        assert!(submit_limit_order(&mut order_book, Side::Buy, 15_005, 20).is_ok());
+    }
+
+    #[test]
+    fn test_submit_order_rejects_size_off_lot() {
+        let mut order_book = OrderBook::new(new_market_with_rules());
+        assert_eq!(
+            submit_limit_order(&mut order_book, Side::Buy, 15_000, 25),
+            Err(ExchangeError::SizeNotOnLot)
+        );
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: "BTCUSD".to_string(),
+            price: 15_000,
+            size: 100,
+            display_size: 15,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            ..Default::default()
+        });
+        assert_eq!(res, Err(ExchangeError::DisplaySizeNotOnLot));
+    }
+
+    #[test]
+    fn test_submit_order_rejects_size_outside_quantity_limits() {
+        let mut order_book = OrderBook::new(new_market_with_rules());
+        assert_eq!(
+            submit_limit_order(&mut order_book, Side::Buy, 15_000, 10),
+            Err(ExchangeError::SizeBelowMin)
+        );
+        assert_eq!(
+            submit_limit_order(&mut order_book, Side::Buy, 15_000, 1_010),
+            Err(ExchangeError::SizeAboveMax)
+        );
+        assert!(submit_limit_order(&mut order_book, Side::Buy, 15_000, 1_000).is_ok());
+    }
+
+    #[test]
+    fn test_submit_order_rejects_notional_below_min() {
+        let mut order_book = OrderBook::new(new_market_with_rules());
+        assert_eq!(
+            submit_limit_order(&mut order_book, Side::Buy, 14_995, 20),
+            Err(ExchangeError::NotionalBelowMin)
+        );
+        assert!(submit_limit_order(&mut order_book, Side::Buy, 15_000, 20).is_ok());
+        // market orders have no limit price, so only their size is checked
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: "BTCUSD".to_string(),
+            price: 0,
+            size: 20,
+            order_type: OrderType::Market,
+            side: Side::Sell,
+            ..Default::default()
+        });
+        assert_eq!(res.unwrap().trades.len(), 1);
+    }
+
+    #[test]
+    fn test_price_levels_are_indexed_in_ticks() {
+        let market = new_market_with_rules();
+        let order_book = OrderBook::new(market);
+        assert_eq!(order_book.get_price_ladder().get_allocated_levels(), 2_000);
+    }
+
+    #[test]
+    fn test_matching_across_tick_sized_levels() {
+        let mut order_book = OrderBook::new(new_market_with_rules());
+        assert!(submit_limit_order(&mut order_book, Side::Sell, 15_005, 20).is_ok());
+        assert!(submit_limit_order(&mut order_book, Side::Sell, 15_010, 20).is_ok());
+        assert!(submit_limit_order(&mut order_book, Side::Buy, 14_995, 30).is_ok());
+        assert_eq!(order_book.get_best_ask_price(), 15_005);
+        assert_eq!(order_book.get_best_bid_price(), 14_995);
+        let res = submit_limit_order(&mut order_book, Side::Buy, 15_010, 30).unwrap();
+        assert_eq!(res.trades.len(), 2);
+        assert_eq!(res.trades.first().unwrap().get_price(), 15_005);
+        assert_eq!(res.trades.get(1).unwrap().get_price(), 15_010);
+        assert_eq!(order_book.get_best_ask_price(), 15_010);
+        assert_eq!(order_book.get_best_ask_size(), 10);
+        let asks = order_book.get_asks();
+        assert_eq!(asks.len(), 1);
+        assert_eq!(asks.first().unwrap().get_price(), 15_010);
+    }
+
+    #[test]
+    fn test_post_only_slides_by_tick_size() {
+        let mut order_book = OrderBook::new(new_market_with_rules());
+        assert!(submit_limit_order(&mut order_book, Side::Sell, 15_005, 20).is_ok());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: "BTCUSD".to_string(),
+            price: 15_010,
+            size: 20,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            post_only: PostOnly::Slide,
+            ..Default::default()
+        });
+        assert!(res.unwrap().trades.is_empty());
+        assert_eq!(order_book.get_best_bid_price(), 15_000);
+    }
+
+    #[test]
+    fn test_amend_order_checks_market_rules() {
+        let mut order_book = OrderBook::new(new_market_with_rules());
+        let id = submit_limit_order(&mut order_book, Side::Buy, 15_000, 50)
+            .unwrap()
+            .id;
+        let mut request = AmendOrderRequest {
+            symbol: "BTCUSD".to_string(),
+            id,
+            price: 15_001,
+            size: 50,
+            ..Default::default()
+        };
+        assert_eq!(
+            order_book.amend_order(&request),
+            Err(ExchangeError::PriceNotOnTick)
+        );
+        request.price = 15_000;
+        request.size = 45;
+        assert_eq!(
+            order_book.amend_order(&request),
+            Err(ExchangeError::SizeNotOnLot)
+        );
+        request.size = 2_000;
+        assert_eq!(
+            order_book.amend_order(&request),
+            Err(ExchangeError::SizeAboveMax)
+        );
+        request.size = 10;
+        assert!(order_book.amend_order(&request).is_ok());
+        assert_eq!(order_book.get_best_bid_size(), 10);
+    }
+
+    #[test]
+    fn test_published_quote_follows_requests() {
+        let market = Market::new("BTCUSD", 1_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        let published = order_book.get_published_quote().clone();
+        assert_eq!(published.read().sequence, 0);
+        order_book
+            .submit_order(&mut SubmitOrderRequest {
+                symbol: market.get_symbol().to_string(),
+                price: 100,
+                size: 2,
+                order_type: OrderType::Limit,
+                side: Side::Buy,
+                ..Default::default()
+            })
+            .unwrap();
+        let snapshot = published.read();
+        assert_eq!(snapshot.sequence, 1);
+        assert_eq!(snapshot.best_quote, order_book.get_best_quote());
+        // a rejected request leaves the quote unchanged
+        assert!(
+            order_book
+                .cancel_order(&CancelOrderRequest {
+                    symbol: market.get_symbol().to_string(),
+                    id: 99,
+                })
+                .is_err()
+        );
+        assert_eq!(published.read().sequence, 1);
+        order_book
+            .cancel_order(&CancelOrderRequest {
+                symbol: market.get_symbol().to_string(),
+                id: 1,
+            })
+            .unwrap();
+        let snapshot = published.read();
+        assert_eq!(snapshot.sequence, 2);
+        assert_eq!(snapshot.best_quote.best_bid_size, 0);
+    }
+
+    #[test]
+    fn test_event_sequence_numbers_are_gap_free() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        let mut sequences = Vec::new();
+        for (price, size) in [(101, 2), (102, 3)] {
+            let res = order_book
+                .submit_order(&mut SubmitOrderRequest {
+                    symbol: market.get_symbol().to_string(),
+                    price,
+                    size,
+                    order_type: OrderType::Limit,
+                    side: Side::Sell,
+                    ..Default::default()
+                })
+                .unwrap();
+            sequences.push(res.sequence);
+        }
+        let res = order_book
+            .submit_order(&mut SubmitOrderRequest {
+                symbol: market.get_symbol().to_string(),
+                price: 103,
+                size: 1,
+                order_type: OrderType::Limit,
+                side: Side::Sell,
+                time_in_force: TimeInForce::GoodTillDate,
+                expire_time: 1_000,
+                ..Default::default()
+            })
+            .unwrap();
+        sequences.push(res.sequence);
+        // the accept of an aggressive order comes before its fills
+        let res = order_book
+            .submit_order(&mut SubmitOrderRequest {
+                symbol: market.get_symbol().to_string(),
+                price: 101,
+                size: 1,
+                order_type: OrderType::Limit,
+                side: Side::Buy,
+                ..Default::default()
+            })
+            .unwrap();
+        sequences.push(res.sequence);
+        sequences.extend(res.trades.iter().map(|trade| trade.get_sequence()));
+        // rejected requests do not take a sequence number
+        assert!(
+            order_book
+                .cancel_order(&CancelOrderRequest {
+                    symbol: market.get_symbol().to_string(),
+                    id: 99,
+                })
+                .is_err()
+        );
+        let res = order_book
+            .amend_order(&AmendOrderRequest {
+                symbol: market.get_symbol().to_string(),
+                id: 2,
+                price: 102,
+                size: 2,
+                ..Default::default()
+            })
+            .unwrap();
+        sequences.push(res.sequence);
+        let cancelled = order_book
+            .cancel_order(&CancelOrderRequest {
+                symbol: market.get_symbol().to_string(),
+                id: 1,
+            })
+            .unwrap();
+        assert_eq!(cancelled.id, 1);
+        sequences.push(cancelled.sequence);
+        let expired = order_book.advance_clock(1_000).unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].id, 3);
+        sequences.push(expired[0].sequence);
+
+        assert_eq!(sequences, (1..=8).collect::<Vec<u64>>());
+        assert_eq!(order_book.get_sequence(), 8);
+        assert_eq!(order_book.get_published_quote().read().market_sequence, 8);
+    }
+
+    #[test]
+    fn test_get_depth_is_aggregated_and_bounded() {
+        for ladder_type in [LadderType::Dense, LadderType::Sparse] {
+            let mut market = Market::new("BTCUSD", 10_000, 1);
+            market.set_ladder_type(ladder_type);
+            let mut order_book = OrderBook::new(market.clone());
+            submit_resting_orders(
+                &mut order_book,
+                Side::Buy,
+                &[(100, 2), (97, 4), (100, 3), (99, 1)],
+            );
+            submit_resting_orders(&mut order_book, Side::Sell, &[(103, 1)]);
+            let res = order_book.submit_order(&mut SubmitOrderRequest {
+                symbol: market.get_symbol().to_string(),
+                price: 101,
+                size: 6,
+                display_size: 2,
+                order_type: OrderType::Limit,
+                side: Side::Sell,
+                ..Default::default()
+            });
+            assert!(res.is_ok());
+
+            let depth = order_book.get_depth(2);
+            assert_eq!(depth.sequence, 6);
+            assert_eq!(
+                depth.bids,
+                vec![
+                    DepthLevel {
+                        price: 100,
+                        size: 5,
+                        order_count: 2,
+                    },
+                    DepthLevel {
+                        price: 99,
+                        size: 1,
+                        order_count: 1,
+                    },
+                ]
+            );
+            // only the displayed size of the iceberg order is shown
+            assert_eq!(
+                depth.asks,
+                vec![
+                    DepthLevel {
+                        price: 101,
+                        size: 2,
+                        order_count: 1,
+                    },
+                    DepthLevel {
+                        price: 103,
+                        size: 1,
+                        order_count: 1,
+                    },
+                ]
+            );
+            assert_eq!(order_book.get_depth(10).bids.len(), 3);
+            // the same levels are published to readers on other threads
+            assert_eq!(
+                order_book.get_published_depth().read(DEFAULT_DEPTH_LEVELS),
+                order_book.get_depth(DEFAULT_DEPTH_LEVELS)
+            );
+            let depth = order_book.get_depth(0);
+            assert!(depth.bids.is_empty() && depth.asks.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_amend_post_only_order_does_not_take_liquidity() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        submit_resting_orders(&mut order_book, Side::Sell, &[(105, 2)]);
+        for (price, post_only) in [(100, PostOnly::Reject), (99, PostOnly::Slide)] {
+            let res = order_book.submit_order(&mut SubmitOrderRequest {
+                symbol: market.get_symbol().to_string(),
+                price,
+                size: 2,
+                order_type: OrderType::Limit,
+                side: Side::Buy,
+                post_only,
+                ..Default::default()
+            });
+            assert!(res.is_ok());
+        }
+        let res = order_book.amend_order(&AmendOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            id: 2,
+            price: 105,
+            size: 2,
+            ..Default::default()
+        });
+        assert_eq!(res.unwrap_err(), ExchangeError::PostOnlyWouldTake);
+        assert_eq!(order_book.get_order(2).unwrap().get_price(), 100);
+        // the sliding order is repriced one tick behind the best ask
+        let res = order_book
+            .amend_order(&AmendOrderRequest {
+                symbol: market.get_symbol().to_string(),
+                id: 3,
+                price: 106,
+                size: 2,
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(res.trades.is_empty());
+        assert_eq!(order_book.get_order(3).unwrap().get_price(), 104);
+        assert_eq!(order_book.get_best_bid_price(), 104);
+        assert_eq!(order_book.get_best_ask_size(), 2);
+    }
+
+    #[test]
+    fn test_fill_or_kill_stop_order_killed_on_activation_is_cancelled() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        submit_resting_orders(&mut order_book, Side::Sell, &[(101, 1), (102, 1)]);
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            size: 5,
+            order_type: OrderType::StopMarket,
+            trigger_price: 101,
+            side: Side::Buy,
+            time_in_force: TimeInForce::FillOrKill,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let res = order_book
+            .submit_order(&mut SubmitOrderRequest {
+                symbol: market.get_symbol().to_string(),
+                price: 101,
+                size: 1,
+                order_type: OrderType::Limit,
+                side: Side::Buy,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(res.triggered_ids, vec![3]);
+        assert_eq!(
+            res.order_events,
+            vec![
+                order_event(OrderEventKind::Triggered, 3, 6, 5),
+                order_event(OrderEventKind::Cancelled, 3, 7, 5),
+            ]
+        );
+        assert_eq!(order_book.get_stop_order_count(), 0);
+        assert_eq!(order_book.get_best_ask_size(), 1);
+        assert_eq!(order_book.get_sequence(), 7);
+    }
+
+    #[test]
+    fn test_stop_orders_expire_before_activation() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        let day = 86_400_000_000_000;
+        assert!(order_book.advance_clock(day + 5).is_ok());
+        for (time_in_force, expire_time) in
+            [(TimeInForce::GoodTillDate, day + 10), (TimeInForce::Day, 0)]
+        {
+            let res = order_book.submit_order(&mut SubmitOrderRequest {
+                symbol: market.get_symbol().to_string(),
+                price: 105,
+                size: 2,
+                order_type: OrderType::StopLimit,
+                trigger_price: 103,
+                side: Side::Buy,
+                time_in_force,
+                expire_time,
+                ..Default::default()
+            });
+            assert!(res.is_ok());
+        }
+        assert_eq!(order_book.get_stop_order_count(), 2);
+        assert_eq!(
+            order_book.advance_clock(day + 10),
+            Ok(vec![order_event(OrderEventKind::Expired, 1, 3, 2)])
+        );
+        assert_eq!(order_book.get_stop_order_count(), 1);
+        // the day stop order expires at the end of the day it was submitted on
+        assert_eq!(order_book.advance_clock(2 * day - 1), Ok(vec![]));
+        assert_eq!(
+            order_book.advance_clock(2 * day),
+            Ok(vec![order_event(OrderEventKind::Expired, 2, 4, 2)])
+        );
+        assert_eq!(order_book.get_stop_order_count(), 0);
+    }
+
+    #[test]
+    fn test_zero_size_order_is_rejected() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        submit_resting_orders(&mut order_book, Side::Sell, &[(101, 5)]);
+        let quote = order_book.get_best_quote();
+        let depth = order_book.get_depth(10);
+        for (order_type, price) in [
+            (OrderType::Limit, 100),
+            (OrderType::Limit, 101),
+            (OrderType::Market, 0),
+            (OrderType::StopMarket, 0),
+        ] {
+            let res = order_book.submit_order(&mut SubmitOrderRequest {
+                symbol: market.get_symbol().to_string(),
+                price,
+                size: 0,
+                order_type,
+                side: Side::Buy,
+                trigger_price: 102,
+                ..Default::default()
+            });
+            assert_eq!(res, Err(ExchangeError::SizeZero));
+        }
+        let res = order_book.amend_order(&AmendOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            id: 1,
+            price: 101,
+            size: 0,
+            ..Default::default()
+        });
+        assert_eq!(res, Err(ExchangeError::AmendSizeZero));
+        assert_eq!(order_book.get_best_quote(), quote);
+        assert_eq!(order_book.get_depth(10), depth);
+        assert_eq!(order_book.get_stop_order_count(), 0);
+        assert_eq!(order_book.get_sequence(), 1);
+    }
+
+    #[test]
+    fn test_unfilled_remainders_are_cancelled() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        let order = |order_type, time_in_force, price, size| SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price,
+            size,
+            order_type,
+            side: Side::Buy,
+            time_in_force,
+            trigger_price: 102,
+            ..Default::default()
+        };
+
+        // immediate-or-cancel remainder
+        submit_resting_orders(&mut order_book, Side::Sell, &[(101, 2)]);
+        let res = order_book
+            .submit_order(&mut order(
+                OrderType::Limit,
+                TimeInForce::ImmediateOrCancel,
+                102,
+                5,
+            ))
+            .unwrap();
+        assert_eq!(res.trades.len(), 1);
+        assert_eq!(
+            res.order_events,
+            vec![order_event(OrderEventKind::Cancelled, 2, 4, 3)]
+        );
+
+        // market order remainder
+        submit_resting_orders(&mut order_book, Side::Sell, &[(101, 1)]);
+        let res = order_book
+            .submit_order(&mut order(
+                OrderType::Market,
+                TimeInForce::GoodTillCancel,
+                0,
+                4,
+            ))
+            .unwrap();
+        assert_eq!(res.trades.len(), 1);
+        assert_eq!(
+            res.order_events,
+            vec![order_event(OrderEventKind::Cancelled, 4, 8, 3)]
+        );
+
+        // triggered stop market order remainder
+        submit_resting_orders(&mut order_book, Side::Sell, &[(102, 1)]);
+        order_book
+            .submit_order(&mut order(
+                OrderType::StopMarket,
+                TimeInForce::GoodTillCancel,
+                0,
+                3,
+            ))
+            .unwrap();
+        let res = order_book
+            .submit_order(&mut order(
+                OrderType::Limit,
+                TimeInForce::GoodTillCancel,
+                102,
+                1,
+            ))
+            .unwrap();
+        assert_eq!(
+            res.order_events,
+            vec![
+                order_event(OrderEventKind::Triggered, 6, 13, 3),
+                order_event(OrderEventKind::Cancelled, 6, 14, 3),
+            ]
+        );
+        assert_eq!(order_book.get_sequence(), 14);
+        assert_eq!(order_book.get_best_bid_size(), 0);
+    }
+
+    #[test]
+    fn test_amend_order_keeps_time_in_force() {
+        let market = Market::new("BTCUSD", 10_000, 1);
+        let mut order_book = OrderBook::new(market.clone());
+        let res = order_book.submit_order(&mut SubmitOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            price: 100,
+            size: 5,
+            order_type: OrderType::Limit,
+            side: Side::Buy,
+            time_in_force: TimeInForce::GoodTillDate,
+            expire_time: 500,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        // a new price replaces the order, which must keep its time in force
+        let res = order_book.amend_order(&AmendOrderRequest {
+            symbol: market.get_symbol().to_string(),
+            id: 1,
+            price: 98,
+            size: 5,
+            ..Default::default()
+        });
+        assert!(res.is_ok());
+        let snapshot = order_book.snapshot();
+        let order = snapshot.bids.first().unwrap().orders.first().unwrap();
+        assert_eq!(order.get_price(), 98);
+        assert_eq!(order.get_time_in_force(), TimeInForce::GoodTillDate);
+        assert_eq!(order.get_expire_time(), 500);
+        let mut order_book = OrderBook::restore(snapshot).unwrap();
+        assert_eq!(order_book.advance_clock(499), Ok(vec![]));
+        assert_eq!(
+            order_book.advance_clock(500),
+            Ok(vec![order_event(OrderEventKind::Expired, 1, 3, 5)])
+        );
+        assert_eq!(order_book.get_best_bid_size(), 0);
     }
 }
@@ -17,7 +17,7 @@ mod tests {
     fn test_encode_and_decode() {
         let order = Order::new(100, 1, Side::Buy, OrderStatus::Open, OrderType::Limit);
         let bytes = bincode::encode_to_vec(order, bincode::config::standard());
-        assert_eq!(bytes.is_ok(), true);
+        assert!(bytes.is_ok());
         let (decoded_order, _): (Order, usize) =
             bincode::decode_from_slice(&bytes.unwrap(), bincode::config::standard()).unwrap();
         assert_eq!(decoded_order, order);
@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+
+use crate::error::ExchangeError;
+use crate::level_index::LevelIndex;
+use crate::market::LadderType;
+use crate::order::{Order, Side};
+use crate::order_arena::OrderArena;
+use crate::price_level::PriceLevel;
+
+/// Storage for the price levels of an order book, addressed by level index
+///
+/// The dense ladder allocates every level of the market up front and finds occupied
+/// levels through an occupancy bitmap per side. The sparse ladder only holds occupied
+/// levels, in an ordered map per side, so markets with huge price ranges only pay for
+/// the levels that have orders
+#[derive(Debug)]
+pub enum PriceLadder {
+    Dense {
+        levels: Vec<PriceLevel>,
+        bids: LevelIndex,
+        asks: LevelIndex,
+    },
+    Sparse {
+        bids: BTreeMap<usize, PriceLevel>,
+        asks: BTreeMap<usize, PriceLevel>,
+    },
+}
+
+impl PriceLadder {
+    /// Creates a ladder of given type with one level per tick from min price up to, but
+    /// not including, max price
+    pub fn new(ladder_type: LadderType, min_price: u64, max_price: u64, tick_size: u64) -> Self {
+        let total_levels = (max_price - min_price).div_ceil(tick_size) as usize;
+        match ladder_type {
+            LadderType::Dense => {
+                let mut levels = Vec::with_capacity(total_levels);
+                for i in 0..total_levels {
+                    levels.push(PriceLevel::new(min_price + i as u64 * tick_size));
+                }
+                PriceLadder::Dense {
+                    levels,
+                    bids: LevelIndex::new(total_levels),
+                    asks: LevelIndex::new(total_levels),
+                }
+            }
+            LadderType::Sparse => PriceLadder::Sparse {
+                bids: BTreeMap::new(),
+                asks: BTreeMap::new(),
+            },
+        }
+    }
+
+    /// Returns the type of this ladder
+    pub fn get_ladder_type(&self) -> LadderType {
+        match self {
+            PriceLadder::Dense { .. } => LadderType::Dense,
+            PriceLadder::Sparse { .. } => LadderType::Sparse,
+        }
+    }
+
+    /// Returns the number of price levels currently allocated
+    pub fn get_allocated_levels(&self) -> usize {
+        match self {
+            PriceLadder::Dense { levels, .. } => levels.len(),
+            PriceLadder::Sparse { bids, asks } => bids.len() + asks.len(),
+        }
+    }
+
+    /// Returns the occupied price level with the given index on specified side of the book
+    pub fn get_level(&self, side: Side, index: usize) -> Option<&PriceLevel> {
+        match self {
+            PriceLadder::Dense { levels, bids, asks } => {
+                let occupied = match side {
+                    Side::Buy => bids,
+                    Side::Sell => asks,
+                };
+                if occupied.contains(index) {
+                    levels.get(index)
+                } else {
+                    None
+                }
+            }
+            PriceLadder::Sparse { bids, asks } => match side {
+                Side::Buy => bids.get(&index),
+                Side::Sell => asks.get(&index),
+            },
+        }
+    }
+
+    /// Returns mutable reference to the occupied price level with the given index on
+    /// specified side of the book
+    pub fn get_level_mut(&mut self, side: Side, index: usize) -> Option<&mut PriceLevel> {
+        match self {
+            PriceLadder::Dense { levels, bids, asks } => {
+                let occupied = match side {
+                    Side::Buy => bids,
+                    Side::Sell => asks,
+                };
+                if occupied.contains(index) {
+                    levels.get_mut(index)
+                } else {
+                    None
+                }
+            }
+            PriceLadder::Sparse { bids, asks } => match side {
+                Side::Buy => bids.get_mut(&index),
+                Side::Sell => asks.get_mut(&index),
+            },
+        }
+    }
+
+    /// Returns the index of the best occupied price level on specified side of the book
+    pub fn get_best_index(&self, side: Side) -> Option<usize> {
+        match self {
+            PriceLadder::Dense { bids, asks, .. } => match side {
+                Side::Buy => bids.last(),
+                Side::Sell => asks.first(),
+            },
+            PriceLadder::Sparse { bids, asks } => match side {
+                Side::Buy => bids.last_key_value().map(|(index, _)| *index),
+                Side::Sell => asks.first_key_value().map(|(index, _)| *index),
+            },
+        }
+    }
+
+    /// Returns the index of the next occupied price level on specified side of the book,
+    /// moving away from the best price
+    pub fn get_next_index(&self, side: Side, index: usize) -> Option<usize> {
+        match self {
+            PriceLadder::Dense { bids, asks, .. } => match side {
+                Side::Buy => index
+                    .checked_sub(1)
+                    .and_then(|index| bids.prev_at_or_below(index)),
+                Side::Sell => asks.next_at_or_above(index + 1),
+            },
+            PriceLadder::Sparse { bids, asks } => match side {
+                Side::Buy => bids.range(..index).next_back().map(|(index, _)| *index),
+                Side::Sell => asks.range(index + 1..).next().map(|(index, _)| *index),
+            },
+        }
+    }
+
+    /// Adds an order to the back of the price level with the given index, and returns
+    /// the price level
+    pub fn add_order(
+        &mut self,
+        arena: &mut OrderArena,
+        index: usize,
+        order: Order,
+    ) -> Result<&PriceLevel, ExchangeError> {
+        match self {
+            PriceLadder::Dense { levels, bids, asks } => {
+                let price_level = &mut levels[index];
+                price_level.add_order(arena, order)?;
+                match order.get_side() {
+                    Side::Buy => bids.insert(index),
+                    Side::Sell => asks.insert(index),
+                }
+                Ok(price_level)
+            }
+            PriceLadder::Sparse { bids, asks } => {
+                let side = match order.get_side() {
+                    Side::Buy => bids,
+                    Side::Sell => asks,
+                };
+                let result = side
+                    .entry(index)
+                    .or_insert_with(|| PriceLevel::new(order.get_price()))
+                    .add_order(arena, order);
+                if let Err(err) = result {
+                    if side.get(&index).is_some_and(|level| level.is_empty()) {
+                        side.remove(&index);
+                    }
+                    return Err(err);
+                }
+                Ok(&side[&index])
+            }
+        }
+    }
+
+    /// Removes an order from the price level with the given index, returning it if it
+    /// was found
+    pub fn remove_order(
+        &mut self,
+        arena: &mut OrderArena,
+        side: Side,
+        index: usize,
+        id: u64,
+    ) -> Option<Order> {
+        let order = self.get_level_mut(side, index)?.remove_order(arena, id);
+        self.release_level(side, index);
+        order
+    }
+
+    /// Marks the price level with the given index as unoccupied if it has no orders left
+    pub fn release_level(&mut self, side: Side, index: usize) {
+        match self {
+            PriceLadder::Dense { levels, bids, asks } => {
+                if levels[index].is_empty() {
+                    match side {
+                        Side::Buy => bids.remove(index),
+                        Side::Sell => asks.remove(index),
+                    }
+                }
+            }
+            PriceLadder::Sparse { bids, asks } => {
+                let side = match side {
+                    Side::Buy => bids,
+                    Side::Sell => asks,
+                };
+                if side.get(&index).is_some_and(|level| level.is_empty()) {
+                    side.remove(&index);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::market::LadderType;
+    use crate::order::{Order, OrderStatus, OrderType, Side};
+    use crate::order_arena::OrderArena;
+    use crate::price_ladder::PriceLadder;
+
+    fn new_order(price: u64, side: Side, id: u64) -> Order {
+        let mut order = Order::new(price, 1, side, OrderStatus::Open, OrderType::Limit);
+        order.set_id(id);
+        order
+    }
+
+    #[test]
+    fn test_new_dense() {
+        let ladder = PriceLadder::new(LadderType::Dense, 1, 101, 1);
+        assert_eq!(ladder.get_ladder_type(), LadderType::Dense);
+        assert_eq!(ladder.get_allocated_levels(), 100);
+        assert!(ladder.get_best_index(Side::Buy).is_none());
+        assert!(ladder.get_best_index(Side::Sell).is_none());
+    }
+
+    #[test]
+    fn test_new_sparse() {
+        let ladder = PriceLadder::new(LadderType::Sparse, 1, 1_000_000_001, 1);
+        assert_eq!(ladder.get_ladder_type(), LadderType::Sparse);
+        assert_eq!(ladder.get_allocated_levels(), 0);
+        assert!(ladder.get_best_index(Side::Buy).is_none());
+        assert!(ladder.get_best_index(Side::Sell).is_none());
+    }
+
+    #[test]
+    fn test_add_and_remove_order() {
+        for ladder_type in [LadderType::Dense, LadderType::Sparse] {
+            let mut arena = OrderArena::with_capacity(16);
+            let mut ladder = PriceLadder::new(ladder_type, 1, 101, 1);
+            let price_level = ladder.add_order(&mut arena, 9, new_order(10, Side::Buy, 1));
+            assert_eq!(price_level.unwrap().get_size(), 1);
+            assert_eq!(ladder.get_level(Side::Buy, 9).unwrap().get_price(), 10);
+            assert!(ladder.get_level(Side::Sell, 9).is_none());
+            assert!(ladder.get_level(Side::Buy, 8).is_none());
+            let order = ladder.remove_order(&mut arena, Side::Buy, 9, 1);
+            assert_eq!(order.unwrap().get_id(), 1);
+            assert!(ladder.get_level(Side::Buy, 9).is_none());
+            assert!(ladder.get_best_index(Side::Buy).is_none());
+            assert!(ladder.remove_order(&mut arena, Side::Buy, 9, 1).is_none());
+        }
+    }
+
+    #[test]
+    fn test_sparse_ladder_releases_empty_levels() {
+        let mut arena = OrderArena::with_capacity(16);
+        let mut ladder = PriceLadder::new(LadderType::Sparse, 1, 1_000_000_001, 1);
+        assert!(
+            ladder
+                .add_order(&mut arena, 999_999, new_order(1_000_000, Side::Sell, 1))
+                .is_ok()
+        );
+        assert!(
+            ladder
+                .add_order(&mut arena, 999_999, new_order(1_000_000, Side::Sell, 2))
+                .is_ok()
+        );
+        assert_eq!(ladder.get_allocated_levels(), 1);
+        ladder.remove_order(&mut arena, Side::Sell, 999_999, 1);
+        assert_eq!(ladder.get_allocated_levels(), 1);
+        ladder.remove_order(&mut arena, Side::Sell, 999_999, 2);
+        assert_eq!(ladder.get_allocated_levels(), 0);
+    }
+
+    #[test]
+    fn test_best_and_next_index() {
+        for ladder_type in [LadderType::Dense, LadderType::Sparse] {
+            let mut arena = OrderArena::with_capacity(16);
+            let mut ladder = PriceLadder::new(ladder_type, 1, 10_001, 1);
+            let orders = [
+                (10, Side::Buy),
+                (500, Side::Buy),
+                (2_000, Side::Buy),
+                (2_001, Side::Sell),
+                (7_000, Side::Sell),
+                (9_999, Side::Sell),
+            ];
+            for (id, (price, side)) in orders.iter().enumerate() {
+                let index = (*price - 1) as usize;
+                let order = new_order(*price, *side, id as u64 + 1);
+                assert!(ladder.add_order(&mut arena, index, order).is_ok());
+            }
+            assert_eq!(ladder.get_best_index(Side::Buy), Some(1_999));
+            assert_eq!(ladder.get_next_index(Side::Buy, 1_999), Some(499));
+            assert_eq!(ladder.get_next_index(Side::Buy, 499), Some(9));
+            assert_eq!(ladder.get_next_index(Side::Buy, 9), None);
+            assert_eq!(ladder.get_best_index(Side::Sell), Some(2_000));
+            assert_eq!(ladder.get_next_index(Side::Sell, 2_000), Some(6_999));
+            assert_eq!(ladder.get_next_index(Side::Sell, 6_999), Some(9_998));
+            assert_eq!(ladder.get_next_index(Side::Sell, 9_998), None);
+        }
+    }
+
+    #[test]
+    fn test_release_level_keeps_occupied_levels() {
+        for ladder_type in [LadderType::Dense, LadderType::Sparse] {
+            let mut arena = OrderArena::with_capacity(16);
+            let mut ladder = PriceLadder::new(ladder_type, 1, 101, 1);
+            assert!(
+                ladder
+                    .add_order(&mut arena, 4, new_order(5, Side::Sell, 1))
+                    .is_ok()
+            );
+            ladder.release_level(Side::Sell, 4);
+            assert!(ladder.get_level(Side::Sell, 4).is_some());
+            let slot = arena.get_slot(1).unwrap();
+            let price_level = ladder.get_level_mut(Side::Sell, 4).unwrap();
+            price_level.resize_order(&mut arena, slot, 0, 0);
+            price_level.remove_order(&mut arena, 1);
+            ladder.release_level(Side::Sell, 4);
+            assert!(ladder.get_level(Side::Sell, 4).is_none());
+            assert!(ladder.get_best_index(Side::Sell).is_none());
+        }
+    }
+}
@@ -1,60 +1,111 @@
 use bincode::Decode;
 use bincode::Encode;
 
+use crate::error::ExchangeError;
 use crate::order::Order;
-use crate::order::OrderStatus;
-use crate::order::OrderType;
-use crate::order::Side;
-
-const MAX_ORDERS_PER_LEVEL: usize = 200;
+use crate::order_arena::{NIL, OrderArena};
 
+/// Queue of orders resting at a single price
+///
+/// The orders are stored in an `OrderArena` shared across the book and linked together
+/// in time priority, so adding, removing and requeueing an order are constant time. The
+/// displayed size, hidden size and order count are maintained as orders change, so any
+/// change to a resting order's size must go through its price level
 #[derive(Encode, Decode, Debug)]
 pub struct PriceLevel {
-    orders: Vec<Order>,
-    order_cursor: usize,
+    head: usize,
+    tail: usize,
     price: u64,
+    size: u64,
+    hidden_size: u64,
+    order_count: u64,
+}
+
+pub struct PriceLevelIter<'a> {
+    arena: &'a OrderArena,
+    slot: usize,
+}
+
+impl<'a> Iterator for PriceLevelIter<'a> {
+    type Item = &'a Order;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.slot == NIL {
+            return None;
+        }
+        let order = self.arena.get(self.slot);
+        self.slot = self.arena.get_next(self.slot);
+        Some(order)
+    }
 }
 
 impl PriceLevel {
     /// Creates a price level with given price
     pub fn new(price: u64) -> Self {
-        let mut orders: Vec<Order> = Vec::new();
-        for _ in 0..MAX_ORDERS_PER_LEVEL {
-            orders.push(Order::new(
-                0,
-                0,
-                Side::Buy,
-                OrderStatus::Open,
-                OrderType::Limit,
-            ));
-        }
         Self {
             price,
-            order_cursor: 0,
-            orders,
+            head: NIL,
+            tail: NIL,
+            size: 0,
+            hidden_size: 0,
+            order_count: 0,
         }
     }
 
-    /// Returns mutable reference to active orders
-    pub fn get_orders_mut(&mut self) -> &mut [Order] {
-        &mut self.orders[0..self.order_cursor]
+    /// Returns the slot of the order at the front of the queue, if there is one
+    pub fn get_front(&self) -> Option<usize> {
+        if self.head == NIL {
+            None
+        } else {
+            Some(self.head)
+        }
     }
 
-    /// Returns immutable reference to active orders
-    pub fn get_orders(&self) -> &[Order] {
-        &self.orders[0..self.order_cursor]
+    /// Returns an iterator over the orders at this price level in time priority
+    pub fn iter<'a>(&self, arena: &'a OrderArena) -> PriceLevelIter<'a> {
+        PriceLevelIter {
+            arena,
+            slot: self.head,
+        }
+    }
+
+    /// Returns a copy of the orders at this price level in time priority
+    pub fn get_orders(&self, arena: &OrderArena) -> Vec<Order> {
+        self.iter(arena).copied().collect()
+    }
+
+    /// Returns true if there are no orders at this price level
+    pub fn is_empty(&self) -> bool {
+        self.head == NIL
     }
 
     /// Returns the total remaining volume available at this price level
     pub fn get_size(&self) -> u64 {
-        let mut total_size = 0;
-        for order in &self.orders {
-            if order.get_price() == 0 {
-                break;
-            }
-            total_size += order.get_remaining();
+        self.size
+    }
+
+    /// Returns the total unfilled volume at this price level, including the hidden
+    /// reserve of iceberg orders
+    pub fn get_total_size(&self) -> u64 {
+        self.size + self.hidden_size
+    }
+
+    /// Returns the number of orders at this price level
+    pub fn get_order_count(&self) -> u64 {
+        self.order_count
+    }
+
+    /// Returns true if the cached size and order count match the orders in the queue
+    pub fn is_consistent(&self, arena: &OrderArena) -> bool {
+        let mut size = 0;
+        let mut hidden_size = 0;
+        let mut order_count = 0;
+        for order in self.iter(arena) {
+            size += order.get_remaining();
+            hidden_size += order.get_hidden();
+            order_count += 1;
         }
-        total_size
+        size == self.size && hidden_size == self.hidden_size && order_count == self.order_count
     }
 
     /// Returns the price of this price level
@@ -62,43 +113,108 @@ impl PriceLevel {
         self.price
     }
 
-    /// Adds an order to this price level
-    pub fn add_order(&mut self, price: u64, size: u64, side: Side, order_type: OrderType, id: u64) {
-        if price != self.price {
+    /// Adds an order to the back of this price level
+    pub fn add_order(&mut self, arena: &mut OrderArena, order: Order) -> Result<(), ExchangeError> {
+        if order.get_price() != self.price {
             panic!(
                 "order price {} does not match level price {}",
-                price, self.price
+                order.get_price(),
+                self.price
             );
         }
-        if let Some(order) = self.orders.get_mut(self.order_cursor) {
-            order.set_price(price);
-            order.set_remaining(size);
-            order.set_side(side);
-            order.set_size(size);
-            order.set_status(OrderStatus::Open);
-            order.set_type(order_type);
-            order.set_id(id);
-            self.order_cursor += 1;
+        let slot = arena.insert(order)?;
+        self.link_back(arena, slot);
+        self.size += order.get_remaining();
+        self.hidden_size += order.get_hidden();
+        self.order_count += 1;
+        debug_assert!(self.is_consistent(arena));
+        Ok(())
+    }
+
+    /// Removes an order from this price level, returning it if it was found
+    pub fn remove_order(&mut self, arena: &mut OrderArena, id: u64) -> Option<Order> {
+        let slot = arena.get_slot(id)?;
+        if arena.get(slot).get_price() != self.price {
+            return None;
+        }
+        self.unlink(arena, slot);
+        let order = arena.remove(slot);
+        self.size -= order.get_remaining();
+        self.hidden_size -= order.get_hidden();
+        self.order_count -= 1;
+        debug_assert!(self.is_consistent(arena));
+        Some(order)
+    }
+
+    /// Reduces the displayed size of the order in the given slot by the filled size
+    pub fn fill_order(&mut self, arena: &mut OrderArena, slot: usize, size: u64) {
+        let order = arena.get_mut(slot);
+        order.set_remaining(order.get_remaining() - size);
+        self.size -= size;
+        debug_assert!(self.is_consistent(arena));
+    }
+
+    /// Sets the displayed and hidden size of the order in the given slot, keeping its
+    /// queue priority
+    pub fn resize_order(
+        &mut self,
+        arena: &mut OrderArena,
+        slot: usize,
+        remaining: u64,
+        hidden: u64,
+    ) {
+        let order = arena.get_mut(slot);
+        self.size = self.size - order.get_remaining() + remaining;
+        self.hidden_size = self.hidden_size - order.get_hidden() + hidden;
+        order.set_remaining(remaining);
+        order.set_hidden(hidden);
+        debug_assert!(self.is_consistent(arena));
+    }
+
+    /// Replenishes the displayed size of an iceberg order from its hidden reserve
+    ///
+    /// The replenished order loses time priority, so it is moved to the back of the level
+    pub fn replenish_order(&mut self, arena: &mut OrderArena, id: u64) {
+        if let Some(slot) = arena.get_slot(id) {
+            let order = arena.get_mut(slot);
+            let peak = order.get_display_size().min(order.get_hidden());
+            self.size = self.size - order.get_remaining() + peak;
+            self.hidden_size -= peak;
+            order.set_hidden(order.get_hidden() - peak);
+            order.set_remaining(peak);
+            self.unlink(arena, slot);
+            self.link_back(arena, slot);
+            debug_assert!(self.is_consistent(arena));
+        }
+    }
+
+    /// Links the order in the given slot to the back of the queue
+    fn link_back(&mut self, arena: &mut OrderArena, slot: usize) {
+        arena.set_prev(slot, self.tail);
+        arena.set_next(slot, NIL);
+        if self.tail == NIL {
+            self.head = slot;
         } else {
-            panic!("max orders at price level reached");
+            arena.set_next(self.tail, slot);
         }
+        self.tail = slot;
     }
 
-    /// Removes an order from this price level
-    pub fn remove_order(&mut self, id: u64) {
-        for (index, order) in self.get_orders_mut().iter_mut().enumerate() {
-            if order.get_id() == id {
-                order.set_price(0);
-                order.set_remaining(0);
-                order.set_side(Side::Buy);
-                order.set_size(0);
-                order.set_status(OrderStatus::Open);
-                order.set_type(OrderType::Limit);
-                order.set_id(0);
-                self.orders[index..self.order_cursor].rotate_left(1);
-                self.order_cursor -= 1;
-                break;
-            }
+    /// Unlinks the order in the given slot from the queue
+    fn unlink(&mut self, arena: &mut OrderArena, slot: usize) {
+        let prev = arena.get_prev(slot);
+        let next = arena.get_next(slot);
+        if prev == NIL {
+            self.head = next;
+        } else {
+            arena.set_next(prev, next);
+        }
+        if next == NIL {
+            self.tail = prev;
+        } else {
+            arena.set_prev(next, prev);
         }
+        arena.set_prev(slot, NIL);
+        arena.set_next(slot, NIL);
     }
 }
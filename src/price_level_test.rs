@@ -1,71 +1,220 @@
 #[cfg(test)]
 mod tests {
 
-    use crate::order::{OrderType, Side};
+    use crate::order::{Order, OrderStatus, OrderType, Side};
+    use crate::order_arena::OrderArena;
     use crate::price_level::PriceLevel;
 
+    fn new_order(price: u64, size: u64, id: u64) -> Order {
+        let mut order = Order::new(price, size, Side::Buy, OrderStatus::Open, OrderType::Limit);
+        order.set_id(id);
+        order
+    }
+
+    fn get_ids(price_level: &PriceLevel, arena: &OrderArena) -> Vec<u64> {
+        price_level
+            .iter(arena)
+            .map(|order| order.get_id())
+            .collect()
+    }
+
     #[test]
     fn test_new() {
         let price_level = PriceLevel::new(100);
         assert_eq!(price_level.get_price(), 100);
         assert_eq!(price_level.get_size(), 0);
+        assert!(price_level.is_empty());
+        assert!(price_level.get_front().is_none());
     }
 
     #[test]
     fn test_add_order() {
+        let mut arena = OrderArena::with_capacity(16);
         let mut price_level = PriceLevel::new(100);
-        price_level.add_order(100, 1, Side::Buy, OrderType::Limit, 1);
+        assert!(
+            price_level
+                .add_order(&mut arena, new_order(100, 1, 1))
+                .is_ok()
+        );
         assert_eq!(price_level.get_price(), 100);
         assert_eq!(price_level.get_size(), 1);
+        assert!(!price_level.is_empty());
     }
 
     #[test]
-    #[should_panic]
-    fn test_add_order_fails_when_level_full() {
+    fn test_add_order_grows_beyond_initial_capacity() {
+        let mut arena = OrderArena::with_capacity(16);
         let mut price_level = PriceLevel::new(100);
-        for i in 0..201 {
-            price_level.add_order(100, i + 1, Side::Buy, OrderType::Limit, i + 1);
-            assert_eq!(price_level.get_price(), 100);
+        for i in 0..1_000 {
+            assert!(
+                price_level
+                    .add_order(&mut arena, new_order(100, 1, i + 1))
+                    .is_ok()
+            );
             assert_eq!(price_level.get_size(), i + 1);
         }
+        assert_eq!(price_level.get_orders(&arena).len(), 1_000);
+        assert_eq!(arena.len(), 1_000);
+        assert!(arena.reserve().is_ok());
     }
 
     #[test]
     #[should_panic]
     fn test_add_order_fails_with_price_mismatch() {
+        let mut arena = OrderArena::with_capacity(16);
         let mut price_level = PriceLevel::new(100);
-        price_level.add_order(101, 1, Side::Buy, OrderType::Limit, 1);
+        let _ = price_level.add_order(&mut arena, new_order(101, 1, 1));
     }
 
     #[test]
     fn test_remove_order() {
+        let mut arena = OrderArena::with_capacity(16);
         let mut price_level = PriceLevel::new(100);
         for i in 0..3 {
-            price_level.add_order(100, 1, Side::Buy, OrderType::Limit, i + 1);
+            assert!(
+                price_level
+                    .add_order(&mut arena, new_order(100, 1, i + 1))
+                    .is_ok()
+            );
             assert_eq!(price_level.get_price(), 100);
             assert_eq!(price_level.get_size(), i + 1);
         }
-        assert_eq!(price_level.get_size(), 3);
-        assert_eq!(price_level.get_orders().get(0).unwrap().get_price(), 100);
-        assert_eq!(price_level.get_orders().get(1).unwrap().get_price(), 100);
-        assert_eq!(price_level.get_orders().get(2).unwrap().get_price(), 100);
-        price_level.remove_order(2);
-        assert_eq!(price_level.get_price(), 100);
+        assert_eq!(get_ids(&price_level, &arena), vec![1, 2, 3]);
+        assert!(price_level.remove_order(&mut arena, 2).is_some());
         assert_eq!(price_level.get_size(), 2);
-        assert_eq!(price_level.get_orders().len(), 2);
-        assert_eq!(price_level.get_orders().get(0).unwrap().get_price(), 100);
-        assert_eq!(price_level.get_orders().get(1).unwrap().get_price(), 100);
-        price_level.remove_order(1);
-        assert_eq!(price_level.get_price(), 100);
+        assert_eq!(get_ids(&price_level, &arena), vec![1, 3]);
+        assert!(price_level.remove_order(&mut arena, 1).is_some());
         assert_eq!(price_level.get_size(), 1);
-        assert_eq!(price_level.get_orders().len(), 1);
-        assert_eq!(price_level.get_orders().get(0).unwrap().get_price(), 100);
-        price_level.remove_order(3);
-        assert_eq!(price_level.get_price(), 100);
+        assert_eq!(get_ids(&price_level, &arena), vec![3]);
+        assert!(price_level.remove_order(&mut arena, 3).is_some());
         assert_eq!(price_level.get_size(), 0);
-        price_level.remove_order(100);
-        assert_eq!(price_level.get_price(), 100);
-        assert_eq!(price_level.get_size(), 0);
-        assert_eq!(price_level.get_orders().len(), 0);
+        assert!(price_level.is_empty());
+        assert!(price_level.remove_order(&mut arena, 100).is_none());
+        assert_eq!(price_level.get_orders(&arena).len(), 0);
+        assert!(arena.is_empty());
+    }
+
+    #[test]
+    fn test_removed_slots_are_reused() {
+        let mut arena = OrderArena::with_capacity(16);
+        let mut price_level = PriceLevel::new(100);
+        assert!(
+            price_level
+                .add_order(&mut arena, new_order(100, 1, 1))
+                .is_ok()
+        );
+        let slot = arena.get_slot(1).unwrap();
+        assert!(price_level.remove_order(&mut arena, 1).is_some());
+        assert!(arena.get_slot(1).is_none());
+        assert!(
+            price_level
+                .add_order(&mut arena, new_order(100, 1, 2))
+                .is_ok()
+        );
+        assert_eq!(arena.get_slot(2), Some(slot));
+        assert_eq!(price_level.get_front(), Some(slot));
+    }
+
+    #[test]
+    fn test_replenish_order() {
+        let mut arena = OrderArena::with_capacity(16);
+        let mut price_level = PriceLevel::new(100);
+        let mut iceberg = new_order(100, 2, 1);
+        iceberg.set_display_size(2);
+        iceberg.set_hidden(3);
+        assert!(price_level.add_order(&mut arena, iceberg).is_ok());
+        assert!(
+            price_level
+                .add_order(&mut arena, new_order(100, 1, 2))
+                .is_ok()
+        );
+        assert_eq!(price_level.get_size(), 3);
+        assert_eq!(price_level.get_total_size(), 6);
+        let slot = arena.get_slot(1).unwrap();
+        price_level.fill_order(&mut arena, slot, 2);
+        price_level.replenish_order(&mut arena, 1);
+        assert_eq!(get_ids(&price_level, &arena), vec![2, 1]);
+        let orders = price_level.get_orders(&arena);
+        assert_eq!(orders.get(1).unwrap().get_remaining(), 2);
+        assert_eq!(orders.get(1).unwrap().get_hidden(), 1);
+        assert_eq!(price_level.get_size(), 3);
+        assert_eq!(price_level.get_total_size(), 4);
+    }
+
+    #[test]
+    fn test_order_count() {
+        let mut arena = OrderArena::with_capacity(16);
+        let mut price_level = PriceLevel::new(100);
+        for i in 0..3 {
+            assert!(
+                price_level
+                    .add_order(&mut arena, new_order(100, 2, i + 1))
+                    .is_ok()
+            );
+        }
+        assert_eq!(price_level.get_order_count(), 3);
+        assert!(price_level.remove_order(&mut arena, 2).is_some());
+        assert_eq!(price_level.get_order_count(), 2);
+        assert!(price_level.remove_order(&mut arena, 2).is_none());
+        assert_eq!(price_level.get_order_count(), 2);
+        assert!(price_level.is_consistent(&arena));
+    }
+
+    #[test]
+    fn test_fill_order() {
+        let mut arena = OrderArena::with_capacity(16);
+        let mut price_level = PriceLevel::new(100);
+        assert!(
+            price_level
+                .add_order(&mut arena, new_order(100, 5, 1))
+                .is_ok()
+        );
+        assert!(
+            price_level
+                .add_order(&mut arena, new_order(100, 3, 2))
+                .is_ok()
+        );
+        let slot = arena.get_slot(1).unwrap();
+        price_level.fill_order(&mut arena, slot, 4);
+        assert_eq!(price_level.get_size(), 4);
+        assert_eq!(price_level.get_order_count(), 2);
+        assert_eq!(arena.get(slot).get_remaining(), 1);
+        assert!(price_level.is_consistent(&arena));
+    }
+
+    #[test]
+    fn test_resize_order() {
+        let mut arena = OrderArena::with_capacity(16);
+        let mut price_level = PriceLevel::new(100);
+        assert!(
+            price_level
+                .add_order(&mut arena, new_order(100, 5, 1))
+                .is_ok()
+        );
+        assert!(
+            price_level
+                .add_order(&mut arena, new_order(100, 3, 2))
+                .is_ok()
+        );
+        let slot = arena.get_slot(1).unwrap();
+        price_level.resize_order(&mut arena, slot, 2, 4);
+        assert_eq!(price_level.get_size(), 5);
+        assert_eq!(price_level.get_total_size(), 9);
+        assert_eq!(get_ids(&price_level, &arena), vec![1, 2]);
+        assert!(price_level.is_consistent(&arena));
+    }
+
+    #[test]
+    fn test_is_consistent_detects_untracked_changes() {
+        let mut arena = OrderArena::with_capacity(16);
+        let mut price_level = PriceLevel::new(100);
+        assert!(
+            price_level
+                .add_order(&mut arena, new_order(100, 5, 1))
+                .is_ok()
+        );
+        let slot = arena.get_slot(1).unwrap();
+        arena.get_mut(slot).set_remaining(1);
+        assert!(!price_level.is_consistent(&arena));
     }
 }
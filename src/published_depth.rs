@@ -0,0 +1,159 @@
+use crate::order_book::{Depth, DepthLevel};
+use crate::sync::{AtomicU64, Ordering, fence, spin_loop};
+
+/// Default number of price levels on each side of a book published to readers on other
+/// threads
+pub const DEFAULT_DEPTH_LEVELS: usize = 10;
+
+#[derive(Debug)]
+struct PublishedLevel {
+    price: AtomicU64,
+    size: AtomicU64,
+    order_count: AtomicU64,
+}
+
+impl PublishedLevel {
+    fn new() -> Self {
+        Self {
+            price: AtomicU64::new(0),
+            size: AtomicU64::new(0),
+            order_count: AtomicU64::new(0),
+        }
+    }
+
+    fn store(&self, level: &DepthLevel) {
+        self.price.store(level.price, Ordering::Relaxed);
+        self.size.store(level.size, Ordering::Relaxed);
+        self.order_count.store(level.order_count, Ordering::Relaxed);
+    }
+
+    fn load(&self) -> DepthLevel {
+        DepthLevel {
+            price: self.price.load(Ordering::Relaxed),
+            size: self.size.load(Ordering::Relaxed),
+            order_count: self.order_count.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Best price levels of an order book, published by its worker thread through a seqlock
+///
+/// Works like the published quote: the worker is the only writer and readers retry if
+/// they overlap with a write, so both sides of every snapshot are from the same state of
+/// the book. Only the best levels of each side are published, up to a number fixed when
+/// the published depth is created
+#[derive(Debug)]
+pub struct PublishedDepth {
+    sequence: AtomicU64,
+    market_sequence: AtomicU64,
+    bid_count: AtomicU64,
+    ask_count: AtomicU64,
+    bids: Box<[PublishedLevel]>,
+    asks: Box<[PublishedLevel]>,
+}
+
+impl PublishedDepth {
+    /// Creates a published depth of the given number of levels on each side, with the
+    /// initial levels of a book
+    pub fn new(depth: &Depth, levels: usize) -> Self {
+        let published_side = || (0..levels).map(|_| PublishedLevel::new()).collect();
+        let published = Self {
+            sequence: AtomicU64::new(0),
+            market_sequence: AtomicU64::new(0),
+            bid_count: AtomicU64::new(0),
+            ask_count: AtomicU64::new(0),
+            bids: published_side(),
+            asks: published_side(),
+        };
+        published.store_fields(
+            depth.sequence,
+            depth.bids.iter().copied(),
+            depth.asks.iter().copied(),
+        );
+        published
+    }
+
+    /// Returns the number of levels published on each side
+    pub fn get_levels(&self) -> usize {
+        self.bids.len()
+    }
+
+    /// Stores the levels of each side, up to the number of published levels
+    fn store_fields(
+        &self,
+        market_sequence: u64,
+        bids: impl Iterator<Item = DepthLevel>,
+        asks: impl Iterator<Item = DepthLevel>,
+    ) {
+        self.market_sequence
+            .store(market_sequence, Ordering::Relaxed);
+        self.bid_count
+            .store(store_side(&self.bids, bids), Ordering::Relaxed);
+        self.ask_count
+            .store(store_side(&self.asks, asks), Ordering::Relaxed);
+    }
+
+    /// Loads up to the given number of levels on each side, which may be torn unless
+    /// checked against the sequence
+    fn load_fields(&self, levels: usize) -> Depth {
+        let load_side = |published: &[PublishedLevel], count: &AtomicU64| {
+            let count = (count.load(Ordering::Relaxed) as usize).min(levels);
+            published
+                .iter()
+                .take(count)
+                .map(PublishedLevel::load)
+                .collect()
+        };
+        Depth {
+            sequence: self.market_sequence.load(Ordering::Relaxed),
+            bids: load_side(&self.bids, &self.bid_count),
+            asks: load_side(&self.asks, &self.ask_count),
+        }
+    }
+
+    /// Publishes the best levels of each side, best price first
+    ///
+    /// Levels beyond the number of published levels are ignored. Must only be called
+    /// from the thread that owns the order book
+    pub fn publish(
+        &self,
+        market_sequence: u64,
+        bids: impl Iterator<Item = DepthLevel>,
+        asks: impl Iterator<Item = DepthLevel>,
+    ) {
+        self.sequence.fetch_add(1, Ordering::Relaxed);
+        // keep the field writes after the sequence is made odd
+        fence(Ordering::Release);
+        self.store_fields(market_sequence, bids, asks);
+        self.sequence.fetch_add(1, Ordering::Release);
+    }
+
+    /// Returns a consistent snapshot of up to the given number of levels on each side,
+    /// retrying while a write is in progress
+    pub fn read(&self, levels: usize) -> Depth {
+        loop {
+            let before = self.sequence.load(Ordering::Acquire);
+            if before & 1 == 1 {
+                spin_loop();
+                continue;
+            }
+            let depth = self.load_fields(levels);
+            // keep the field reads before the sequence is checked again
+            fence(Ordering::Acquire);
+            if self.sequence.load(Ordering::Relaxed) == before {
+                return depth;
+            }
+            spin_loop();
+        }
+    }
+}
+
+/// Store levels into the published levels of one side, returning how many were stored
+fn store_side(published: &[PublishedLevel], levels: impl Iterator<Item = DepthLevel>) -> u64 {
+    let mut count = 0;
+    for (published_level, level) in published.iter().zip(levels) {
+        published_level.store(&level);
+        count += 1;
+    }
+    count
+}
@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+    use std::thread;
+
+    use crate::order_book::{Depth, DepthLevel};
+    use crate::published_depth::{DEFAULT_DEPTH_LEVELS, PublishedDepth};
+
+    fn level(value: u64) -> DepthLevel {
+        DepthLevel {
+            price: value,
+            size: value,
+            order_count: value,
+        }
+    }
+
+    fn levels(value: u64, count: usize) -> Vec<DepthLevel> {
+        vec![level(value); count]
+    }
+
+    #[test]
+    fn test_read_initial_depth() {
+        let depth = Depth {
+            sequence: 7,
+            bids: levels(5, 2),
+            asks: levels(6, 1),
+        };
+        let published = PublishedDepth::new(&depth, DEFAULT_DEPTH_LEVELS);
+        assert_eq!(published.read(DEFAULT_DEPTH_LEVELS), depth);
+        assert_eq!(published.read(0).bids, vec![]);
+        assert_eq!(published.read(0).sequence, 7);
+    }
+
+    #[test]
+    fn test_publish_is_bounded() {
+        let published = PublishedDepth::new(&Depth::default(), DEFAULT_DEPTH_LEVELS);
+        let bids = levels(1, DEFAULT_DEPTH_LEVELS + 5);
+        published.publish(3, bids.into_iter(), levels(2, 3).into_iter());
+        let depth = published.read(usize::MAX);
+        assert_eq!(depth.sequence, 3);
+        assert_eq!(depth.bids, levels(1, DEFAULT_DEPTH_LEVELS));
+        assert_eq!(depth.asks, levels(2, 3));
+        assert_eq!(published.read(2).bids, levels(1, 2));
+        // levels left over from a deeper update are not read back
+        published.publish(4, std::iter::empty(), levels(3, 1).into_iter());
+        let depth = published.read(usize::MAX);
+        assert_eq!(depth.bids, vec![]);
+        assert_eq!(depth.asks, levels(3, 1));
+    }
+
+    #[test]
+    fn test_concurrent_reads_are_never_torn() {
+        let published = Arc::new(PublishedDepth::new(&Depth::default(), DEFAULT_DEPTH_LEVELS));
+        let writer = {
+            let published = published.clone();
+            thread::spawn(move || {
+                for value in 1..=10_000 {
+                    let count = value as usize % DEFAULT_DEPTH_LEVELS;
+                    published.publish(
+                        value,
+                        levels(value, count).into_iter(),
+                        levels(value, count).into_iter(),
+                    );
+                }
+            })
+        };
+        let mut last_sequence = 0;
+        loop {
+            let depth = published.read(DEFAULT_DEPTH_LEVELS);
+            // every level was written in the same update as the sequence number
+            let count = depth.sequence as usize % DEFAULT_DEPTH_LEVELS;
+            assert_eq!(depth.bids, levels(depth.sequence, count));
+            assert_eq!(depth.asks, levels(depth.sequence, count));
+            assert!(depth.sequence >= last_sequence);
+            last_sequence = depth.sequence;
+            if last_sequence == 10_000 {
+                break;
+            }
+        }
+        writer.join().unwrap();
+    }
+}
@@ -0,0 +1,97 @@
+use crate::order_book::BestQuote;
+use crate::sync::{AtomicU64, Ordering, fence, spin_loop};
+
+/// Best quote of an order book together with the number of times it has been published
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteSnapshot {
+    /// Number of quote updates published before this snapshot, starting at zero
+    pub sequence: u64,
+    /// Sequence number of the last event in the market when the quote was published
+    pub market_sequence: u64,
+    pub best_quote: BestQuote,
+}
+
+/// Best quote of an order book, published by its worker thread through a seqlock
+///
+/// The worker is the only writer. Readers on other threads never block it: they retry
+/// if they overlap with a write, so every snapshot has prices and sizes from the same
+/// instant. The sequence counter is odd while a write is in progress
+#[derive(Debug)]
+pub struct PublishedQuote {
+    sequence: AtomicU64,
+    market_sequence: AtomicU64,
+    best_bid_price: AtomicU64,
+    best_bid_size: AtomicU64,
+    best_ask_price: AtomicU64,
+    best_ask_size: AtomicU64,
+}
+
+impl PublishedQuote {
+    /// Creates a published quote with the initial quote of a book and the sequence number
+    /// of its last event
+    pub fn new(best_quote: BestQuote, market_sequence: u64) -> Self {
+        Self {
+            sequence: AtomicU64::new(0),
+            market_sequence: AtomicU64::new(market_sequence),
+            best_bid_price: AtomicU64::new(best_quote.best_bid_price),
+            best_bid_size: AtomicU64::new(best_quote.best_bid_size),
+            best_ask_price: AtomicU64::new(best_quote.best_ask_price),
+            best_ask_size: AtomicU64::new(best_quote.best_ask_size),
+        }
+    }
+
+    /// Loads the fields of the quote, which may be torn unless checked against the
+    /// sequence
+    fn load_fields(&self) -> (u64, BestQuote) {
+        let market_sequence = self.market_sequence.load(Ordering::Relaxed);
+        let best_quote = BestQuote {
+            best_bid_price: self.best_bid_price.load(Ordering::Relaxed),
+            best_bid_size: self.best_bid_size.load(Ordering::Relaxed),
+            best_ask_price: self.best_ask_price.load(Ordering::Relaxed),
+            best_ask_size: self.best_ask_size.load(Ordering::Relaxed),
+        };
+        (market_sequence, best_quote)
+    }
+
+    /// Publishes a new quote
+    ///
+    /// Must only be called from the thread that owns the order book
+    pub fn publish(&self, best_quote: &BestQuote, market_sequence: u64) {
+        self.sequence.fetch_add(1, Ordering::Relaxed);
+        // keep the field writes after the sequence is made odd
+        fence(Ordering::Release);
+        self.market_sequence
+            .store(market_sequence, Ordering::Relaxed);
+        self.best_bid_price
+            .store(best_quote.best_bid_price, Ordering::Relaxed);
+        self.best_bid_size
+            .store(best_quote.best_bid_size, Ordering::Relaxed);
+        self.best_ask_price
+            .store(best_quote.best_ask_price, Ordering::Relaxed);
+        self.best_ask_size
+            .store(best_quote.best_ask_size, Ordering::Relaxed);
+        self.sequence.fetch_add(1, Ordering::Release);
+    }
+
+    /// Returns a consistent snapshot of the quote, retrying while a write is in progress
+    pub fn read(&self) -> QuoteSnapshot {
+        loop {
+            let before = self.sequence.load(Ordering::Acquire);
+            if before & 1 == 1 {
+                spin_loop();
+                continue;
+            }
+            let (market_sequence, best_quote) = self.load_fields();
+            // keep the field reads before the sequence is checked again
+            fence(Ordering::Acquire);
+            if self.sequence.load(Ordering::Relaxed) == before {
+                return QuoteSnapshot {
+                    sequence: before / 2,
+                    market_sequence,
+                    best_quote,
+                };
+            }
+            spin_loop();
+        }
+    }
+}
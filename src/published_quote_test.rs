@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+    use std::thread;
+
+    use crate::order_book::BestQuote;
+    use crate::published_quote::PublishedQuote;
+
+    fn quote(value: u64) -> BestQuote {
+        BestQuote {
+            best_bid_price: value,
+            best_bid_size: value,
+            best_ask_price: value,
+            best_ask_size: value,
+        }
+    }
+
+    #[test]
+    fn test_read_initial_quote() {
+        let published = PublishedQuote::new(quote(5), 7);
+        let snapshot = published.read();
+        assert_eq!(snapshot.sequence, 0);
+        assert_eq!(snapshot.market_sequence, 7);
+        assert_eq!(snapshot.best_quote, quote(5));
+    }
+
+    #[test]
+    fn test_publish_increments_sequence() {
+        let published = PublishedQuote::new(quote(0), 0);
+        published.publish(&quote(1), 3);
+        published.publish(&quote(2), 5);
+        let snapshot = published.read();
+        assert_eq!(snapshot.sequence, 2);
+        assert_eq!(snapshot.market_sequence, 5);
+        assert_eq!(snapshot.best_quote, quote(2));
+    }
+
+    #[test]
+    fn test_concurrent_reads_are_never_torn() {
+        let published = Arc::new(PublishedQuote::new(quote(0), 0));
+        let writer = {
+            let published = published.clone();
+            thread::spawn(move || {
+                for value in 1..=10_000 {
+                    published.publish(&quote(value), value);
+                }
+            })
+        };
+        let mut last_sequence = 0;
+        loop {
+            let snapshot = published.read();
+            // every field was written with the same value in the same update
+            assert_eq!(snapshot.best_quote, quote(snapshot.sequence));
+            assert_eq!(snapshot.market_sequence, snapshot.sequence);
+            assert!(snapshot.sequence >= last_sequence);
+            last_sequence = snapshot.sequence;
+            if last_sequence == 10_000 {
+                break;
+            }
+        }
+        writer.join().unwrap();
+    }
+}
@@ -0,0 +1,440 @@
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ExchangeError;
+use crate::market::{LadderType, Market};
+use crate::order_book::{BestQuote, OrderBook};
+use crate::request::{
+    AdvanceClockRequest, AmendOrderRequest, CancelOrderRequest, SubmitOrderRequest,
+};
+use crate::response::{OrderEvent, OrderEventKind, SubmitOrderResponse};
+use crate::snapshot::LevelSnapshot;
+use crate::trade::Trade;
+
+/// Line of a replay input file
+///
+/// Each line is a JSON object whose `type` field names the request, for example
+/// `{"type":"submit","symbol":"BTCUSD","side":"buy","price":100,"size":5}`. Fields of a
+/// submit request that are left out take their default value
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReplayRequest {
+    ListMarket(MarketDefinition),
+    Submit(SubmitOrderRequest),
+    Cancel(CancelOrderRequest),
+    Amend(AmendOrderRequest),
+    AdvanceClock(AdvanceClockRequest),
+}
+
+/// Trading rules of a market listed by a replay input file
+///
+/// Rules that are left out take the defaults of `Market::new`
+#[derive(Debug, Deserialize)]
+pub struct MarketDefinition {
+    pub symbol: String,
+    pub min_price: u64,
+    pub max_price: u64,
+    pub tick_size: Option<u64>,
+    pub lot_size: Option<u64>,
+    pub min_quantity: Option<u64>,
+    pub max_quantity: Option<u64>,
+    pub min_notional: Option<u64>,
+    pub price_scale: Option<u8>,
+    pub quantity_scale: Option<u8>,
+    pub ladder_type: Option<LadderType>,
+}
+
+impl MarketDefinition {
+    /// Returns the market with these trading rules
+    pub fn to_market(&self) -> Market {
+        let mut market = Market::new(&self.symbol, self.max_price, self.min_price);
+        if let Some(tick_size) = self.tick_size {
+            market.set_tick_size(tick_size);
+        }
+        if let Some(lot_size) = self.lot_size {
+            market.set_lot_size(lot_size);
+        }
+        if let Some(min_quantity) = self.min_quantity {
+            market.set_min_quantity(min_quantity);
+        }
+        if let Some(max_quantity) = self.max_quantity {
+            market.set_max_quantity(max_quantity);
+        }
+        if let Some(min_notional) = self.min_notional {
+            market.set_min_notional(min_notional);
+        }
+        if let Some(price_scale) = self.price_scale {
+            market.set_price_scale(price_scale);
+        }
+        if let Some(quantity_scale) = self.quantity_scale {
+            market.set_quantity_scale(quantity_scale);
+        }
+        if let Some(ladder_type) = self.ladder_type {
+            market.set_ladder_type(ladder_type);
+        }
+        market
+    }
+}
+
+/// Line of a replay output file
+///
+/// Events carry the number of the input line that caused them, and events that change a
+/// book carry their sequence number in its market and are written in sequence order. The
+/// final state of every order book follows the events, in symbol order
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReplayEvent {
+    /// A market was listed
+    Listed { line: usize, symbol: String },
+    /// An order was accepted and given an id
+    Accepted {
+        line: usize,
+        symbol: String,
+        order_id: u64,
+        sequence: u64,
+    },
+    /// A resting order was amended
+    Amended {
+        line: usize,
+        symbol: String,
+        order_id: u64,
+        sequence: u64,
+    },
+    /// An order traded with a resting order
+    Fill {
+        line: usize,
+        symbol: String,
+        trade: Trade,
+    },
+    /// A stop order was triggered and submitted to the book
+    Triggered {
+        line: usize,
+        symbol: String,
+        order_id: u64,
+        sequence: u64,
+        size: u64,
+    },
+    /// An order was cancelled, by request, by self-trade prevention, because it could
+    /// not be activated or rested, or because its unfilled size was not allowed to rest
+    Cancelled {
+        line: usize,
+        symbol: String,
+        order_id: u64,
+        sequence: u64,
+        size: u64,
+    },
+    /// An order reached its expire time
+    Expired {
+        line: usize,
+        symbol: String,
+        order_id: u64,
+        sequence: u64,
+        size: u64,
+    },
+    /// An order was reduced by self-trade prevention
+    Reduced {
+        line: usize,
+        symbol: String,
+        order_id: u64,
+        sequence: u64,
+        size: u64,
+    },
+    /// A line was rejected, with the code of the exchange error if it was not malformed
+    Reject {
+        line: usize,
+        symbol: Option<String>,
+        code: Option<u16>,
+        reason: String,
+    },
+    /// Final state of an order book
+    Book {
+        symbol: String,
+        last_order_id: u64,
+        last_trade_price: u64,
+        last_sequence: u64,
+        best_quote: BestQuote,
+        bids: Vec<LevelSnapshot>,
+        asks: Vec<LevelSnapshot>,
+        stop_order_count: usize,
+    },
+}
+
+/// Replays requests against a set of order books
+///
+/// Requests are processed one at a time directly by the order books, without worker
+/// threads, so replaying the same input always gives the same output
+#[derive(Default)]
+pub struct Replay {
+    order_books: BTreeMap<String, OrderBook>,
+}
+
+impl ReplayEvent {
+    /// Returns the replay event for an order event caused by the given line
+    fn from_order_event(line: usize, symbol: &str, event: &OrderEvent) -> Self {
+        let symbol = symbol.to_string();
+        let (order_id, sequence, size) = (event.id, event.sequence, event.size);
+        match event.kind {
+            OrderEventKind::Cancelled => ReplayEvent::Cancelled {
+                line,
+                symbol,
+                order_id,
+                sequence,
+                size,
+            },
+            OrderEventKind::Expired => ReplayEvent::Expired {
+                line,
+                symbol,
+                order_id,
+                sequence,
+                size,
+            },
+            OrderEventKind::Reduced => ReplayEvent::Reduced {
+                line,
+                symbol,
+                order_id,
+                sequence,
+                size,
+            },
+            OrderEventKind::Triggered => ReplayEvent::Triggered {
+                line,
+                symbol,
+                order_id,
+                sequence,
+                size,
+            },
+        }
+    }
+
+    /// Returns the replay events for the trades and order events of a submission or
+    /// amendment caused by the given line, in sequence order
+    fn from_response(line: usize, symbol: &str, response: SubmitOrderResponse) -> Vec<Self> {
+        let mut events: Vec<(u64, ReplayEvent)> = response
+            .trades
+            .into_iter()
+            .map(|trade| {
+                let event = ReplayEvent::Fill {
+                    line,
+                    symbol: symbol.to_string(),
+                    trade,
+                };
+                (trade.get_sequence(), event)
+            })
+            .collect();
+        events.extend(response.order_events.iter().map(|event| {
+            (
+                event.sequence,
+                ReplayEvent::from_order_event(line, symbol, event),
+            )
+        }));
+        events.sort_by_key(|(sequence, _)| *sequence);
+        events.into_iter().map(|(_, event)| event).collect()
+    }
+}
+
+impl Replay {
+    /// Creates a replay with no markets
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the order book with the given symbol
+    pub fn get_order_book(&self, symbol: &str) -> Option<&OrderBook> {
+        self.order_books.get(symbol)
+    }
+
+    /// Process one line of input, returning the events it caused
+    ///
+    /// Blank lines are skipped
+    pub fn handle_line(&mut self, line: usize, text: &str) -> Vec<ReplayEvent> {
+        if text.trim().is_empty() {
+            return Vec::new();
+        }
+        match serde_json::from_str::<ReplayRequest>(text) {
+            Ok(request) => self.handle_request(line, request),
+            Err(err) => vec![ReplayEvent::Reject {
+                line,
+                symbol: None,
+                code: None,
+                reason: format!("invalid request: {}", err),
+            }],
+        }
+    }
+
+    /// Process a request, returning the events it caused
+    pub fn handle_request(&mut self, line: usize, request: ReplayRequest) -> Vec<ReplayEvent> {
+        let (symbol, result) = match request {
+            ReplayRequest::ListMarket(definition) => {
+                let symbol = definition.symbol.clone();
+                let result = self.list_market(line, definition.to_market());
+                (symbol, result)
+            }
+            ReplayRequest::Submit(mut request) => {
+                let symbol = request.symbol.clone();
+                let result = self.submit_order(line, &mut request);
+                (symbol, result)
+            }
+            ReplayRequest::Cancel(request) => {
+                let symbol = request.symbol.clone();
+                let result = self.cancel_order(line, &request);
+                (symbol, result)
+            }
+            ReplayRequest::Amend(request) => {
+                let symbol = request.symbol.clone();
+                let result = self.amend_order(line, &request);
+                (symbol, result)
+            }
+            ReplayRequest::AdvanceClock(request) => {
+                let symbol = request.symbol.clone();
+                let result = self.advance_clock(line, &request);
+                (symbol, result)
+            }
+        };
+        match result {
+            Ok(events) => events,
+            Err(err) => vec![ReplayEvent::Reject {
+                line,
+                symbol: Some(symbol),
+                code: Some(err.code()),
+                reason: err.message().to_string(),
+            }],
+        }
+    }
+
+    /// Get mutable reference to the order book with the given symbol
+    fn get_order_book_mut(&mut self, symbol: &str) -> Result<&mut OrderBook, ExchangeError> {
+        match self.order_books.get_mut(symbol) {
+            Some(order_book) => Ok(order_book),
+            None => Err(ExchangeError::MarketNotFound),
+        }
+    }
+
+    /// List a new market with an empty order book
+    fn list_market(
+        &mut self,
+        line: usize,
+        market: Market,
+    ) -> Result<Vec<ReplayEvent>, ExchangeError> {
+        market.validate()?;
+        let symbol = market.get_symbol().clone();
+        if self.order_books.contains_key(&symbol) {
+            return Err(ExchangeError::MarketAlreadyExists);
+        }
+        self.order_books
+            .insert(symbol.clone(), OrderBook::new(market));
+        Ok(vec![ReplayEvent::Listed { line, symbol }])
+    }
+
+    /// Submit an order, returning its acceptance followed by its fills, the stop orders
+    /// it triggered and the orders cancelled or reduced by self-trade prevention, in
+    /// sequence order
+    fn submit_order(
+        &mut self,
+        line: usize,
+        request: &mut SubmitOrderRequest,
+    ) -> Result<Vec<ReplayEvent>, ExchangeError> {
+        let symbol = request.symbol.clone();
+        let response = self.get_order_book_mut(&symbol)?.submit_order(request)?;
+        let mut events = vec![ReplayEvent::Accepted {
+            line,
+            symbol: symbol.clone(),
+            order_id: response.id,
+            sequence: response.sequence,
+        }];
+        events.extend(ReplayEvent::from_response(line, &symbol, response));
+        Ok(events)
+    }
+
+    /// Amend a resting order, returning the amendment followed by the events of the
+    /// replacement order crossing the book, in sequence order
+    fn amend_order(
+        &mut self,
+        line: usize,
+        request: &AmendOrderRequest,
+    ) -> Result<Vec<ReplayEvent>, ExchangeError> {
+        let response = self
+            .get_order_book_mut(&request.symbol)?
+            .amend_order(request)?;
+        let mut events = vec![ReplayEvent::Amended {
+            line,
+            symbol: request.symbol.clone(),
+            order_id: response.id,
+            sequence: response.sequence,
+        }];
+        events.extend(ReplayEvent::from_response(line, &request.symbol, response));
+        Ok(events)
+    }
+
+    /// Advance the clock of a market, returning the orders that expired
+    fn advance_clock(
+        &mut self,
+        line: usize,
+        request: &AdvanceClockRequest,
+    ) -> Result<Vec<ReplayEvent>, ExchangeError> {
+        let expired = self
+            .get_order_book_mut(&request.symbol)?
+            .advance_clock(request.time)?;
+        Ok(expired
+            .iter()
+            .map(|event| ReplayEvent::from_order_event(line, &request.symbol, event))
+            .collect())
+    }
+
+    /// Cancel a resting or stop order
+    fn cancel_order(
+        &mut self,
+        line: usize,
+        request: &CancelOrderRequest,
+    ) -> Result<Vec<ReplayEvent>, ExchangeError> {
+        let cancelled = self
+            .get_order_book_mut(&request.symbol)?
+            .cancel_order(request)?;
+        Ok(vec![ReplayEvent::from_order_event(
+            line,
+            &request.symbol,
+            &cancelled,
+        )])
+    }
+
+    /// Returns the final state of every order book, in symbol order
+    pub fn get_books(&self) -> Vec<ReplayEvent> {
+        self.order_books
+            .iter()
+            .map(|(symbol, order_book)| {
+                let snapshot = order_book.snapshot();
+                ReplayEvent::Book {
+                    symbol: symbol.clone(),
+                    last_order_id: snapshot.last_order_id,
+                    last_trade_price: snapshot.last_trade_price,
+                    last_sequence: snapshot.last_sequence,
+                    best_quote: snapshot.best_quote,
+                    bids: snapshot.bids,
+                    asks: snapshot.asks,
+                    stop_order_count: snapshot.stop_orders.len(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Replay every line of the input, writing the events and final order books to the
+/// output as JSON lines
+pub fn run<R: BufRead, W: Write>(input: R, mut output: W) -> io::Result<()> {
+    let mut replay = Replay::new();
+    for (index, text) in input.lines().enumerate() {
+        for event in replay.handle_line(index + 1, &text?) {
+            write_event(&mut output, &event)?;
+        }
+    }
+    for book in replay.get_books() {
+        write_event(&mut output, &book)?;
+    }
+    output.flush()
+}
+
+/// Write an event to the output as a line of JSON
+fn write_event<W: Write>(output: &mut W, event: &ReplayEvent) -> io::Result<()> {
+    serde_json::to_writer(&mut *output, event)?;
+    output.write_all(b"\n")
+}
@@ -0,0 +1,174 @@
+#[cfg(test)]
+mod tests {
+
+    use serde_json::Value;
+
+    use crate::replay::{self, Replay};
+
+    const INPUT: &str = r#"{"type":"list_market","symbol":"BTCUSD","min_price":1,"max_price":1000}
+{"type":"submit","symbol":"BTCUSD","side":"sell","price":101,"size":3}
+{"type":"submit","symbol":"BTCUSD","side":"sell","price":102,"size":3,"account_id":7}
+{"type":"submit","symbol":"BTCUSD","side":"buy","order_type":"stop_market","trigger_price":102,"size":1}
+
+{"type":"submit","symbol":"BTCUSD","side":"buy","price":102,"size":4}
+{"type":"cancel","symbol":"BTCUSD","id":1}
+{"type":"submit","symbol":"ETHUSD","side":"buy","price":1,"size":1}
+{"type":"list_market","symbol":"BTCUSD","min_price":1,"max_price":1000}
+not json
+"#;
+
+    /// Returns the output of replaying the input, one JSON value per line
+    fn replay_lines(input: &str) -> Vec<Value> {
+        let mut output = Vec::new();
+        replay::run(input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_replay_events() {
+        let lines = replay_lines(INPUT);
+        let types: Vec<&str> = lines
+            .iter()
+            .map(|line| line["type"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                "listed",
+                "accepted",
+                "accepted",
+                "accepted",
+                "accepted",
+                "fill",
+                "fill",
+                "triggered",
+                "fill",
+                "reject",
+                "reject",
+                "reject",
+                "reject",
+                "book",
+            ]
+        );
+        // every accept, fill and trigger in the market takes the next sequence number, and
+        // events are written in sequence order
+        let sequences: Vec<u64> = lines[1..9]
+            .iter()
+            .map(|line| {
+                let sequence = match line["type"].as_str().unwrap() {
+                    "fill" => &line["trade"]["sequence"],
+                    _ => &line["sequence"],
+                };
+                sequence.as_u64().unwrap()
+            })
+            .collect();
+        assert_eq!(sequences, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        // the buy order sweeps both asks, and the last trade triggers the stop order
+        let fill = &lines[6];
+        assert_eq!(fill["line"], 6);
+        assert_eq!(fill["trade"]["passive_id"], 2);
+        assert_eq!(fill["trade"]["price"], 102);
+        assert_eq!(fill["trade"]["size"], 1);
+        assert_eq!(lines[7]["order_id"], 3);
+        assert_eq!(lines[8]["trade"]["aggressor_id"], 3);
+        // order 1 was filled, so cancelling it is rejected with the exchange error code
+        assert_eq!(lines[9]["line"], 7);
+        assert_eq!(lines[9]["code"], 400);
+        assert_eq!(lines[10]["code"], 100);
+        assert_eq!(lines[11]["code"], 101);
+        assert_eq!(lines[12]["line"], 10);
+        assert!(lines[12]["code"].is_null());
+
+        let book = &lines[13];
+        assert_eq!(book["symbol"], "BTCUSD");
+        assert_eq!(book["last_order_id"], 4);
+        assert_eq!(book["last_trade_price"], 102);
+        assert_eq!(book["last_sequence"], 8);
+        assert_eq!(book["bids"].as_array().unwrap().len(), 0);
+        let asks = book["asks"].as_array().unwrap();
+        assert_eq!(asks.len(), 1);
+        assert_eq!(asks[0]["price"], 102);
+        assert_eq!(asks[0]["orders"][0]["id"], 2);
+        assert_eq!(asks[0]["orders"][0]["remaining"], 1);
+    }
+
+    #[test]
+    fn test_replay_is_deterministic() {
+        let mut first = Vec::new();
+        replay::run(INPUT.as_bytes(), &mut first).unwrap();
+        let mut second = Vec::new();
+        replay::run(INPUT.as_bytes(), &mut second).unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_replay_market_rules() {
+        let mut replay = Replay::new();
+        let events = replay.handle_line(
+            1,
+            r#"{"type":"list_market","symbol":"BTCUSD","min_price":10,"max_price":1000,"tick_size":5,"ladder_type":"sparse"}"#,
+        );
+        assert_eq!(events.len(), 1);
+        let market = replay.get_order_book("BTCUSD").unwrap().get_market();
+        assert_eq!(market.get_tick_size(), 5);
+        assert_eq!(market.get_min_price(), 10);
+
+        let events = replay.handle_line(
+            2,
+            r#"{"type":"submit","symbol":"BTCUSD","side":"buy","price":12,"size":1}"#,
+        );
+        let line = serde_json::to_value(&events[0]).unwrap();
+        assert_eq!(line["type"], "reject");
+        assert_eq!(line["reason"], "order price is not a multiple of tick size");
+    }
+
+    #[test]
+    fn test_replay_amend_and_advance_clock() {
+        let input = r#"{"type":"list_market","symbol":"BTCUSD","min_price":1,"max_price":1000}
+{"type":"submit","symbol":"BTCUSD","side":"sell","price":101,"size":1}
+{"type":"submit","symbol":"BTCUSD","side":"sell","price":101,"size":2,"account_id":7}
+{"type":"submit","symbol":"BTCUSD","side":"sell","price":102,"size":2}
+{"type":"submit","symbol":"BTCUSD","side":"buy","price":99,"size":3,"account_id":7}
+{"type":"amend","symbol":"BTCUSD","id":4,"price":102,"size":3,"self_trade_prevention":"cancel_oldest"}
+{"type":"submit","symbol":"BTCUSD","side":"buy","price":90,"size":1,"time_in_force":"good_till_date","expire_time":50}
+{"type":"advance_clock","symbol":"BTCUSD","time":50}
+{"type":"advance_clock","symbol":"BTCUSD","time":10}
+"#;
+        let lines = replay_lines(input);
+        let events: Vec<(&str, u64)> = lines[5..11]
+            .iter()
+            .map(|line| {
+                let sequence = match line["type"].as_str().unwrap() {
+                    "fill" => &line["trade"]["sequence"],
+                    _ => &line["sequence"],
+                };
+                (line["type"].as_str().unwrap(), sequence.as_u64().unwrap())
+            })
+            .collect();
+        // the self-trade prevention cancel comes between the fills of the amended order
+        assert_eq!(
+            events,
+            vec![
+                ("amended", 5),
+                ("fill", 6),
+                ("cancelled", 7),
+                ("fill", 8),
+                ("accepted", 9),
+                ("expired", 10),
+            ]
+        );
+        assert_eq!(lines[5]["order_id"], 4);
+        assert_eq!(lines[5]["line"], 6);
+        assert_eq!(lines[7]["order_id"], 2);
+        assert_eq!(lines[7]["size"], 2);
+        assert_eq!(lines[10]["order_id"], 5);
+        assert_eq!(lines[10]["line"], 8);
+        assert_eq!(lines[11]["type"], "reject");
+        assert_eq!(lines[11]["line"], 9);
+        assert_eq!(lines[12]["last_sequence"], 10);
+    }
+}
@@ -1,40 +1,104 @@
 use bincode::{Decode, Encode};
+use serde::Deserialize;
 
-use crate::order::{OrderType, Side};
+use crate::completion::Completion;
+use crate::decimal::{Price, Quantity};
+use crate::error::ExchangeError;
+use crate::market::Market;
+use crate::order::{OrderType, PostOnly, SelfTradePrevention, Side, TimeInForce};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RequestType {
     SubmitOrder,
     CancelOrder,
+    AmendOrder,
+    AdvanceClock,
 }
 
 pub trait MarketRequest {
     fn get_symbol(&self) -> &String;
+    fn into_record(self) -> RequestRecord;
+}
+
+/// Request sent to a worker thread
+///
+/// Records are moved through the transport as typed values rather than encoded into a
+/// byte payload first. They are not free of heap allocations, as every request carries
+/// the symbol of its market as a `String`
+#[derive(Debug, Encode, Decode)]
+pub enum RequestRecord {
+    SubmitOrder(SubmitOrderRequest),
+    CancelOrder(CancelOrderRequest),
+    AmendOrder(AmendOrderRequest),
+    AdvanceClock(AdvanceClockRequest),
+}
+
+impl RequestRecord {
+    /// Returns the type of the request
+    pub fn get_request_type(&self) -> RequestType {
+        match self {
+            RequestRecord::SubmitOrder(_) => RequestType::SubmitOrder,
+            RequestRecord::CancelOrder(_) => RequestType::CancelOrder,
+            RequestRecord::AmendOrder(_) => RequestType::AmendOrder,
+            RequestRecord::AdvanceClock(_) => RequestType::AdvanceClock,
+        }
+    }
+}
+
+impl MarketRequest for RequestRecord {
+    fn get_symbol(&self) -> &String {
+        match self {
+            RequestRecord::SubmitOrder(request) => request.get_symbol(),
+            RequestRecord::CancelOrder(request) => request.get_symbol(),
+            RequestRecord::AmendOrder(request) => request.get_symbol(),
+            RequestRecord::AdvanceClock(request) => request.get_symbol(),
+        }
+    }
+
+    fn into_record(self) -> RequestRecord {
+        self
+    }
 }
 
 #[derive(Debug)]
 pub struct SignedRequest {
     pub id: u128,
-    pub request_type: RequestType,
-    pub payload: Vec<u8>,
+    pub request: RequestRecord,
+    /// How the result is delivered once the request has been processed
+    pub completion: Completion,
 }
 
-#[derive(Debug, Encode, Decode, Default)]
+#[derive(Debug, Encode, Decode, Default, Clone, Deserialize)]
+#[serde(default)]
 pub struct SubmitOrderRequest {
     pub symbol: String,
     pub price: u64,
     pub size: u64,
     pub side: Side,
     pub order_type: OrderType,
+    pub time_in_force: TimeInForce,
+    /// Expire time in nanoseconds, only used by good-till-date orders
+    pub expire_time: u64,
+    pub post_only: PostOnly,
+    pub account_id: u64,
+    pub self_trade_prevention: SelfTradePrevention,
+    /// Trigger price, only used by stop orders
+    pub trigger_price: u64,
+    /// Displayed peak size of an iceberg order, or zero to display the full size
+    pub display_size: u64,
 }
 
 impl MarketRequest for SubmitOrderRequest {
     fn get_symbol(&self) -> &String {
         &self.symbol
     }
+
+    fn into_record(self) -> RequestRecord {
+        RequestRecord::SubmitOrder(self)
+    }
 }
 
-#[derive(Debug, Encode, Decode)]
+#[derive(Debug, Encode, Decode, Deserialize)]
 pub struct CancelOrderRequest {
     pub symbol: String,
     pub id: u64,
@@ -44,4 +108,114 @@ impl MarketRequest for CancelOrderRequest {
     fn get_symbol(&self) -> &String {
         &self.symbol
     }
+
+    fn into_record(self) -> RequestRecord {
+        RequestRecord::CancelOrder(self)
+    }
+}
+
+#[derive(Debug, Encode, Decode, Default, Deserialize)]
+#[serde(default)]
+pub struct AmendOrderRequest {
+    pub symbol: String,
+    pub id: u64,
+    pub price: u64,
+    /// New unfilled size of the order, including any hidden reserve
+    pub size: u64,
+    /// Self-trade prevention applied if the amended order crosses the book
+    pub self_trade_prevention: SelfTradePrevention,
+}
+
+impl MarketRequest for AmendOrderRequest {
+    fn get_symbol(&self) -> &String {
+        &self.symbol
+    }
+
+    fn into_record(self) -> RequestRecord {
+        RequestRecord::AmendOrder(self)
+    }
+}
+
+#[derive(Debug, Encode, Decode, Deserialize)]
+pub struct AdvanceClockRequest {
+    pub symbol: String,
+    /// Current time in nanoseconds
+    pub time: u64,
+}
+
+impl MarketRequest for AdvanceClockRequest {
+    fn get_symbol(&self) -> &String {
+        &self.symbol
+    }
+
+    fn into_record(self) -> RequestRecord {
+        RequestRecord::AdvanceClock(self)
+    }
+}
+
+/// Order submission with decimal prices and quantities, converted to a
+/// `SubmitOrderRequest` by the exchange using the scales of the market
+#[derive(Debug, Encode, Decode, Default, Clone)]
+pub struct DecimalSubmitOrderRequest {
+    pub symbol: String,
+    pub price: Price,
+    pub size: Quantity,
+    pub side: Side,
+    pub order_type: OrderType,
+    pub time_in_force: TimeInForce,
+    /// Expire time in nanoseconds, only used by good-till-date orders
+    pub expire_time: u64,
+    pub post_only: PostOnly,
+    pub account_id: u64,
+    pub self_trade_prevention: SelfTradePrevention,
+    /// Trigger price, only used by stop orders
+    pub trigger_price: Price,
+    /// Displayed peak size of an iceberg order, or zero to display the full size
+    pub display_size: Quantity,
+}
+
+impl DecimalSubmitOrderRequest {
+    /// Convert to an order submission in the integer prices and sizes of the market
+    pub fn to_request(&self, market: &Market) -> Result<SubmitOrderRequest, ExchangeError> {
+        Ok(SubmitOrderRequest {
+            symbol: self.symbol.clone(),
+            price: market.encode_price(self.price)?,
+            size: market.encode_quantity(self.size)?,
+            side: self.side,
+            order_type: self.order_type,
+            time_in_force: self.time_in_force,
+            expire_time: self.expire_time,
+            post_only: self.post_only,
+            account_id: self.account_id,
+            self_trade_prevention: self.self_trade_prevention,
+            trigger_price: market.encode_price(self.trigger_price)?,
+            display_size: market.encode_quantity(self.display_size)?,
+        })
+    }
+}
+
+/// Order amendment with decimal price and quantity, converted to an
+/// `AmendOrderRequest` by the exchange using the scales of the market
+#[derive(Debug, Encode, Decode, Default, Clone)]
+pub struct DecimalAmendOrderRequest {
+    pub symbol: String,
+    pub id: u64,
+    pub price: Price,
+    /// New unfilled size of the order, including any hidden reserve
+    pub size: Quantity,
+    /// Self-trade prevention applied if the amended order crosses the book
+    pub self_trade_prevention: SelfTradePrevention,
+}
+
+impl DecimalAmendOrderRequest {
+    /// Convert to an order amendment in the integer prices and sizes of the market
+    pub fn to_request(&self, market: &Market) -> Result<AmendOrderRequest, ExchangeError> {
+        Ok(AmendOrderRequest {
+            symbol: self.symbol.clone(),
+            id: self.id,
+            price: market.encode_price(self.price)?,
+            size: market.encode_quantity(self.size)?,
+            self_trade_prevention: self.self_trade_prevention,
+        })
+    }
 }
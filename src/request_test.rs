@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::decimal::{Price, Quantity};
+    use crate::error::ExchangeError;
+    use crate::market::Market;
+    use crate::order::{OrderType, Side};
+    use crate::request::{DecimalAmendOrderRequest, DecimalSubmitOrderRequest};
+
+    fn new_market() -> Market {
+        let mut market = Market::new("BTCUSD", 10_000_000, 1);
+        market.set_price_scale(2);
+        market.set_quantity_scale(4);
+        market
+    }
+
+    #[test]
+    fn test_decimal_submit_order_to_request() {
+        let request = DecimalSubmitOrderRequest {
+            symbol: "BTCUSD".to_string(),
+            price: "101.5".parse().unwrap(),
+            size: "0.25".parse().unwrap(),
+            side: Side::Buy,
+            order_type: OrderType::StopLimit,
+            trigger_price: "100".parse().unwrap(),
+            display_size: Quantity::new(1, 1),
+            account_id: 7,
+            ..Default::default()
+        };
+        let request = request.to_request(&new_market()).unwrap();
+        assert_eq!(request.symbol, "BTCUSD");
+        assert_eq!(request.price, 10_150);
+        assert_eq!(request.size, 2_500);
+        assert_eq!(request.trigger_price, 10_000);
+        assert_eq!(request.display_size, 1_000);
+        assert_eq!(request.side, Side::Buy);
+        assert_eq!(request.order_type, OrderType::StopLimit);
+        assert_eq!(request.account_id, 7);
+    }
+
+    #[test]
+    fn test_decimal_submit_order_rejects_excess_precision() {
+        let market = new_market();
+        let request = DecimalSubmitOrderRequest {
+            price: "101.125".parse().unwrap(),
+            size: Quantity::new(1, 0),
+            ..Default::default()
+        };
+        assert_eq!(
+            request.to_request(&market).unwrap_err(),
+            ExchangeError::PriceNotRepresentable
+        );
+        let request = DecimalSubmitOrderRequest {
+            price: Price::new(1, 0),
+            size: "0.00001".parse().unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(
+            request.to_request(&market).unwrap_err(),
+            ExchangeError::QuantityNotRepresentable
+        );
+    }
+
+    #[test]
+    fn test_decimal_amend_order_to_request() {
+        let request = DecimalAmendOrderRequest {
+            symbol: "BTCUSD".to_string(),
+            id: 3,
+            price: "99.99".parse().unwrap(),
+            size: "1".parse().unwrap(),
+            ..Default::default()
+        };
+        let request = request.to_request(&new_market()).unwrap();
+        assert_eq!(request.id, 3);
+        assert_eq!(request.price, 9_999);
+        assert_eq!(request.size, 10_000);
+    }
+}
@@ -0,0 +1,154 @@
+use bincode::{Decode, Encode};
+use serde::Serialize;
+
+use crate::decimal::{Price, Quantity};
+use crate::market::Market;
+use crate::order::Side;
+use crate::order_book::BestQuote;
+use crate::trade::Trade;
+
+/// Change made to an order other than by trading
+#[derive(Debug, Encode, Decode, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderEventKind {
+    /// The order was cancelled, by request, by self-trade prevention, because it could
+    /// not be activated or rested, or because its unfilled size was not allowed to rest
+    #[default]
+    Cancelled,
+    /// The order reached its expire time
+    Expired,
+    /// The order was reduced by self-trade prevention and is still live
+    Reduced,
+    /// The stop order was triggered and taken out of the stop book to be submitted to
+    /// the book
+    Triggered,
+}
+
+/// Order cancelled, expired, reduced or triggered, with the sequence number of the event
+#[derive(Debug, Encode, Decode, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct OrderEvent {
+    pub id: u64,
+    pub sequence: u64,
+    pub kind: OrderEventKind,
+    /// Size taken off the order, including any hidden reserve, or the size of a
+    /// triggered stop order
+    pub size: u64,
+}
+
+/// Order event with decimal size
+#[derive(Debug, Encode, Decode, Clone, Copy, PartialEq, Eq)]
+pub struct DecimalOrderEvent {
+    pub id: u64,
+    pub sequence: u64,
+    pub kind: OrderEventKind,
+    pub size: Quantity,
+}
+
+impl DecimalOrderEvent {
+    /// Convert an order event in the integer sizes of the market
+    pub fn from_order_event(event: &OrderEvent, market: &Market) -> Self {
+        Self {
+            id: event.id,
+            sequence: event.sequence,
+            kind: event.kind,
+            size: market.decode_quantity(event.size),
+        }
+    }
+}
+
+#[derive(Debug, Encode, Decode, Default, Clone, PartialEq, Eq)]
+pub struct SubmitOrderResponse {
+    pub id: u64,
+    /// Sequence number of the event that accepted the order or amendment
+    pub sequence: u64,
+    pub trades: Vec<Trade>,
+    /// Orders changed while handling the request other than by trading, in sequence
+    /// order: stop orders triggered by its trades, resting and incoming orders hit by
+    /// self-trade prevention, and the unfilled size of incoming or triggered orders that
+    /// could not rest
+    pub order_events: Vec<OrderEvent>,
+    /// Stop orders activated by trades generated from this request
+    pub triggered_ids: Vec<u64>,
+}
+
+/// Trade with decimal price and quantity
+#[derive(Debug, Encode, Decode, Clone, Copy, PartialEq, Eq)]
+pub struct DecimalTrade {
+    pub id: u64,
+    pub sequence: u64,
+    pub aggressor_id: u64,
+    pub passive_id: u64,
+    pub price: Price,
+    pub size: Quantity,
+    pub side: Side,
+}
+
+impl DecimalTrade {
+    /// Convert a trade in the integer prices and sizes of the market
+    pub fn from_trade(trade: &Trade, market: &Market) -> Self {
+        Self {
+            id: trade.get_id(),
+            sequence: trade.get_sequence(),
+            aggressor_id: trade.get_aggressor_id(),
+            passive_id: trade.get_passive_id(),
+            price: market.decode_price(trade.get_price()),
+            size: market.decode_quantity(trade.get_size()),
+            side: trade.get_side(),
+        }
+    }
+}
+
+/// Order submission or amendment response with decimal trades
+#[derive(Debug, Encode, Decode, Default, Clone, PartialEq, Eq)]
+pub struct DecimalSubmitOrderResponse {
+    pub id: u64,
+    /// Sequence number of the event that accepted the order or amendment
+    pub sequence: u64,
+    pub trades: Vec<DecimalTrade>,
+    /// Orders cancelled or reduced while handling the request, other than by trading
+    pub order_events: Vec<DecimalOrderEvent>,
+    /// Stop orders activated by trades generated from this request
+    pub triggered_ids: Vec<u64>,
+}
+
+impl DecimalSubmitOrderResponse {
+    /// Convert a response in the integer prices and sizes of the market
+    pub fn from_response(response: &SubmitOrderResponse, market: &Market) -> Self {
+        Self {
+            id: response.id,
+            sequence: response.sequence,
+            trades: response
+                .trades
+                .iter()
+                .map(|trade| DecimalTrade::from_trade(trade, market))
+                .collect(),
+            order_events: response
+                .order_events
+                .iter()
+                .map(|event| DecimalOrderEvent::from_order_event(event, market))
+                .collect(),
+            triggered_ids: response.triggered_ids.clone(),
+        }
+    }
+}
+
+/// Best bid and best ask with decimal prices and quantities
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecimalBestQuote {
+    pub best_bid_price: Price,
+    pub best_ask_price: Price,
+    pub best_bid_size: Quantity,
+    pub best_ask_size: Quantity,
+}
+
+impl DecimalBestQuote {
+    /// Convert a best quote in the integer prices and sizes of the market
+    pub fn from_best_quote(best_quote: &BestQuote, market: &Market) -> Self {
+        Self {
+            best_bid_price: market.decode_price(best_quote.best_bid_price),
+            best_ask_price: market.decode_price(best_quote.best_ask_price),
+            best_bid_size: market.decode_quantity(best_quote.best_bid_size),
+            best_ask_size: market.decode_quantity(best_quote.best_ask_size),
+        }
+    }
+}
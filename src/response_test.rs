@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::decimal::{Price, Quantity};
+    use crate::market::Market;
+    use crate::order::Side;
+    use crate::order_book::BestQuote;
+    use crate::response::{
+        DecimalBestQuote, DecimalOrderEvent, DecimalSubmitOrderResponse, OrderEvent,
+        OrderEventKind, SubmitOrderResponse,
+    };
+    use crate::trade::Trade;
+
+    fn new_market() -> Market {
+        let mut market = Market::new("BTCUSD", 10_000_000, 1);
+        market.set_price_scale(2);
+        market.set_quantity_scale(4);
+        market
+    }
+
+    #[test]
+    fn test_decimal_submit_order_response_from_response() {
+        let response = SubmitOrderResponse {
+            id: 2,
+            sequence: 3,
+            trades: vec![Trade::new(1, 4, 2, 1, 10_150, 2_500, Side::Sell)],
+            order_events: vec![OrderEvent {
+                id: 4,
+                sequence: 5,
+                kind: OrderEventKind::Reduced,
+                size: 1_250,
+            }],
+            triggered_ids: vec![5],
+        };
+        let response = DecimalSubmitOrderResponse::from_response(&response, &new_market());
+        assert_eq!(response.id, 2);
+        assert_eq!(response.sequence, 3);
+        assert_eq!(
+            response.order_events,
+            vec![DecimalOrderEvent {
+                id: 4,
+                sequence: 5,
+                kind: OrderEventKind::Reduced,
+                size: Quantity::new(1_250, 4),
+            }]
+        );
+        assert_eq!(response.triggered_ids, vec![5]);
+        let trade = response.trades.first().unwrap();
+        assert_eq!(trade.id, 1);
+        assert_eq!(trade.sequence, 4);
+        assert_eq!(trade.aggressor_id, 2);
+        assert_eq!(trade.passive_id, 1);
+        assert_eq!(trade.price, Price::new(10_150, 2));
+        assert_eq!(trade.price.to_string(), "101.50");
+        assert_eq!(trade.size, Quantity::new(2_500, 4));
+        assert_eq!(trade.size.to_string(), "0.2500");
+        assert_eq!(trade.side, Side::Sell);
+    }
+
+    #[test]
+    fn test_decimal_best_quote_from_best_quote() {
+        let best_quote = BestQuote {
+            best_bid_price: 10_000,
+            best_ask_price: 10_001,
+            best_bid_size: 5,
+            best_ask_size: 20_000,
+        };
+        let best_quote = DecimalBestQuote::from_best_quote(&best_quote, &new_market());
+        assert_eq!(best_quote.best_bid_price.to_string(), "100.00");
+        assert_eq!(best_quote.best_ask_price.to_string(), "100.01");
+        assert_eq!(best_quote.best_bid_size.to_string(), "0.0005");
+        assert_eq!(best_quote.best_ask_size.to_string(), "2.0000");
+    }
+}
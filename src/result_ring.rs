@@ -0,0 +1,106 @@
+use crate::completion::RequestResult;
+use crate::error::ExchangeError;
+
+/// Number of request results the exchange keeps by default
+pub const DEFAULT_RESULT_CAPACITY: usize = 1 << 20;
+
+#[derive(Debug)]
+enum SlotState {
+    Free,
+    Pending,
+    Ready(RequestResult),
+}
+
+#[derive(Debug)]
+struct ResultSlot {
+    generation: u64,
+    state: SlotState,
+}
+
+/// Bounded storage for the results of requests, indexed by request ID
+///
+/// Request IDs are assigned in increasing order starting at 1 and map onto the slots in
+/// a ring. Each slot records the generation of the request that owns it, i.e. how many
+/// times the ring has wrapped, so a request whose slot has been reused by a newer
+/// request is reported as expired instead of receiving someone else's result
+#[derive(Debug)]
+pub struct ResultRing {
+    slots: Vec<ResultSlot>,
+}
+
+impl ResultRing {
+    /// Creates a ring able to hold the results of the given number of requests
+    pub fn with_capacity(capacity: usize) -> Self {
+        assert!(capacity > 0, "result capacity must be positive");
+        let mut slots = Vec::with_capacity(capacity);
+        slots.resize_with(capacity, || ResultSlot {
+            generation: 0,
+            state: SlotState::Free,
+        });
+        Self { slots }
+    }
+
+    /// Returns the number of slots in the ring
+    pub fn get_capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns the slot index and generation of a request ID, or `None` for ID 0,
+    /// which is never assigned to a request
+    fn locate(&self, request_id: u128) -> Option<(usize, u64)> {
+        let capacity = self.slots.len() as u128;
+        let position = request_id.checked_sub(1)?;
+        Some(((position % capacity) as usize, (position / capacity) as u64))
+    }
+
+    /// Claims the slot of a request before it is processed
+    ///
+    /// Any result still held by the previous owner of the slot is discarded
+    pub fn reserve(&mut self, request_id: u128) {
+        let Some((index, generation)) = self.locate(request_id) else {
+            return;
+        };
+        self.slots[index] = ResultSlot {
+            generation,
+            state: SlotState::Pending,
+        };
+    }
+
+    /// Stores the result of a request
+    ///
+    /// Returns false if the slot is no longer owned by the request, in which case the
+    /// result is discarded
+    pub fn store(&mut self, request_id: u128, result: RequestResult) -> bool {
+        let Some((index, generation)) = self.locate(request_id) else {
+            return false;
+        };
+        let slot = &mut self.slots[index];
+        if slot.generation != generation || !matches!(slot.state, SlotState::Pending) {
+            return false;
+        }
+        slot.state = SlotState::Ready(result);
+        true
+    }
+
+    /// Takes the result of a request, freeing its slot
+    ///
+    /// Returns `None` if the request is still being processed, and an error if the
+    /// result was overwritten by a newer request or has already been taken
+    pub fn take(&mut self, request_id: u128) -> Result<Option<RequestResult>, ExchangeError> {
+        let (index, generation) = self
+            .locate(request_id)
+            .ok_or(ExchangeError::ResultExpired)?;
+        let slot = &mut self.slots[index];
+        if slot.generation != generation {
+            return Err(ExchangeError::ResultExpired);
+        }
+        match std::mem::replace(&mut slot.state, SlotState::Free) {
+            SlotState::Ready(result) => Ok(Some(result)),
+            SlotState::Pending => {
+                slot.state = SlotState::Pending;
+                Ok(None)
+            }
+            SlotState::Free => Err(ExchangeError::ResultExpired),
+        }
+    }
+}
@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::ExchangeError;
+    use crate::request::RequestType;
+    use crate::result_ring::ResultRing;
+
+    #[test]
+    fn test_take_pending_result() {
+        let mut ring = ResultRing::with_capacity(4);
+        ring.reserve(1);
+        assert_eq!(ring.take(1), Ok(None));
+        // a pending slot stays pending after an early take
+        assert!(ring.store(1, Ok((RequestType::SubmitOrder, vec![1]))));
+        assert_eq!(
+            ring.take(1),
+            Ok(Some(Ok((RequestType::SubmitOrder, vec![1]))))
+        );
+    }
+
+    #[test]
+    fn test_result_taken_once() {
+        let mut ring = ResultRing::with_capacity(4);
+        ring.reserve(2);
+        assert!(ring.store(2, Err(ExchangeError::OrderNotFound)));
+        assert_eq!(ring.take(2), Ok(Some(Err(ExchangeError::OrderNotFound))));
+        assert_eq!(ring.take(2), Err(ExchangeError::ResultExpired));
+    }
+
+    #[test]
+    fn test_stale_id_expires_after_wrap() {
+        let mut ring = ResultRing::with_capacity(2);
+        assert_eq!(ring.get_capacity(), 2);
+        ring.reserve(1);
+        assert!(ring.store(1, Ok((RequestType::CancelOrder, vec![1]))));
+        // request 3 reuses the slot of request 1 before its result was taken
+        ring.reserve(3);
+        assert_eq!(ring.take(1), Err(ExchangeError::ResultExpired));
+        assert!(ring.store(3, Ok((RequestType::CancelOrder, vec![3]))));
+        assert_eq!(
+            ring.take(3),
+            Ok(Some(Ok((RequestType::CancelOrder, vec![3]))))
+        );
+    }
+
+    #[test]
+    fn test_store_into_reused_slot_is_discarded() {
+        let mut ring = ResultRing::with_capacity(2);
+        ring.reserve(1);
+        ring.reserve(3);
+        assert!(!ring.store(1, Ok((RequestType::SubmitOrder, vec![1]))));
+        assert_eq!(ring.take(3), Ok(None));
+    }
+
+    #[test]
+    fn test_take_unreserved_id_expires() {
+        let mut ring = ResultRing::with_capacity(2);
+        assert_eq!(ring.take(1), Err(ExchangeError::ResultExpired));
+        assert_eq!(ring.take(5), Err(ExchangeError::ResultExpired));
+    }
+
+    #[test]
+    #[should_panic(expected = "result capacity must be positive")]
+    fn test_zero_capacity() {
+        ResultRing::with_capacity(0);
+    }
+
+    #[test]
+    fn test_request_id_zero_expires() {
+        let mut ring = ResultRing::with_capacity(4);
+        ring.reserve(1);
+        ring.reserve(0);
+        assert!(!ring.store(0, Ok((RequestType::SubmitOrder, vec![0]))));
+        assert_eq!(ring.take(0), Err(ExchangeError::ResultExpired));
+        // request 1 keeps its slot
+        assert_eq!(ring.take(1), Ok(None));
+        assert!(ring.store(1, Ok((RequestType::SubmitOrder, vec![1]))));
+        assert_eq!(
+            ring.take(1),
+            Ok(Some(Ok((RequestType::SubmitOrder, vec![1]))))
+        );
+    }
+}
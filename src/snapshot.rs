@@ -0,0 +1,117 @@
+use std::fs::{self, File};
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+
+use bincode::{Decode, Encode};
+use serde::Serialize;
+
+use crate::crc32::crc32;
+use crate::error::ExchangeError;
+use crate::market::Market;
+use crate::order::Order;
+use crate::order_book::BestQuote;
+use crate::request::SubmitOrderRequest;
+
+/// Bytes at the start of every snapshot file
+pub const SNAPSHOT_MAGIC: [u8; 6] = *b"OBSNAP";
+
+/// Version of the snapshot format written by this build
+pub const SNAPSHOT_VERSION: u16 = 3;
+
+/// Size of the magic, version, payload length and checksum at the start of the file
+const HEADER_SIZE: usize = 20;
+
+/// Resting orders at a single price, in queue order
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, Serialize)]
+pub struct LevelSnapshot {
+    pub price: u64,
+    pub orders: Vec<Order>,
+}
+
+/// Complete state of an order book
+#[derive(Debug, Encode, Decode)]
+pub struct OrderBookSnapshot {
+    pub market: Market,
+    pub last_order_id: u64,
+    pub last_trade_id: u64,
+    pub last_trade_price: u64,
+    /// Sequence number of the last event in the book
+    pub last_sequence: u64,
+    /// Best quote when the snapshot was taken, which is recomputed from the price levels
+    /// when the book is restored
+    pub best_quote: BestQuote,
+    pub time: u64,
+    /// Bid price levels, best price first
+    pub bids: Vec<LevelSnapshot>,
+    /// Ask price levels, best price first
+    pub asks: Vec<LevelSnapshot>,
+    /// Pending expiries as expire time and order id, including those of orders that
+    /// have since left the book
+    pub expiries: Vec<(u64, u64)>,
+    /// Stop orders waiting to be triggered, by order id
+    pub stop_orders: Vec<(u64, SubmitOrderRequest)>,
+}
+
+/// State of an exchange at a position in its journal
+#[derive(Debug, Encode, Decode)]
+pub struct ExchangeSnapshot {
+    /// Sequence number of the last journal record included in the snapshot, or zero if
+    /// the exchange has no journal
+    pub journal_sequence: u64,
+    /// ID of the last request included in the snapshot
+    pub last_request_id: u128,
+    /// Order books of every market, in the order the markets were listed
+    pub order_books: Vec<OrderBookSnapshot>,
+}
+
+/// Write a snapshot to the given file
+///
+/// The file starts with `SNAPSHOT_MAGIC`, then the format version as a little endian
+/// `u16`, the length of the payload as a little endian `u64` and the CRC-32 of the
+/// payload as a little endian `u32`, followed by the payload: the snapshot encoded with
+/// bincode. The snapshot is written to a temporary file that replaces the given file
+/// once it is on disk, so a crash never leaves a partial snapshot behind
+pub fn write_snapshot(path: &Path, snapshot: &ExchangeSnapshot) -> Result<(), ExchangeError> {
+    let payload = bincode::encode_to_vec(snapshot, bincode::config::standard())
+        .map_err(|_| ExchangeError::PayloadEncoding)?;
+    let mut bytes = Vec::with_capacity(HEADER_SIZE + payload.len());
+    bytes.extend_from_slice(&SNAPSHOT_MAGIC);
+    bytes.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(&crc32(&payload).to_le_bytes());
+    bytes.extend_from_slice(&payload);
+    let temp_path = path.with_extension("tmp");
+    let mut file = File::create(&temp_path).map_err(|_| ExchangeError::SnapshotWrite)?;
+    file.write_all(&bytes)
+        .and_then(|_| file.sync_all())
+        .map_err(|_| ExchangeError::SnapshotWrite)?;
+    fs::rename(&temp_path, path).map_err(|_| ExchangeError::SnapshotWrite)
+}
+
+/// Read the snapshot in the given file
+///
+/// Returns `None` if the file does not exist
+pub fn read_snapshot(path: &Path) -> Result<Option<ExchangeSnapshot>, ExchangeError> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(_) => return Err(ExchangeError::SnapshotRead),
+    };
+    if bytes.len() < HEADER_SIZE || bytes[..SNAPSHOT_MAGIC.len()] != SNAPSHOT_MAGIC {
+        return Err(ExchangeError::SnapshotCorrupt);
+    }
+    let version = u16::from_le_bytes(bytes[6..8].try_into().unwrap());
+    if version != SNAPSHOT_VERSION {
+        return Err(ExchangeError::SnapshotVersion);
+    }
+    let length = u64::from_le_bytes(bytes[8..16].try_into().unwrap());
+    let checksum = u32::from_le_bytes(bytes[16..20].try_into().unwrap());
+    let payload = &bytes[HEADER_SIZE..];
+    if payload.len() as u64 != length || crc32(payload) != checksum {
+        return Err(ExchangeError::SnapshotCorrupt);
+    }
+    match bincode::decode_from_slice(payload, bincode::config::standard()) {
+        Ok((snapshot, _)) => Ok(Some(snapshot)),
+        Err(_) => Err(ExchangeError::SnapshotCorrupt),
+    }
+}
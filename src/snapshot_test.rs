@@ -0,0 +1,262 @@
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+    use std::path::PathBuf;
+
+    use crate::error::ExchangeError;
+    use crate::market::{LadderType, Market};
+    use crate::order::{OrderType, Side, TimeInForce};
+    use crate::order_book::OrderBook;
+    use crate::published_depth::DEFAULT_DEPTH_LEVELS;
+    use crate::request::{CancelOrderRequest, SubmitOrderRequest};
+    use crate::snapshot::{
+        ExchangeSnapshot, OrderBookSnapshot, SNAPSHOT_MAGIC, read_snapshot, write_snapshot,
+    };
+
+    fn limit_order(side: Side, price: u64, size: u64) -> SubmitOrderRequest {
+        SubmitOrderRequest {
+            symbol: "BTCUSD".to_string(),
+            price,
+            size,
+            side,
+            order_type: OrderType::Limit,
+            ..Default::default()
+        }
+    }
+
+    fn encode(snapshot: &OrderBookSnapshot) -> Vec<u8> {
+        bincode::encode_to_vec(snapshot, bincode::config::standard()).unwrap()
+    }
+
+    /// Returns a book with several levels per side, an iceberg order, a partly filled
+    /// order, a pending expiry and a stop order
+    fn busy_order_book(ladder_type: LadderType) -> OrderBook {
+        let mut market = Market::new("BTCUSD", 1_000, 1);
+        market.set_ladder_type(ladder_type);
+        let mut order_book = OrderBook::new(market);
+        order_book
+            .submit_order(&mut limit_order(Side::Buy, 99, 3))
+            .unwrap();
+        order_book
+            .submit_order(&mut limit_order(Side::Buy, 99, 4))
+            .unwrap();
+        order_book
+            .submit_order(&mut limit_order(Side::Buy, 97, 5))
+            .unwrap();
+        order_book
+            .submit_order(&mut SubmitOrderRequest {
+                display_size: 2,
+                ..limit_order(Side::Sell, 101, 6)
+            })
+            .unwrap();
+        order_book
+            .submit_order(&mut limit_order(Side::Sell, 101, 2))
+            .unwrap();
+        order_book
+            .submit_order(&mut SubmitOrderRequest {
+                time_in_force: TimeInForce::GoodTillDate,
+                expire_time: 500,
+                ..limit_order(Side::Sell, 104, 1)
+            })
+            .unwrap();
+        order_book
+            .submit_order(&mut SubmitOrderRequest {
+                order_type: OrderType::StopMarket,
+                trigger_price: 103,
+                ..limit_order(Side::Buy, 0, 2)
+            })
+            .unwrap();
+        // fills the displayed size of the iceberg order, which is requeued behind the
+        // other order at its price
+        order_book
+            .submit_order(&mut limit_order(Side::Buy, 101, 3))
+            .unwrap();
+        order_book.advance_clock(100).unwrap();
+        order_book
+    }
+
+    fn snapshot_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "order-book-{}-{}.snapshot",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_restore_matches_original() {
+        for ladder_type in [LadderType::Dense, LadderType::Sparse] {
+            let mut original = busy_order_book(ladder_type);
+            let snapshot = original.snapshot();
+            assert_eq!(snapshot.bids.len(), 2);
+            assert_eq!(snapshot.asks.len(), 2);
+            assert_eq!(snapshot.stop_orders.len(), 1);
+            let mut restored = OrderBook::restore(original.snapshot()).unwrap();
+            assert_eq!(encode(&restored.snapshot()), encode(&snapshot));
+            assert_eq!(restored.get_best_quote(), original.get_best_quote());
+            assert_eq!(
+                restored.get_published_quote().read().best_quote,
+                original.get_best_quote()
+            );
+            assert_eq!(
+                restored.get_published_depth().read(DEFAULT_DEPTH_LEVELS),
+                original.get_depth(DEFAULT_DEPTH_LEVELS)
+            );
+
+            // both books behave the same from here on, including queue priority, stop
+            // order triggering and expiry
+            let mut sweep = limit_order(Side::Buy, 104, 20);
+            assert_eq!(
+                restored.submit_order(&mut sweep.clone()),
+                original.submit_order(&mut sweep)
+            );
+            let cancel = CancelOrderRequest {
+                symbol: "BTCUSD".to_string(),
+                id: 3,
+            };
+            assert_eq!(
+                restored.cancel_order(&cancel),
+                original.cancel_order(&cancel)
+            );
+            assert_eq!(restored.advance_clock(600), original.advance_clock(600));
+            assert_eq!(encode(&restored.snapshot()), encode(&original.snapshot()));
+        }
+    }
+
+    #[test]
+    fn test_restore_rejects_misplaced_order() {
+        let original = busy_order_book(LadderType::Dense);
+        let mut snapshot = original.snapshot();
+        let order = snapshot.bids[0].orders.pop().unwrap();
+        snapshot.asks[0].orders.push(order);
+        assert!(matches!(
+            OrderBook::restore(snapshot),
+            Err(ExchangeError::SnapshotCorrupt)
+        ));
+
+        let mut snapshot = original.snapshot();
+        snapshot.bids[0].price = 1_000;
+        assert!(matches!(
+            OrderBook::restore(snapshot),
+            Err(ExchangeError::SnapshotCorrupt)
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_snapshot_file_round_trip() {
+        let path = snapshot_path("round-trip");
+        assert!(read_snapshot(&path).unwrap().is_none());
+        let order_book = busy_order_book(LadderType::Sparse);
+        let snapshot = ExchangeSnapshot {
+            journal_sequence: 12,
+            last_request_id: 10,
+            order_books: vec![order_book.snapshot()],
+        };
+        write_snapshot(&path, &snapshot).unwrap();
+
+        let restored = read_snapshot(&path).unwrap().unwrap();
+        assert_eq!(restored.journal_sequence, 12);
+        assert_eq!(restored.last_request_id, 10);
+        assert_eq!(restored.order_books.len(), 1);
+        assert_eq!(
+            encode(&restored.order_books[0]),
+            encode(&order_book.snapshot())
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_damaged_snapshot_file() {
+        let path = snapshot_path("damaged");
+        let snapshot = ExchangeSnapshot {
+            journal_sequence: 0,
+            last_request_id: 0,
+            order_books: vec![busy_order_book(LadderType::Dense).snapshot()],
+        };
+        write_snapshot(&path, &snapshot).unwrap();
+        let bytes = fs::read(&path).unwrap();
+
+        let mut damaged = bytes.clone();
+        let last = damaged.len() - 1;
+        damaged[last] ^= 0x01;
+        fs::write(&path, &damaged).unwrap();
+        assert!(matches!(
+            read_snapshot(&path),
+            Err(ExchangeError::SnapshotCorrupt)
+        ));
+
+        let mut truncated = bytes.clone();
+        truncated.truncate(bytes.len() - 1);
+        fs::write(&path, &truncated).unwrap();
+        assert!(matches!(
+            read_snapshot(&path),
+            Err(ExchangeError::SnapshotCorrupt)
+        ));
+
+        let mut future_version = bytes.clone();
+        future_version[SNAPSHOT_MAGIC.len()] += 1;
+        fs::write(&path, &future_version).unwrap();
+        assert!(matches!(
+            read_snapshot(&path),
+            Err(ExchangeError::SnapshotVersion)
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_restore_recomputes_best_quote() {
+        let original = busy_order_book(LadderType::Sparse);
+        let mut snapshot = original.snapshot();
+        snapshot.best_quote.best_bid_price = 1;
+        snapshot.best_quote.best_ask_size = 0;
+        let restored = OrderBook::restore(snapshot).unwrap();
+        assert_eq!(restored.get_best_quote(), original.get_best_quote());
+        assert_eq!(
+            restored.get_published_quote().read().best_quote,
+            original.get_best_quote()
+        );
+    }
+
+    #[test]
+    fn test_restore_rejects_stale_last_order_id() {
+        let original = busy_order_book(LadderType::Dense);
+        let snapshot = original.snapshot();
+        let max_order_id = snapshot
+            .bids
+            .iter()
+            .chain(&snapshot.asks)
+            .flat_map(|level| &level.orders)
+            .map(|order| order.get_id())
+            .max()
+            .unwrap();
+        let max_stop_id = snapshot
+            .stop_orders
+            .iter()
+            .map(|(id, _)| *id)
+            .max()
+            .unwrap();
+        assert!(max_stop_id > max_order_id);
+
+        // a resting order above the last order id
+        let mut snapshot = original.snapshot();
+        snapshot.stop_orders.clear();
+        snapshot.last_order_id = max_order_id - 1;
+        assert!(matches!(
+            OrderBook::restore(snapshot),
+            Err(ExchangeError::SnapshotCorrupt)
+        ));
+
+        // a stop order above the last order id
+        let mut snapshot = original.snapshot();
+        snapshot.last_order_id = max_stop_id - 1;
+        assert!(matches!(
+            OrderBook::restore(snapshot),
+            Err(ExchangeError::SnapshotCorrupt)
+        ));
+    }
+}
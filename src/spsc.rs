@@ -0,0 +1,227 @@
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+
+use crate::sync::{
+    Arc, AtomicBool, AtomicUsize, Condvar, Mutex, Ordering, UnsafeCell, fence, spin_loop, yield_now,
+};
+
+/// Number of times a side spins on the ring before yielding its core
+const SPIN_LIMIT: u32 = 64;
+
+/// Number of times the consumer polls an empty ring before going to sleep
+#[cfg(not(loom))]
+const POLL_LIMIT: u32 = 1 << 12;
+#[cfg(loom)]
+const POLL_LIMIT: u32 = 1;
+
+/// Waits between polls of the ring, spinning at first and then yielding the core so the
+/// other side can make progress when they share it
+fn backoff(attempt: u32) {
+    if attempt < SPIN_LIMIT {
+        spin_loop();
+    } else {
+        yield_now();
+    }
+}
+
+/// Aligns a value to its own cache line, so the producer and consumer do not invalidate
+/// each other's cache lines when they update their own index
+#[derive(Debug)]
+#[repr(align(64))]
+struct CachePadded<T>(T);
+
+/// Ring buffer shared by a producer and a consumer
+///
+/// `head` is the next slot to read and is only written by the consumer, `tail` is the
+/// next slot to write and is only written by the producer. Both only ever increase, and
+/// are mapped onto the slots with a mask, so the ring holds `tail - head` values
+struct Ring<T> {
+    head: CachePadded<AtomicUsize>,
+    tail: CachePadded<AtomicUsize>,
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    mask: usize,
+    closed: AtomicBool,
+    sleeping: AtomicBool,
+    lock: Mutex<()>,
+    wakeup: Condvar,
+}
+
+// values are only moved between the two threads through the slots, and each slot is
+// only accessed by one side at a time, as handed over by the head and tail indexes
+unsafe impl<T: Send> Send for Ring<T> {}
+unsafe impl<T: Send> Sync for Ring<T> {}
+
+impl<T> Ring<T> {
+    /// Wakes the consumer if it has gone to sleep waiting for a value
+    fn wake_consumer(&self) {
+        // pairs with the fence in `Consumer::pop`, so either the consumer sees the new
+        // tail before sleeping or the producer sees that it is sleeping
+        fence(Ordering::SeqCst);
+        if self.sleeping.load(Ordering::Relaxed) {
+            let _lock = self.lock.lock().unwrap();
+            self.wakeup.notify_one();
+        }
+    }
+}
+
+impl<T> Drop for Ring<T> {
+    fn drop(&mut self) {
+        // drop the values that were sent but never received
+        let head = self.head.0.load(Ordering::Relaxed);
+        let tail = self.tail.0.load(Ordering::Relaxed);
+        for index in head..tail {
+            self.slots[index & self.mask].with_mut(|slot| unsafe { (*slot).assume_init_drop() });
+        }
+    }
+}
+
+/// Creates a single producer, single consumer ring buffer
+///
+/// Memory for all values is allocated up front, with the capacity rounded up to a power
+/// of two
+pub fn channel<T>(capacity: usize) -> (Producer<T>, Consumer<T>) {
+    assert!(capacity > 0, "ring capacity must be positive");
+    let capacity = capacity.next_power_of_two();
+    let slots = (0..capacity)
+        .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+        .collect();
+    let ring = Arc::new(Ring {
+        head: CachePadded(AtomicUsize::new(0)),
+        tail: CachePadded(AtomicUsize::new(0)),
+        slots,
+        mask: capacity - 1,
+        closed: AtomicBool::new(false),
+        sleeping: AtomicBool::new(false),
+        lock: Mutex::new(()),
+        wakeup: Condvar::new(),
+    });
+    (
+        Producer {
+            ring: ring.clone(),
+            _not_sync: PhantomData,
+        },
+        Consumer {
+            ring,
+            _not_sync: PhantomData,
+        },
+    )
+}
+
+/// Sending half of a ring buffer
+///
+/// The producer can be moved to another thread but not shared, so there is only ever
+/// one thread writing to the ring
+pub struct Producer<T> {
+    ring: Arc<Ring<T>>,
+    _not_sync: PhantomData<Cell<()>>,
+}
+
+impl<T> Producer<T> {
+    /// Returns the number of values the ring can hold
+    pub fn get_capacity(&self) -> usize {
+        self.ring.slots.len()
+    }
+
+    /// Adds a value to the ring without blocking
+    ///
+    /// Returns the value if the ring is full
+    pub fn try_push(&self, value: T) -> Result<(), T> {
+        let ring = &self.ring;
+        let tail = ring.tail.0.load(Ordering::Relaxed);
+        let head = ring.head.0.load(Ordering::Acquire);
+        if tail.wrapping_sub(head) == ring.slots.len() {
+            return Err(value);
+        }
+        ring.slots[tail & ring.mask].with_mut(|slot| unsafe { (*slot).write(value) });
+        ring.tail.0.store(tail.wrapping_add(1), Ordering::Release);
+        ring.wake_consumer();
+        Ok(())
+    }
+
+    /// Adds a value to the ring, waiting while it is full
+    ///
+    /// Returns the value if the consumer has been dropped
+    pub fn push(&self, mut value: T) -> Result<(), T> {
+        let mut attempt = 0;
+        loop {
+            if self.ring.closed.load(Ordering::Acquire) {
+                return Err(value);
+            }
+            match self.try_push(value) {
+                Ok(()) => return Ok(()),
+                Err(rejected) => value = rejected,
+            }
+            backoff(attempt);
+            attempt = attempt.saturating_add(1);
+        }
+    }
+}
+
+impl<T> Drop for Producer<T> {
+    fn drop(&mut self) {
+        self.ring.closed.store(true, Ordering::Release);
+        self.ring.wake_consumer();
+    }
+}
+
+/// Receiving half of a ring buffer
+pub struct Consumer<T> {
+    ring: Arc<Ring<T>>,
+    _not_sync: PhantomData<Cell<()>>,
+}
+
+impl<T> Consumer<T> {
+    /// Takes the oldest value from the ring without blocking
+    pub fn try_pop(&self) -> Option<T> {
+        let ring = &self.ring;
+        let head = ring.head.0.load(Ordering::Relaxed);
+        let tail = ring.tail.0.load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+        let value = ring.slots[head & ring.mask].with(|slot| unsafe { (*slot).assume_init_read() });
+        ring.head.0.store(head.wrapping_add(1), Ordering::Release);
+        Some(value)
+    }
+
+    /// Takes the oldest value from the ring, blocking until one is available
+    ///
+    /// The consumer polls the ring for a while before going to sleep, so a busy ring is
+    /// never slowed down by waking up. Returns `None` once the producer has been dropped
+    /// and the ring is empty
+    pub fn pop(&self) -> Option<T> {
+        let ring = &self.ring;
+        loop {
+            for attempt in 0..POLL_LIMIT {
+                if let Some(value) = self.try_pop() {
+                    return Some(value);
+                }
+                if ring.closed.load(Ordering::Acquire) {
+                    // the producer may have pushed a last value before it was dropped
+                    return self.try_pop();
+                }
+                backoff(attempt);
+            }
+            let lock = ring.lock.lock().unwrap();
+            ring.sleeping.store(true, Ordering::Relaxed);
+            // pairs with the fence in `Ring::wake_consumer`
+            fence(Ordering::SeqCst);
+            let is_empty =
+                ring.head.0.load(Ordering::Relaxed) == ring.tail.0.load(Ordering::Acquire);
+            let lock = if is_empty && !ring.closed.load(Ordering::Acquire) {
+                ring.wakeup.wait(lock).unwrap()
+            } else {
+                lock
+            };
+            ring.sleeping.store(false, Ordering::Relaxed);
+            drop(lock);
+        }
+    }
+}
+
+impl<T> Drop for Consumer<T> {
+    fn drop(&mut self) {
+        self.ring.closed.store(true, Ordering::Release);
+    }
+}
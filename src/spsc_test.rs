@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    use crate::spsc;
+
+    #[test]
+    fn test_capacity_rounded_to_power_of_two() {
+        let (producer, _consumer) = spsc::channel::<u64>(5);
+        assert_eq!(producer.get_capacity(), 8);
+    }
+
+    #[test]
+    #[should_panic(expected = "ring capacity must be positive")]
+    fn test_zero_capacity() {
+        spsc::channel::<u64>(0);
+    }
+
+    #[test]
+    fn test_values_received_in_order() {
+        let (producer, consumer) = spsc::channel(4);
+        assert!(consumer.try_pop().is_none());
+        for value in 0..4 {
+            assert!(producer.try_push(value).is_ok());
+        }
+        // the ring is full until the consumer takes a value
+        assert_eq!(producer.try_push(4), Err(4));
+        assert_eq!(consumer.try_pop(), Some(0));
+        assert!(producer.try_push(4).is_ok());
+        for value in 1..5 {
+            assert_eq!(consumer.try_pop(), Some(value));
+        }
+        assert!(consumer.try_pop().is_none());
+    }
+
+    #[test]
+    fn test_pop_after_producer_dropped() {
+        let (producer, consumer) = spsc::channel(4);
+        producer.push(1).unwrap();
+        drop(producer);
+        assert_eq!(consumer.pop(), Some(1));
+        assert_eq!(consumer.pop(), None);
+    }
+
+    #[test]
+    fn test_push_after_consumer_dropped() {
+        let (producer, consumer) = spsc::channel(4);
+        drop(consumer);
+        assert_eq!(producer.push(1), Err(1));
+    }
+
+    #[test]
+    fn test_unreceived_values_dropped() {
+        let dropped = Arc::new(AtomicUsize::new(0));
+        struct Counted(Arc<AtomicUsize>);
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        let (producer, consumer) = spsc::channel(4);
+        for _ in 0..3 {
+            assert!(producer.try_push(Counted(dropped.clone())).is_ok());
+        }
+        drop(consumer.try_pop());
+        drop(producer);
+        drop(consumer);
+        assert_eq!(dropped.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn test_values_cross_threads_through_full_ring() {
+        let (producer, consumer) = spsc::channel(2);
+        let total = if cfg!(miri) { 100 } else { 10_000 };
+        let sender = thread::spawn(move || {
+            for value in 0..total {
+                producer.push(value).unwrap();
+            }
+        });
+        for expected in 0..total {
+            assert_eq!(consumer.pop(), Some(expected));
+        }
+        sender.join().unwrap();
+        assert_eq!(consumer.pop(), None);
+    }
+}
@@ -1,64 +1,100 @@
-use std::{collections::HashMap, sync::atomic::AtomicPtr};
+use std::time::Instant;
 
-use crate::{market::Market, order_book::OrderBook, request::RequestType};
-
-type RequestResults = HashMap<u128, Option<Result<(RequestType, Vec<u8>), String>>>;
+use crate::{
+    completion::{Completion, CompletionQueue, RequestResult},
+    result_ring::ResultRing,
+    sync::{Condvar, Mutex},
+};
 
+/// State shared between the exchange and its worker threads
+///
+/// Only request results cross threads through here, and every access is synchronised.
+/// Order books are owned exclusively by the worker thread of their market
 pub struct State {
-    markets: AtomicPtr<HashMap<String, Market>>,
-    order_books: AtomicPtr<HashMap<String, OrderBook>>,
-    request_results: AtomicPtr<RequestResults>,
+    request_results: Mutex<ResultRing>,
+    results_ready: Condvar,
+    completion_queue: CompletionQueue,
 }
 
 impl State {
-    /// Create instance of internal state
-    pub fn new() -> Self {
-        let mut request_results = HashMap::new();
-        for i in 0..10_000_000 {
-            request_results.insert(i + 1, None);
-        }
-        println!("pre-allocated memory!");
+    /// Create instance of internal state, keeping the results of up to the given number
+    /// of requests
+    pub fn new(result_capacity: usize) -> Self {
         Self {
-            markets: AtomicPtr::new(Box::into_raw(Box::new(HashMap::new()))),
-            order_books: AtomicPtr::new(Box::into_raw(Box::new(HashMap::new()))),
-            request_results: AtomicPtr::new(Box::into_raw(Box::new(request_results))),
+            request_results: Mutex::new(ResultRing::with_capacity(result_capacity)),
+            results_ready: Condvar::new(),
+            completion_queue: CompletionQueue::new(),
         }
     }
 
-    /// Get mutable reference to order books
-    #[allow(clippy::mut_from_ref)]
-    pub fn get_order_books(&self) -> &mut HashMap<String, OrderBook> {
-        unsafe { (*self.order_books.as_ptr()).as_mut().unwrap() }
-    }
-
-    /// Get mutable reference to order book for given symbol
-    #[allow(clippy::mut_from_ref)]
-    pub fn get_order_book_by_symbol(&self, symbol: &String) -> Result<&mut OrderBook, String> {
-        match self.get_order_books().get_mut(symbol) {
-            Some(order_book) => Ok(order_book),
-            None => Err("order book not found".to_string()),
-        }
+    /// Get the number of request results kept before the oldest are overwritten
+    pub fn get_result_capacity(&self) -> usize {
+        self.request_results.lock().unwrap().get_capacity()
     }
 
-    /// Get mutable reference to request results
-    #[allow(clippy::mut_from_ref)]
-    pub fn get_request_results(&self) -> &mut RequestResults {
-        unsafe { (*self.request_results.as_ptr()).as_mut().unwrap() }
+    /// Claim the result slot of a request whose result will be stored
+    pub fn reserve_request_result(&self, request_id: u128) {
+        self.request_results.lock().unwrap().reserve(request_id);
     }
 
-    /// Get mutable reference to markets
-    #[allow(clippy::mut_from_ref)]
-    pub fn get_markets(&self) -> &mut HashMap<String, Market> {
-        unsafe { (*self.markets.as_ptr()).as_mut().unwrap() }
+    /// Get the queue of completed requests that asked for queued completion
+    pub fn get_completion_queue(&self) -> &CompletionQueue {
+        &self.completion_queue
     }
-
-    /// Save request result for given request ID
-    #[allow(clippy::mut_from_ref)]
+    /// Save request result for given request ID, delivering it as requested by the client
     pub fn save_request_result(
         &self,
         request_id: u128,
-        result: Result<(RequestType, Vec<u8>), String>,
+        result: RequestResult,
+        completion: Completion,
     ) {
-        *self.get_request_results().get_mut(&request_id).unwrap() = Some(result);
+        match completion {
+            Completion::Store => {
+                // the result is discarded if its slot was reused by a newer request
+                if self
+                    .request_results
+                    .lock()
+                    .unwrap()
+                    .store(request_id, result)
+                {
+                    self.results_ready.notify_all();
+                }
+            }
+            Completion::Handle(sender) => {
+                // the client may have dropped its handle, in which case the result is discarded
+                let _ = sender.send(result);
+            }
+            Completion::Queue => self.completion_queue.push(request_id, result),
+            Completion::Callback(callback) => callback(request_id, result),
+            Completion::Discard => {}
+        }
+    }
+
+    /// Take the stored result for given request ID, blocking until it is available or
+    /// the deadline passes
+    ///
+    /// Without a deadline this blocks until the result is available. The result is
+    /// removed from the result table, so it is only handed out once. Taking it again,
+    /// or after its slot was reused by a newer request, gives a `ResultExpired` error
+    pub fn take_request_result(
+        &self,
+        request_id: u128,
+        deadline: Option<Instant>,
+    ) -> Option<RequestResult> {
+        let mut results = self.request_results.lock().unwrap();
+        loop {
+            match results.take(request_id) {
+                Ok(Some(result)) => return Some(result),
+                Ok(None) => {}
+                Err(err) => return Some(Err(err)),
+            }
+            results = match deadline {
+                Some(deadline) => {
+                    let timeout = deadline.checked_duration_since(Instant::now())?;
+                    self.results_ready.wait_timeout(results, timeout).unwrap().0
+                }
+                None => self.results_ready.wait(results).unwrap(),
+            };
+        }
     }
 }
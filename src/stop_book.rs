@@ -0,0 +1,91 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::order::Side;
+use crate::request::SubmitOrderRequest;
+
+#[derive(Debug, Default)]
+pub struct StopBook {
+    buy_stops: BTreeMap<(u64, u64), SubmitOrderRequest>,
+    sell_stops: BTreeMap<(u64, u64), SubmitOrderRequest>,
+    trigger_by_id: HashMap<u64, (Side, u64)>,
+}
+
+impl StopBook {
+    /// Creates an empty stop book
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the total number of stop orders waiting to be triggered
+    pub fn len(&self) -> usize {
+        self.trigger_by_id.len()
+    }
+
+    /// Returns true if there are no stop orders waiting to be triggered
+    pub fn is_empty(&self) -> bool {
+        self.trigger_by_id.is_empty()
+    }
+
+    /// Returns a copy of every stop order waiting to be triggered, in the order they were
+    /// added
+    pub fn get_orders(&self) -> Vec<(u64, SubmitOrderRequest)> {
+        let mut orders: Vec<(u64, SubmitOrderRequest)> = self
+            .buy_stops
+            .iter()
+            .chain(self.sell_stops.iter())
+            .map(|(&(_, id), request)| (id, request.clone()))
+            .collect();
+        orders.sort_by_key(|(id, _)| *id);
+        orders
+    }
+
+    /// Adds a stop order with the given id
+    ///
+    /// Buy stops trigger when the last trade price rises to their trigger price, and
+    /// sell stops trigger when it falls to their trigger price
+    pub fn add_order(&mut self, id: u64, request: SubmitOrderRequest) {
+        let trigger_price = request.trigger_price;
+        self.trigger_by_id.insert(id, (request.side, trigger_price));
+        match request.side {
+            Side::Buy => self.buy_stops.insert((trigger_price, id), request),
+            Side::Sell => self.sell_stops.insert((trigger_price, id), request),
+        };
+    }
+
+    /// Removes and returns the stop order with the given id
+    ///
+    /// Returns `None` if there is no stop order with this id
+    pub fn remove_order(&mut self, id: u64) -> Option<SubmitOrderRequest> {
+        match self.trigger_by_id.remove(&id) {
+            Some((Side::Buy, trigger_price)) => self.buy_stops.remove(&(trigger_price, id)),
+            Some((Side::Sell, trigger_price)) => self.sell_stops.remove(&(trigger_price, id)),
+            None => None,
+        }
+    }
+
+    /// Removes and returns every stop order triggered by the given last trade price
+    ///
+    /// Triggered orders are returned in the order they were added, so that activation
+    /// is deterministic
+    pub fn take_triggered_orders(&mut self, last_price: u64) -> Vec<(u64, SubmitOrderRequest)> {
+        let mut triggered = Vec::new();
+        while let Some((&(trigger_price, id), _)) = self.buy_stops.first_key_value() {
+            if trigger_price > last_price {
+                break;
+            }
+            let (_, request) = self.buy_stops.pop_first().unwrap();
+            self.trigger_by_id.remove(&id);
+            triggered.push((id, request));
+        }
+        while let Some((&(trigger_price, id), _)) = self.sell_stops.last_key_value() {
+            if trigger_price < last_price {
+                break;
+            }
+            let (_, request) = self.sell_stops.pop_last().unwrap();
+            self.trigger_by_id.remove(&id);
+            triggered.push((id, request));
+        }
+        triggered.sort_by_key(|(id, _)| *id);
+        triggered
+    }
+}
@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::order::{OrderType, Side};
+    use crate::request::SubmitOrderRequest;
+    use crate::stop_book::StopBook;
+
+    fn new_stop(side: Side, trigger_price: u64) -> SubmitOrderRequest {
+        SubmitOrderRequest {
+            symbol: "BTCUSD".to_string(),
+            size: 1,
+            side,
+            order_type: OrderType::StopMarket,
+            trigger_price,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_new() {
+        let stop_book = StopBook::new();
+        assert!(stop_book.is_empty());
+        assert_eq!(stop_book.len(), 0);
+    }
+
+    #[test]
+    fn test_take_triggered_orders() {
+        let mut stop_book = StopBook::new();
+        stop_book.add_order(1, new_stop(Side::Buy, 105));
+        stop_book.add_order(2, new_stop(Side::Buy, 103));
+        stop_book.add_order(3, new_stop(Side::Sell, 95));
+        stop_book.add_order(4, new_stop(Side::Sell, 97));
+        stop_book.add_order(5, new_stop(Side::Buy, 110));
+        assert_eq!(stop_book.len(), 5);
+        assert!(stop_book.take_triggered_orders(100).is_empty());
+        let triggered = stop_book.take_triggered_orders(105);
+        let ids: Vec<u64> = triggered.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        let triggered = stop_book.take_triggered_orders(90);
+        let ids: Vec<u64> = triggered.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec![3, 4]);
+        assert_eq!(stop_book.len(), 1);
+    }
+
+    #[test]
+    fn test_remove_order() {
+        let mut stop_book = StopBook::new();
+        stop_book.add_order(1, new_stop(Side::Buy, 105));
+        stop_book.add_order(2, new_stop(Side::Sell, 95));
+        assert_eq!(stop_book.remove_order(1).unwrap().trigger_price, 105);
+        assert!(stop_book.remove_order(1).is_none());
+        assert_eq!(stop_book.remove_order(2).unwrap().trigger_price, 95);
+        assert!(stop_book.is_empty());
+        assert!(stop_book.take_triggered_orders(105).is_empty());
+    }
+}
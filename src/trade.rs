@@ -0,0 +1,73 @@
+use bincode::{Decode, Encode};
+use serde::Serialize;
+
+use crate::order::Side;
+
+#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Trade {
+    id: u64,
+    sequence: u64,
+    aggressor_id: u64,
+    passive_id: u64,
+    price: u64,
+    size: u64,
+    side: Side,
+}
+
+impl Trade {
+    /// Creates a new trade with specified values
+    pub fn new(
+        id: u64,
+        sequence: u64,
+        aggressor_id: u64,
+        passive_id: u64,
+        price: u64,
+        size: u64,
+        side: Side,
+    ) -> Self {
+        Self {
+            id,
+            sequence,
+            aggressor_id,
+            passive_id,
+            price,
+            size,
+            side,
+        }
+    }
+
+    /// Returns the trade id, counting the trades of the market from one
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the sequence number of the fill among the events of the market
+    pub fn get_sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Returns the id of the incoming order that took liquidity
+    pub fn get_aggressor_id(&self) -> u64 {
+        self.aggressor_id
+    }
+
+    /// Returns the id of the resting order that provided liquidity
+    pub fn get_passive_id(&self) -> u64 {
+        self.passive_id
+    }
+
+    /// Returns the execution price
+    pub fn get_price(&self) -> u64 {
+        self.price
+    }
+
+    /// Returns the executed size
+    pub fn get_size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the side of the aggressor order
+    pub fn get_side(&self) -> Side {
+        self.side
+    }
+}
@@ -0,0 +1,28 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::order::Side;
+    use crate::trade::Trade;
+
+    #[test]
+    fn test_new() {
+        let trade = Trade::new(1, 7, 3, 2, 100, 5, Side::Buy);
+        assert_eq!(trade.get_id(), 1);
+        assert_eq!(trade.get_sequence(), 7);
+        assert_eq!(trade.get_aggressor_id(), 3);
+        assert_eq!(trade.get_passive_id(), 2);
+        assert_eq!(trade.get_price(), 100);
+        assert_eq!(trade.get_size(), 5);
+        assert_eq!(trade.get_side(), Side::Buy);
+    }
+
+    #[test]
+    fn test_encode_and_decode() {
+        let trade = Trade::new(1, 7, 3, 2, 100, 5, Side::Sell);
+        let bytes = bincode::encode_to_vec(trade, bincode::config::standard());
+        assert!(bytes.is_ok());
+        let (decoded_trade, _): (Trade, usize) =
+            bincode::decode_from_slice(&bytes.unwrap(), bincode::config::standard()).unwrap();
+        assert_eq!(decoded_trade, trade);
+    }
+}
@@ -0,0 +1,186 @@
+use std::{collections::HashMap, sync::mpsc};
+
+use crate::{
+    completion::RequestResult,
+    config::Transport,
+    error::ExchangeError,
+    order_book::OrderBook,
+    request::{
+        AdvanceClockRequest, AmendOrderRequest, CancelOrderRequest, MarketRequest, RequestRecord,
+        RequestType, SignedRequest, SubmitOrderRequest,
+    },
+    spsc,
+    state::State,
+    sync::Arc,
+};
+
+/// Read-only query run against an order book on the worker thread that owns it
+pub type BookQuery = Box<dyn FnOnce(Result<&OrderBook, ExchangeError>) + Send>;
+
+/// Message sent from the exchange to a worker thread
+pub enum WorkerMessage {
+    /// Request to be processed against the order book of its market
+    Request(SignedRequest),
+    /// Order book of a newly listed market, to be owned by the worker thread
+    AddOrderBook(Box<OrderBook>),
+    /// Query to run against the order book with the given symbol
+    Query(String, BookQuery),
+}
+
+/// Sending half of the transport to a worker thread
+pub enum WorkerSender {
+    Mpsc(mpsc::Sender<WorkerMessage>),
+    Spsc(spsc::Producer<WorkerMessage>),
+}
+
+impl WorkerSender {
+    /// Send a message to the worker thread
+    ///
+    /// With a ring buffer transport this waits while the ring is full
+    pub fn send(&self, message: WorkerMessage) -> Result<(), ExchangeError> {
+        let result = match self {
+            WorkerSender::Mpsc(sender) => sender.send(message).map_err(|_| ()),
+            WorkerSender::Spsc(producer) => producer.push(message).map_err(|_| ()),
+        };
+        result.map_err(|_| ExchangeError::ChannelClosed)
+    }
+}
+
+/// Receiving half of the transport to a worker thread
+pub enum WorkerReceiver {
+    Mpsc(mpsc::Receiver<WorkerMessage>),
+    Spsc(spsc::Consumer<WorkerMessage>),
+}
+
+impl WorkerReceiver {
+    /// Receive the next message, blocking until one is available
+    ///
+    /// Returns `None` once the exchange has been dropped
+    pub fn recv(&self) -> Option<WorkerMessage> {
+        match self {
+            WorkerReceiver::Mpsc(receiver) => receiver.recv().ok(),
+            WorkerReceiver::Spsc(consumer) => consumer.pop(),
+        }
+    }
+}
+
+/// Create the transport used to send messages to a worker thread
+pub fn channel(transport: Transport) -> (WorkerSender, WorkerReceiver) {
+    match transport {
+        Transport::Mpsc => {
+            let (sender, receiver) = mpsc::channel();
+            (WorkerSender::Mpsc(sender), WorkerReceiver::Mpsc(receiver))
+        }
+        Transport::Spsc { capacity } => {
+            let (producer, consumer) = spsc::channel(capacity);
+            (WorkerSender::Spsc(producer), WorkerReceiver::Spsc(consumer))
+        }
+    }
+}
+
+/// Worker thread state
+///
+/// Each worker exclusively owns the order books of the markets allocated to it, so
+/// order books are only ever accessed from a single thread. Other threads read them
+/// by sending a query, which runs between requests and so always sees a consistent book
+pub struct Worker {
+    order_books: HashMap<String, OrderBook>,
+    state: Arc<State>,
+}
+
+impl Worker {
+    /// Create a worker with no order books
+    pub fn new(state: Arc<State>) -> Self {
+        Self {
+            order_books: HashMap::new(),
+            state,
+        }
+    }
+
+    /// Process incoming messages until the exchange is dropped
+    pub fn run(mut self, receiver: WorkerReceiver) {
+        while let Some(message) = receiver.recv() {
+            self.handle_message(message);
+        }
+    }
+
+    /// Handle a message when it is received by the worker thread
+    pub fn handle_message(&mut self, message: WorkerMessage) {
+        match message {
+            WorkerMessage::Request(request) => self.handle_signed_request(request),
+            WorkerMessage::AddOrderBook(order_book) => {
+                let symbol = order_book.get_market().get_symbol().clone();
+                self.order_books.insert(symbol, *order_book);
+            }
+            WorkerMessage::Query(symbol, query) => match self.order_books.get(&symbol) {
+                Some(order_book) => query(Ok(order_book)),
+                None => query(Err(ExchangeError::OrderBookNotFound)),
+            },
+        }
+    }
+
+    /// Get mutable reference to order book for given symbol
+    fn get_order_book(&mut self, symbol: &String) -> Result<&mut OrderBook, ExchangeError> {
+        match self.order_books.get_mut(symbol) {
+            Some(order_book) => Ok(order_book),
+            None => Err(ExchangeError::OrderBookNotFound),
+        }
+    }
+
+    /// Handle a signed request
+    ///
+    /// The result is delivered according to the completion mode of the request
+    fn handle_signed_request(&mut self, request: SignedRequest) {
+        let result = match request.request {
+            RequestRecord::SubmitOrder(mut payload) => self.handle_submit_order(&mut payload),
+            RequestRecord::CancelOrder(mut payload) => self.handle_cancel_order(&mut payload),
+            RequestRecord::AmendOrder(payload) => self.handle_amend_order(&payload),
+            RequestRecord::AdvanceClock(payload) => self.handle_advance_clock(&payload),
+        };
+        self.state
+            .save_request_result(request.id, result, request.completion);
+    }
+
+    /// Handle new order submission requests
+    ///
+    /// The result payload is an encoded `SubmitOrderResponse` carrying the new order
+    /// id and any trades generated while matching
+    fn handle_submit_order(&mut self, request: &mut SubmitOrderRequest) -> RequestResult {
+        let order_book = self.get_order_book(request.get_symbol())?;
+        let result = order_book.submit_order(request)?;
+        let response = bincode::encode_to_vec(result, bincode::config::standard()).unwrap();
+        Ok((RequestType::SubmitOrder, response))
+    }
+
+    /// Handle order cancellation requests
+    ///
+    /// The result payload is an encoded `OrderEvent` carrying the cancelled order id and
+    /// the sequence number of the cancel
+    fn handle_cancel_order(&mut self, request: &mut CancelOrderRequest) -> RequestResult {
+        let order_book = self.get_order_book(request.get_symbol())?;
+        let result = order_book.cancel_order(request)?;
+        let response = bincode::encode_to_vec(result, bincode::config::standard()).unwrap();
+        Ok((RequestType::CancelOrder, response))
+    }
+
+    /// Handle order amendment requests
+    ///
+    /// The result payload is an encoded `SubmitOrderResponse` carrying any trades
+    /// generated if the amended order crossed the book
+    fn handle_amend_order(&mut self, request: &AmendOrderRequest) -> RequestResult {
+        let order_book = self.get_order_book(request.get_symbol())?;
+        let result = order_book.amend_order(request)?;
+        let response = bincode::encode_to_vec(result, bincode::config::standard()).unwrap();
+        Ok((RequestType::AmendOrder, response))
+    }
+
+    /// Handle order book clock requests
+    ///
+    /// The result payload is the list of orders that expired, as encoded `OrderEvent`s
+    fn handle_advance_clock(&mut self, request: &AdvanceClockRequest) -> RequestResult {
+        let order_book = self.get_order_book(request.get_symbol())?;
+        let expired = order_book.advance_clock(request.time)?;
+        let response = bincode::encode_to_vec(expired, bincode::config::standard()).unwrap();
+        Ok((RequestType::AdvanceClock, response))
+    }
+}
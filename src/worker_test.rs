@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::mpsc;
+    use std::time::Instant;
+
+    use crate::completion::Completion;
+    use crate::error::ExchangeError;
+    use crate::market::Market;
+    use crate::order::{OrderType, Side};
+    use crate::order_book::OrderBook;
+    use crate::request::{RequestRecord, RequestType, SignedRequest, SubmitOrderRequest};
+    use crate::state::State;
+    use crate::sync::Arc;
+    use crate::worker::{Worker, WorkerMessage};
+
+    fn submit_message(id: u128, symbol: &str, price: u64) -> WorkerMessage {
+        let request = SubmitOrderRequest {
+            symbol: symbol.to_string(),
+            price,
+            size: 1,
+            side: Side::Buy,
+            order_type: OrderType::Limit,
+            ..Default::default()
+        };
+        WorkerMessage::Request(SignedRequest {
+            id,
+            request: RequestRecord::SubmitOrder(request),
+            completion: Completion::Store,
+        })
+    }
+
+    #[test]
+    fn test_request_without_order_book() {
+        let state = Arc::new(State::new(4));
+        let mut worker = Worker::new(state.clone());
+        state.reserve_request_result(1);
+        worker.handle_message(submit_message(1, "BTCUSD", 100));
+        assert_eq!(
+            state.take_request_result(1, Some(Instant::now())),
+            Some(Err(ExchangeError::OrderBookNotFound))
+        );
+    }
+
+    #[test]
+    fn test_request_and_query_owned_order_book() {
+        let state = Arc::new(State::new(4));
+        let mut worker = Worker::new(state.clone());
+        let market = Market::new("BTCUSD", 1_000, 1);
+        worker.handle_message(WorkerMessage::AddOrderBook(Box::new(OrderBook::new(
+            market,
+        ))));
+        state.reserve_request_result(1);
+        worker.handle_message(submit_message(1, "BTCUSD", 100));
+        assert!(matches!(
+            state.take_request_result(1, Some(Instant::now())),
+            Some(Ok((RequestType::SubmitOrder, _)))
+        ));
+
+        let (sender, receiver) = mpsc::channel();
+        worker.handle_message(WorkerMessage::Query(
+            "BTCUSD".to_string(),
+            Box::new(move |order_book| {
+                sender
+                    .send(order_book.map(|order_book| order_book.get_best_bid_price()))
+                    .unwrap();
+            }),
+        ));
+        assert_eq!(receiver.recv().unwrap(), Ok(100));
+    }
+
+    #[test]
+    fn test_query_unknown_order_book() {
+        let mut worker = Worker::new(Arc::new(State::new(4)));
+        let (sender, receiver) = mpsc::channel();
+        worker.handle_message(WorkerMessage::Query(
+            "BTCUSD".to_string(),
+            Box::new(move |order_book| sender.send(order_book.is_ok()).unwrap()),
+        ));
+        assert!(!receiver.recv().unwrap());
+    }
+}